@@ -1,24 +1,118 @@
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
 
-use anyhow::Result;
-use futures_util::TryStreamExt;
-use log::{debug, info, warn};
-use reqwest::Client;
-use serde::Deserialize;
+use chrono::{Local, Utc};
+use log::{info, warn};
 use serde_json::Value;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_notification::NotificationExt;
-use tokio::{io::AsyncBufReadExt, sync::Mutex};
-use tokio_util::io::StreamReader;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
+
+use crate::event_hub::{HubEvent, OpenCodeEvent};
+use crate::notification_history::{record_and_sync, NewNotification};
+use crate::notification_rules::{
+    NotificationCandidate, NotificationDecision, NotificationSettings, RuleTrigger,
+};
+use crate::permission_requests::PendingPermission;
+use crate::session_activity::ActivityPhase;
+use crate::tray;
+use crate::webhooks::WebhookConfig;
+use crate::{DesktopRuntime, SettingsStore};
+
+/// How often busy sessions are checked against `longRunning` rules
+const LONG_RUNNING_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Events are only redelivered shortly after they happen, so old keys can be forgotten
+const DEDUPE_CAPACITY: usize = 1000;
+
+/// Agent and model last seen for a session, so rules can filter events that don't carry them
+#[derive(Default, Clone)]
+struct SessionContext {
+    agent: Option<String>,
+    model: Option<String>,
+}
+
+struct NotifierState {
+    /// Keys of events already notified (message ids, part ids, busy periods)
+    notified: RecentKeys,
+    /// Only sessions that are running; entries go when a session idles or is deleted
+    sessions: HashMap<String, SessionContext>,
+}
+
+impl Default for NotifierState {
+    fn default() -> Self {
+        Self {
+            notified: RecentKeys::new(DEDUPE_CAPACITY),
+            sessions: HashMap::new(),
+        }
+    }
+}
 
-use crate::DesktopRuntime;
+/// Notification rules and webhooks, parsed again only after settings.json is saved
+#[derive(Default)]
+struct CachedSettings {
+    revision: Option<u64>,
+    notifications: NotificationSettings,
+    webhooks: Vec<WebhookConfig>,
+}
 
-#[derive(Deserialize)]
-struct EventEnvelope {
-    #[serde(rename = "type")]
-    event_type: String,
-    #[serde(default)]
-    properties: Value,
+impl CachedSettings {
+    async fn refresh(&mut self, store: &SettingsStore) {
+        let revision = store.revision();
+        if self.revision == Some(revision) {
+            return;
+        }
+        let notifications = match store.notification_settings().await {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!("[desktop:notify] Failed to load notification settings: {err}");
+                return;
+            }
+        };
+        let webhooks = match store.webhooks().await {
+            Ok(webhooks) => webhooks,
+            Err(err) => {
+                warn!("[desktop:webhooks] Failed to load webhook settings: {err}");
+                return;
+            }
+        };
+        self.notifications = notifications;
+        self.webhooks = webhooks;
+        self.revision = Some(revision);
+    }
+}
+
+/// Set of recently seen keys that forgets the oldest once `capacity` is reached
+struct RecentKeys {
+    capacity: usize,
+    order: VecDeque<String>,
+    keys: HashSet<String>,
+}
+
+impl RecentKeys {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::with_capacity(capacity),
+            keys: HashSet::with_capacity(capacity),
+        }
+    }
+
+    /// Returns false if `key` was already present
+    fn insert(&mut self, key: String) -> bool {
+        if self.keys.contains(&key) {
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        self.keys.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
 }
 
 pub fn spawn_assistant_notifications(
@@ -26,205 +120,398 @@ pub fn spawn_assistant_notifications(
     runtime: DesktopRuntime,
 ) -> tauri::async_runtime::JoinHandle<()> {
     tauri::async_runtime::spawn(async move {
-        let client = Client::builder()
-            // Give SSE a very long overall timeout so idle periods don't abort the stream.
-            .timeout(Duration::from_secs(24 * 60 * 60))
-            .tcp_keepalive(Some(Duration::from_secs(30)))
-            .build()
-            .expect("failed to build reqwest client");
-
         let mut shutdown_rx = runtime.subscribe_shutdown();
-        let notified_messages = Mutex::new(HashSet::<String>::new());
+        let mut events = runtime.event_hub().subscribe();
+        let state = Mutex::new(NotifierState::default());
+        let mut settings = CachedSettings::default();
+        let mut long_running = tokio::time::interval(LONG_RUNNING_CHECK_INTERVAL);
 
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
-                    info!("[desktop:notify] Shutdown received, stopping SSE listener");
+                    info!("[desktop:notify] Shutdown received, stopping notification listener");
                     break;
                 }
-                _ = async {
-                    if let Err(err) = run_once(&app, &runtime, &client, &notified_messages).await {
-                        warn!("[desktop:notify] SSE loop error: {err:?}");
+                _ = long_running.tick() => {
+                    settings.refresh(runtime.settings()).await;
+                    check_long_running(&app, &runtime, &settings, &state).await;
+                }
+                received = events.recv() => match received {
+                    Ok(event) => match event.as_ref() {
+                        // OpenCode restarted or the stream dropped; replies sent meanwhile were missed
+                        HubEvent::Connected { directory } => {
+                            if runtime.permission_requests().clear_directory(directory) {
+                                tray::refresh(&app);
+                            }
+                        }
+                        HubEvent::Event(event) => {
+                            settings.refresh(runtime.settings()).await;
+                            handle_event(&app, &runtime, &settings, event, &state).await;
+                        }
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("[desktop:notify] Event hub lagged; dropped {skipped} events");
                     }
-                    tokio::time::sleep(Duration::from_secs(2)).await;
-                } => {}
+                    Err(RecvError::Closed) => break,
+                }
             }
         }
     })
 }
 
-async fn run_once(
+async fn handle_event(
     app: &AppHandle,
     runtime: &DesktopRuntime,
-    client: &Client,
-    notified_messages: &Mutex<HashSet<String>>,
-) -> Result<()> {
-    let opencode = runtime.opencode_manager();
-
-    let port = match opencode.current_port() {
-        Some(port) => port,
-        None => {
-            warn!("[desktop:notify] OpenCode port unavailable; will retry");
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            return Ok(());
-        }
-    };
-
-    let prefix = opencode.api_prefix();
-    let mut url = format!("http://127.0.0.1:{port}{}/event", prefix);
-
-    if let Some(dir) = opencode.get_working_directory().to_str().map(|s| s.to_string()) {
-        let mut parsed = reqwest::Url::parse(&url)?;
-        parsed
-            .query_pairs_mut()
-            .append_pair("directory", &dir);
-        url = parsed.to_string();
+    settings: &CachedSettings,
+    event: &OpenCodeEvent,
+    state: &Mutex<NotifierState>,
+) {
+    if runtime.permission_requests().observe(event) {
+        tray::refresh(app);
+    }
+    if let Some(session_id) = finished_session(event) {
+        state.lock().await.sessions.remove(&session_id);
     }
 
-    debug!("[desktop:notify] Connecting SSE for notifications: {url}");
-
-    let response = client
-        .get(&url)
-        .header("accept", "text/event-stream")
-        .header("accept-encoding", "identity")
-        .send()
-        .await?;
+    let Some((dedupe_key, mut candidate)) = candidate_for_event(event, state).await else {
+        return;
+    };
 
-    debug!(
-        "[desktop:notify] SSE response status={} headers={:?}",
-        response.status(),
-        response.headers()
-    );
+    {
+        let mut state = state.lock().await;
+        if !state.notified.insert(dedupe_key) {
+            return;
+        }
+        // Events like permission requests don't say which agent is running; use what the session last reported
+        if let Some(context) = candidate
+            .session_id
+            .as_ref()
+            .and_then(|id| state.sessions.get(id))
+        {
+            candidate.agent = candidate.agent.take().or_else(|| context.agent.clone());
+            candidate.model = candidate.model.take().or_else(|| context.model.clone());
+        }
+    }
 
-    if !response.status().is_success() {
-        warn!(
-            "[desktop:notify] SSE connect failed with status {}",
-            response.status()
-        );
-        tokio::time::sleep(Duration::from_secs(2)).await;
-        return Ok(());
+    fire_webhooks(runtime, settings, &candidate);
+    let decision = settings
+        .notifications
+        .evaluate(&candidate, Local::now().time());
+    if let Some(decision) = decision {
+        deliver(app, runtime, &settings.notifications, &candidate, decision).await;
     }
+}
 
-    let stream = response
-        .bytes_stream()
-        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err));
-    let mut reader = StreamReader::new(stream);
-    let mut buf = Vec::new();
-    let mut data_lines: Vec<String> = Vec::new();
-
-    loop {
-        buf.clear();
-        let bytes_read = match reader.read_until(b'\n', &mut buf).await {
-            Ok(n) => n,
-            Err(err) => {
-                warn!("[desktop:notify] Read error in SSE stream: {err:?}");
-                return Err(err.into());
+/// Translate an OpenCode event into a notification candidate and the key used to notify it only once
+async fn candidate_for_event(
+    event: &OpenCodeEvent,
+    state: &Mutex<NotifierState>,
+) -> Option<(String, NotificationCandidate)> {
+    let properties = &event.properties;
+    match event.event_type.as_str() {
+        "message.updated" => {
+            let info = properties.get("info")?;
+            let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
+            if role != "assistant" {
+                return None;
             }
-        };
-        if bytes_read == 0 {
-            break;
-        }
 
-        let line = match std::str::from_utf8(&buf) {
-            Ok(s) => s.trim_end_matches(&['\r', '\n'][..]).to_string(),
-            Err(err) => {
-                warn!("[desktop:notify] Non-UTF8 SSE chunk: {err}");
-                continue;
+            let session_id = info.get("sessionID").and_then(Value::as_str);
+            let agent = non_empty(info.get("mode"));
+            let model = non_empty(info.get("modelID"));
+            if let Some(id) = session_id {
+                state.lock().await.sessions.insert(
+                    id.to_string(),
+                    SessionContext {
+                        agent: agent.clone(),
+                        model: model.clone(),
+                    },
+                );
             }
-        };
 
-        if line.is_empty() {
-            if data_lines.is_empty() {
-                continue;
+            let finish = info.get("finish").and_then(Value::as_str);
+            if finish != Some("stop") {
+                return None;
             }
-            let raw = data_lines.join("\n");
-            data_lines.clear();
-
-            match serde_json::from_str::<EventEnvelope>(&raw) {
-                Ok(event) => handle_event(app, event, notified_messages).await,
-                Err(err) => {
-                    warn!("[desktop:notify] Failed to parse SSE data: {err}; raw={raw}");
-                }
+            let message_id = info.get("id").and_then(Value::as_str)?;
+
+            let raw_mode = agent.as_deref().unwrap_or("agent");
+            let raw_model = model.as_deref().unwrap_or("assistant");
+            Some((
+                format!("message:{message_id}"),
+                NotificationCandidate {
+                    trigger: RuleTrigger::AssistantReady,
+                    directory: event.directory.clone(),
+                    session_id: session_id.map(str::to_string),
+                    title: format!("{} agent is ready", format_mode(raw_mode)),
+                    body: format!("{} completed the task", format_model_id(raw_model)),
+                    agent,
+                    model,
+                    busy_minutes: None,
+                },
+            ))
+        }
+        // `permission.updated` on older OpenCode releases, `permission.asked` on newer ones
+        "permission.updated" | "permission.asked" => {
+            let permission = PendingPermission::from_event(event)?;
+            Some((
+                format!("permission:{}", permission.id),
+                NotificationCandidate {
+                    trigger: RuleTrigger::PermissionRequest,
+                    directory: event.directory.clone(),
+                    session_id: Some(permission.session_id.clone()),
+                    agent: None,
+                    model: None,
+                    busy_minutes: None,
+                    title: format!("Allow {}?", permission.tool),
+                    // Desktop notifications can't carry buttons, so answers go through the tray menu
+                    body: format!(
+                        "{}\nApprove or deny from the OpenChamber tray menu.",
+                        truncate(&permission.label(), 200)
+                    ),
+                },
+            ))
+        }
+        "message.part.updated" => {
+            let part = properties.get("part")?;
+            if part.get("type").and_then(Value::as_str) != Some("tool") {
+                return None;
             }
-            continue;
+            let tool_state = part.get("state")?;
+            if tool_state.get("status").and_then(Value::as_str) != Some("error") {
+                return None;
+            }
+            let part_id = part.get("id").and_then(Value::as_str)?;
+            let tool = non_empty(part.get("tool")).unwrap_or_else(|| "Tool".to_string());
+            let error = non_empty(tool_state.get("error"))
+                .unwrap_or_else(|| "Tool call failed".to_string());
+            Some((
+                format!("tool-error:{part_id}"),
+                NotificationCandidate {
+                    trigger: RuleTrigger::ToolError,
+                    directory: event.directory.clone(),
+                    session_id: non_empty(part.get("sessionID")),
+                    agent: None,
+                    model: None,
+                    busy_minutes: None,
+                    title: format!("{tool} failed"),
+                    body: truncate(&error, 200),
+                },
+            ))
         }
-
-        if let Some(rest) = line.strip_prefix("data:") {
-            data_lines.push(rest.trim_start().to_string());
+        "session.error" => {
+            let error = properties.get("error");
+            let name = error.and_then(|e| non_empty(e.get("name")));
+            // The user stopping a session is not worth a notification
+            if name.as_deref() == Some("MessageAbortedError") {
+                return None;
+            }
+            let message = error
+                .and_then(|e| e.get("data"))
+                .and_then(|data| non_empty(data.get("message")))
+                .or(name)
+                .unwrap_or_else(|| "The session stopped with an error".to_string());
+            let session_id = non_empty(properties.get("sessionID"));
+            Some((
+                format!(
+                    "session-error:{}:{}",
+                    session_id.as_deref().unwrap_or_default(),
+                    event.id.as_deref().unwrap_or(&message)
+                ),
+                NotificationCandidate {
+                    trigger: RuleTrigger::SessionError,
+                    directory: event.directory.clone(),
+                    session_id,
+                    agent: None,
+                    model: None,
+                    busy_minutes: None,
+                    title: "Session error".to_string(),
+                    body: truncate(&message, 200),
+                },
+            ))
         }
+        _ => None,
     }
+}
 
-    Ok(())
+/// Session whose context is no longer needed: it went idle or was deleted
+fn finished_session(event: &OpenCodeEvent) -> Option<String> {
+    let properties = &event.properties;
+    match event.event_type.as_str() {
+        "session.idle" => non_empty(properties.get("sessionID")),
+        "session.status" => {
+            let status = properties.get("status").and_then(|s| s.get("type"));
+            if status.and_then(Value::as_str) != Some("idle") {
+                return None;
+            }
+            non_empty(properties.get("sessionID"))
+        }
+        "session.deleted" => non_empty(properties.get("info").and_then(|info| info.get("id"))),
+        _ => None,
+    }
 }
 
-async fn handle_event(
+/// Notify once per busy period and `longRunning` rule, as each rule's threshold is passed
+async fn check_long_running(
     app: &AppHandle,
-    event: EventEnvelope,
-    notified_messages: &Mutex<HashSet<String>>,
+    runtime: &DesktopRuntime,
+    settings: &CachedSettings,
+    state: &Mutex<NotifierState>,
 ) {
-    if event.event_type.as_str() != "message.updated" {
-        return;
-    }
-
-    let Some(info) = event.properties.get("info") else {
+    let Some(threshold) = settings.notifications.long_running_threshold() else {
         return;
     };
 
-    let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
-    if role != "assistant" {
-        return;
+    let now = Utc::now();
+    for activity in runtime.session_activity().snapshot(None).await {
+        if activity.phase != ActivityPhase::Busy {
+            continue;
+        }
+        let Some(busy_since) = activity.busy_since else {
+            continue;
+        };
+        let busy_minutes = (now - busy_since).num_minutes().max(0) as u64;
+        if busy_minutes < threshold {
+            continue;
+        }
+
+        let context = state
+            .lock()
+            .await
+            .sessions
+            .get(&activity.session_id)
+            .cloned()
+            .unwrap_or_default();
+        let candidate = NotificationCandidate {
+            trigger: RuleTrigger::LongRunning,
+            directory: activity.directory.clone(),
+            session_id: Some(activity.session_id.clone()),
+            title: format!(
+                "{} agent is still working",
+                format_mode(context.agent.as_deref().unwrap_or("agent"))
+            ),
+            body: format!("Busy for {busy_minutes} minutes"),
+            agent: context.agent,
+            model: context.model,
+            busy_minutes: Some(busy_minutes),
+        };
+
+        // A rule counts as notified only once it matched, so a longer threshold still fires later
+        let rule = {
+            let mut state = state.lock().await;
+            settings
+                .notifications
+                .rules
+                .iter()
+                .enumerate()
+                .filter(|(_, rule)| rule.matches(&candidate))
+                .find(|(index, rule)| {
+                    let rule_key = if rule.id.is_empty() {
+                        format!("#{index}")
+                    } else {
+                        rule.id.clone()
+                    };
+                    state.notified.insert(format!(
+                        "long-running:{}:{}:{}:{}",
+                        rule_key,
+                        activity.directory,
+                        activity.session_id,
+                        busy_since.timestamp_millis()
+                    ))
+                })
+                .map(|(_, rule)| rule)
+        };
+        let Some(rule) = rule else {
+            continue;
+        };
+
+        fire_webhooks(runtime, settings, &candidate);
+        let decision = settings
+            .notifications
+            .decide(rule, &candidate, Local::now().time());
+        if let Some(decision) = decision {
+            deliver(app, runtime, &settings.notifications, &candidate, decision).await;
+        }
     }
+}
 
-    let finish = info.get("finish").and_then(Value::as_str);
-    if finish != Some("stop") {
-        return;
+/// Webhooks see every event regardless of notification rules, focus or quiet hours
+fn fire_webhooks(
+    runtime: &DesktopRuntime,
+    settings: &CachedSettings,
+    candidate: &NotificationCandidate,
+) {
+    if !settings.webhooks.is_empty() {
+        runtime
+            .webhooks()
+            .dispatch(settings.webhooks.clone(), candidate);
     }
+}
 
-    let message_id = match info.get("id").and_then(Value::as_str) {
-        Some(id) => id.to_string(),
-        None => return,
-    };
+async fn deliver(
+    app: &AppHandle,
+    runtime: &DesktopRuntime,
+    settings: &NotificationSettings,
+    candidate: &NotificationCandidate,
+    decision: NotificationDecision,
+) {
+    let should_notify = !settings.only_when_unfocused
+        || app
+            .get_webview_window("main")
+            .map(|window| {
+                let focused = window.is_focused().unwrap_or(false);
+                let minimized = window.is_minimized().unwrap_or(false);
+                // Only notify when the app is not in the foreground or is minimized
+                !focused || minimized
+            })
+            .unwrap_or(true);
+
+    if !should_notify {
+        return;
+    }
 
-    {
-        let mut notified = notified_messages.lock().await;
-        if notified.contains(&message_id) {
-            return;
-        }
-        notified.insert(message_id.clone());
+    info!(
+        "[desktop:notify] Rule {} matched {:?} in {}",
+        decision.rule_id, candidate.trigger, candidate.directory
+    );
+    let mut builder = app
+        .notification()
+        .builder()
+        .title(&candidate.title)
+        .body(&candidate.body);
+    if let Some(sound) = decision.sound {
+        builder = builder.sound(sound);
     }
+    let _ = builder.show();
+
+    record_and_sync(
+        app,
+        runtime.notification_history(),
+        NewNotification {
+            title: candidate.title.clone(),
+            body: candidate.body.clone(),
+            session_id: candidate.session_id.clone(),
+            directory: Some(candidate.directory.clone()).filter(|dir| !dir.is_empty()),
+        },
+    )
+    .await;
+}
 
-    let raw_mode = info
-        .get("mode")
-        .and_then(Value::as_str)
-        .filter(|s| !s.is_empty())
-        .unwrap_or("agent");
-    let raw_model = info
-        .get("modelID")
+fn non_empty(value: Option<&Value>) -> Option<String> {
+    value
         .and_then(Value::as_str)
+        .map(str::trim)
         .filter(|s| !s.is_empty())
-        .unwrap_or("assistant");
-
-    let title = format!("{} agent is ready", format_mode(raw_mode));
-    let body = format!("{} completed the task", format_model_id(raw_model));
-
-    let should_notify = app
-        .get_webview_window("main")
-        .map(|window| {
-            let focused = window.is_focused().unwrap_or(false);
-            let minimized = window.is_minimized().unwrap_or(false);
-            // Only notify when the app is not in the foreground or is minimized
-            !focused || minimized
-        })
-        .unwrap_or(true);
-
-    if should_notify {
-        let _ = app
-            .notification()
-            .builder()
-            .title(title)
-            .body(body)
-            .sound("Glass")
-            .show();
+        .map(str::to_string)
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    let mut chars = value.chars();
+    let truncated: String = chars.by_ref().take(max_chars).collect();
+    if chars.next().is_some() {
+        format!("{truncated}…")
+    } else {
+        truncated
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::commands::terminal::TerminalState;
 use crate::{DesktopRuntime, SettingsStore};
 use crate::path_utils::expand_tilde_path;
 use anyhow::{anyhow, Context, Result};
@@ -1398,16 +1399,48 @@ pub async fn remove_git_worktree(
     directory: String,
     path_str: String,
     force: Option<bool>,
+    close_terminals: Option<bool>,
     state: State<'_, DesktopRuntime>,
+    terminals: State<'_, TerminalState>,
 ) -> Result<(), String> {
     let root = validate_git_path(&directory, state.settings())
         .await
         .map_err(|e| e.to_string())?;
+    let force = force.unwrap_or(false);
+
+    // Terminals opened in the worktree lose their directory; refuse while they are still running something
+    let worktree = root.join(&path_str);
+    let sessions = terminals.project_sessions(&worktree);
+    let busy = sessions.iter().filter(|(_, jobs)| *jobs > 0).count();
+    if busy > 0 && !close_terminals.unwrap_or(false) {
+        return Err(format!(
+            "{busy} terminal{} in this worktree still running commands; stop them or close them with the worktree",
+            if busy == 1 { " is" } else { "s are" }
+        ));
+    }
+    // Resolved while the directory still exists; afterwards only stored paths can be compared
+    let projects = vec![
+        worktree.clone(),
+        std::fs::canonicalize(&worktree).unwrap_or_else(|_| worktree.clone()),
+    ];
+
     let mut args = vec!["worktree", "remove", &path_str];
-    if force.unwrap_or(false) {
+    if force {
         args.push("--force");
     }
     run_git(&args, &root).await.map_err(|e| e.to_string())?;
+
+    // Terminals opened while git was running are picked up by their stored project path
+    let mut session_ids: Vec<String> = sessions.into_iter().map(|(id, _)| id).collect();
+    for id in terminals.sessions_with_project(&projects) {
+        if !session_ids.contains(&id) {
+            session_ids.push(id);
+        }
+    }
+    let closed = terminals.close_sessions(&session_ids);
+    if closed > 0 {
+        info!("Closed {closed} terminal(s) in removed worktree {}", worktree.display());
+    }
     Ok(())
 }
 
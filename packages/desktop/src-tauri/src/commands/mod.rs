@@ -1,7 +1,12 @@
 pub mod files;
 pub mod git;
 pub mod logs;
+pub mod opencode;
+pub mod permission_requests;
 pub mod permissions;
+pub mod remote_access;
+pub mod session_activity;
 pub mod settings;
 pub mod terminal;
 pub mod notifications;
+pub mod webhooks;
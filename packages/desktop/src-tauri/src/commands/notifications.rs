@@ -1,18 +1,26 @@
 use serde::Deserialize;
-use tauri::{AppHandle, Runtime};
+use tauri::{AppHandle, State};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::notification_history::{
+    record_and_sync, sync_badge, NewNotification, NotificationRecord,
+};
+use crate::DesktopRuntime;
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NotificationPayload {
     pub title: Option<String>,
     pub body: Option<String>,
+    pub session_id: Option<String>,
+    pub directory: Option<String>,
 }
 
 #[tauri::command]
-pub async fn desktop_notify<R: Runtime>(
-    app: AppHandle<R>,
+pub async fn desktop_notify(
+    app: AppHandle,
     payload: Option<NotificationPayload>,
+    state: State<'_, DesktopRuntime>,
 ) -> Result<bool, String> {
     let title = payload
         .as_ref()
@@ -31,7 +39,58 @@ pub async fn desktop_notify<R: Runtime>(
         .sound("Glass")
         .show()
     {
-        Ok(_) => Ok(true),
+        Ok(_) => {
+            record_and_sync(
+                &app,
+                state.notification_history(),
+                NewNotification {
+                    title: title.to_string(),
+                    body: body.to_string(),
+                    session_id: payload.as_ref().and_then(|p| p.session_id.clone()),
+                    directory: payload.as_ref().and_then(|p| p.directory.clone()),
+                },
+            )
+            .await;
+            Ok(true)
+        }
         Err(e) => Err(e.to_string()),
     }
 }
+
+/// Notifications shown so far, newest first
+#[tauri::command]
+pub async fn list_notification_history(
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<NotificationRecord>, String> {
+    Ok(state.notification_history().list())
+}
+
+/// Mark the given notifications read, or all of them when `ids` is omitted; returns the unread count
+#[tauri::command]
+pub async fn mark_notifications_read(
+    app: AppHandle,
+    ids: Option<Vec<String>>,
+    state: State<'_, DesktopRuntime>,
+) -> Result<usize, String> {
+    let history = state.notification_history();
+    history
+        .mark_read(ids.as_deref())
+        .await
+        .map_err(|e| format!("Failed to update notification history: {}", e))?;
+    sync_badge(&app, history);
+    Ok(history.unread_count())
+}
+
+#[tauri::command]
+pub async fn clear_notification_history(
+    app: AppHandle,
+    state: State<'_, DesktopRuntime>,
+) -> Result<(), String> {
+    let history = state.notification_history();
+    history
+        .clear()
+        .await
+        .map_err(|e| format!("Failed to clear notification history: {}", e))?;
+    sync_badge(&app, history);
+    Ok(())
+}
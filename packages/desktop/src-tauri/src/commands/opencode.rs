@@ -0,0 +1,96 @@
+use log::{info, warn};
+use serde::Deserialize;
+use tauri::State;
+
+use crate::opencode_installer::{self, InstallRequest, InstalledVersion, InstallerStatus};
+use crate::DesktopRuntime;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallOpenCodePayload {
+    pub version: Option<String>,
+    pub mirror: Option<String>,
+    pub sha256: Option<String>,
+    pub activate: Option<bool>,
+}
+
+/// List managed OpenCode installs and the current/previous selection
+#[tauri::command]
+pub async fn get_opencode_installations() -> Result<InstallerStatus, String> {
+    opencode_installer::status()
+        .await
+        .map_err(|e| format!("Failed to read OpenCode installations: {}", e))
+}
+
+/// Download and verify an OpenCode release (pinned version by default)
+#[tauri::command]
+pub async fn install_opencode_version(
+    payload: Option<InstallOpenCodePayload>,
+    state: State<'_, DesktopRuntime>,
+) -> Result<InstalledVersion, String> {
+    let request = payload
+        .map(|p| InstallRequest {
+            version: p.version,
+            mirror: p.mirror,
+            sha256: p.sha256,
+            activate: p.activate.unwrap_or(true),
+        })
+        .unwrap_or(InstallRequest {
+            activate: true,
+            ..Default::default()
+        });
+    let activate = request.activate;
+
+    let installed = opencode_installer::install(request)
+        .await
+        .map_err(|e| format!("Failed to install OpenCode: {}", e))?;
+
+    if activate {
+        apply_selected_binary(&state).await?;
+    }
+
+    Ok(installed)
+}
+
+/// Switch to an already installed OpenCode version
+#[tauri::command]
+pub async fn select_opencode_version(
+    version: String,
+    state: State<'_, DesktopRuntime>,
+) -> Result<InstallerStatus, String> {
+    opencode_installer::select(&version)
+        .await
+        .map_err(|e| format!("Failed to select OpenCode {}: {}", version, e))?;
+    apply_selected_binary(&state).await?;
+    get_opencode_installations().await
+}
+
+/// Return to the previously selected OpenCode version
+#[tauri::command]
+pub async fn rollback_opencode_version(
+    state: State<'_, DesktopRuntime>,
+) -> Result<InstallerStatus, String> {
+    let version = opencode_installer::rollback()
+        .await
+        .map_err(|e| format!("Failed to roll back OpenCode: {}", e))?;
+    info!("[desktop:installer] rolled back to OpenCode {}", version);
+    apply_selected_binary(&state).await?;
+    get_opencode_installations().await
+}
+
+/// Point the manager at the newly selected binary and restart it if it was already running
+async fn apply_selected_binary(state: &State<'_, DesktopRuntime>) -> Result<(), String> {
+    let opencode = state.opencode_manager();
+    if !opencode.refresh_binary() {
+        warn!("[desktop:installer] no OpenCode binary available after selection change");
+        return Ok(());
+    }
+
+    if opencode.current_port().is_some() {
+        opencode
+            .restart()
+            .await
+            .map_err(|e| format!("Failed to restart OpenCode: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use tauri::{AppHandle, State};
+
+use crate::opencode_manager::PermissionReply;
+use crate::permission_requests::PendingPermission;
+use crate::tray;
+use crate::DesktopRuntime;
+
+/// Tool permission requests still waiting for an answer, oldest first
+#[tauri::command]
+pub async fn list_permission_requests(
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<PendingPermission>, String> {
+    Ok(state.permission_requests().list())
+}
+
+/// Approve once/always or deny a pending request; returns what is still pending
+#[tauri::command]
+pub async fn reply_permission_request(
+    app: AppHandle,
+    permission_id: String,
+    reply: PermissionReply,
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<PendingPermission>, String> {
+    let opencode = state.opencode_manager();
+    let result = state
+        .permission_requests()
+        .reply(&opencode, &permission_id, reply)
+        .await;
+    tray::refresh(&app);
+    result.map_err(|e| format!("Failed to answer permission request: {}", e))?;
+    Ok(state.permission_requests().list())
+}
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::remote_access::{PairedDevice, PairingCode, RemoteAccessStatus};
+use crate::DesktopRuntime;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteAccessPayload {
+    pub enabled: bool,
+    pub bind_address: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// Current LAN listener state, address and certificate fingerprint
+#[tauri::command]
+pub async fn get_remote_access_status(
+    state: State<'_, DesktopRuntime>,
+) -> Result<RemoteAccessStatus, String> {
+    let config = state
+        .settings()
+        .remote_access()
+        .await
+        .map_err(|e| format!("Failed to load remote access settings: {}", e))?;
+    Ok(state.remote_access().status(&config).await)
+}
+
+/// Persist remote access settings and start/stop the LAN listener to match
+#[tauri::command]
+pub async fn set_remote_access(
+    payload: RemoteAccessPayload,
+    state: State<'_, DesktopRuntime>,
+) -> Result<RemoteAccessStatus, String> {
+    let mut settings = state
+        .settings()
+        .load()
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    let current = state
+        .settings()
+        .remote_access()
+        .await
+        .map_err(|e| format!("Failed to load remote access settings: {}", e))?;
+
+    let bind_address = payload
+        .bind_address
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or(current.bind_address);
+    if bind_address.parse::<std::net::IpAddr>().is_err() {
+        return Err(format!("Invalid bind address: {}", bind_address));
+    }
+    let port = payload.port.filter(|port| *port > 0).unwrap_or(current.port);
+
+    if let Value::Object(map) = &mut settings {
+        map.insert(
+            "remoteAccess".to_string(),
+            json!({
+                "enabled": payload.enabled,
+                "bindAddress": bind_address,
+                "port": port,
+            }),
+        );
+    }
+    state
+        .settings()
+        .save(settings)
+        .await
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    state
+        .apply_remote_access()
+        .await
+        .map_err(|e| format!("Failed to apply remote access settings: {}", e))?;
+    get_remote_access_status(state).await
+}
+
+/// Show a one-time code that a phone exchanges for a device token
+#[tauri::command]
+pub async fn start_remote_pairing(
+    state: State<'_, DesktopRuntime>,
+) -> Result<PairingCode, String> {
+    let config = state
+        .settings()
+        .remote_access()
+        .await
+        .map_err(|e| format!("Failed to load remote access settings: {}", e))?;
+    if !config.enabled {
+        return Err("Remote access is disabled".to_string());
+    }
+    Ok(state.remote_access().registry().begin_pairing())
+}
+
+#[tauri::command]
+pub async fn list_paired_devices(
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<PairedDevice>, String> {
+    Ok(state.remote_access().registry().list())
+}
+
+/// Forget a paired device; its token stops working immediately
+#[tauri::command]
+pub async fn revoke_paired_device(
+    device_id: String,
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<PairedDevice>, String> {
+    let registry = state.remote_access().registry();
+    let removed = registry
+        .revoke(&device_id)
+        .await
+        .map_err(|e| format!("Failed to revoke device: {}", e))?;
+    if !removed {
+        return Err(format!("Unknown device: {}", device_id));
+    }
+    Ok(registry.list())
+}
@@ -0,0 +1,20 @@
+use tauri::State;
+
+use crate::session_activity::SessionActivitySnapshot;
+use crate::DesktopRuntime;
+
+/// Phase and busy timing of every session seen since launch, most recently changed first;
+/// pass `directory` to limit the result to one project
+#[tauri::command]
+pub async fn get_session_activity(
+    directory: Option<String>,
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<SessionActivitySnapshot>, String> {
+    let directory = directory
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    Ok(state
+        .session_activity()
+        .snapshot(directory.as_deref())
+        .await)
+}
@@ -1,10 +1,13 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashSet;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use crate::DesktopRuntime;
+use crate::notification_rules::{parse_clock, RuleTrigger};
 use crate::path_utils::expand_tilde_path;
+use crate::tray;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -37,6 +40,7 @@ pub async fn load_settings(state: State<'_, DesktopRuntime>) -> Result<SettingsL
 /// Save settings to disk with merge logic matching Express implementation
 #[tauri::command]
 pub async fn save_settings(
+    app: AppHandle,
     changes: Value,
     state: State<'_, DesktopRuntime>,
 ) -> Result<Value, String> {
@@ -60,6 +64,19 @@ pub async fn save_settings(
         .await
         .map_err(|e| format!("Failed to save settings: {}", e))?;
 
+    if sanitized_changes.get("remoteAccess").is_some() {
+        if let Err(e) = state.apply_remote_access().await {
+            warn!("[desktop:remote] Failed to apply remote access settings: {}", e);
+        }
+    }
+
+    // The tray lists recent projects
+    if sanitized_changes.get("lastDirectory").is_some()
+        || sanitized_changes.get("pinnedDirectories").is_some()
+    {
+        tray::refresh(&app);
+    }
+
     // Format response
     Ok(format_settings_response(&merged))
 }
@@ -67,6 +84,7 @@ pub async fn save_settings(
 /// Restart OpenCode CLI (matches Express /api/config/reload)
 #[tauri::command]
 pub async fn restart_opencode(state: State<'_, DesktopRuntime>) -> Result<RestartResult, String> {
+    state.reload_launch_profile().await;
     state
         .opencode
         .restart()
@@ -155,6 +173,9 @@ fn sanitize_settings_update(payload: &Value) -> Value {
         if let Some(Value::Bool(b)) = obj.get("queueModeEnabled") {
             result_obj.insert("queueModeEnabled".to_string(), json!(b));
         }
+        if let Some(Value::Bool(b)) = obj.get("persistentTerminals") {
+            result_obj.insert("persistentTerminals".to_string(), json!(b));
+        }
 
         // Number fields
         if let Some(Value::Number(n)) = obj.get("autoDeleteAfterDays") {
@@ -192,6 +213,41 @@ fn sanitize_settings_update(payload: &Value) -> Value {
             }
         }
 
+        // Per-project OpenCode launch profiles (object keyed by directory)
+        if let Some(Value::Object(profiles)) = obj.get("opencodeLaunchProfiles") {
+            result_obj.insert(
+                "opencodeLaunchProfiles".to_string(),
+                sanitize_launch_profiles(profiles),
+            );
+        }
+
+        // LAN remote access listener
+        if let Some(Value::Object(remote)) = obj.get("remoteAccess") {
+            result_obj.insert("remoteAccess".to_string(), sanitize_remote_access(remote));
+        }
+
+        // Notification rules, quiet hours and muted projects
+        if let Some(Value::Object(notifications)) = obj.get("notifications") {
+            result_obj.insert(
+                "notifications".to_string(),
+                sanitize_notifications(notifications),
+            );
+        }
+
+        // Outgoing webhooks (array of objects)
+        if let Some(Value::Array(arr)) = obj.get("webhooks") {
+            result_obj.insert("webhooks".to_string(), sanitize_webhooks(arr));
+        }
+
+        // Terminal shell profiles (array of objects) and the one used by default
+        if let Some(Value::Array(arr)) = obj.get("terminalProfiles") {
+            result_obj.insert("terminalProfiles".to_string(), sanitize_terminal_profiles(arr));
+        }
+        if let Some(Value::String(s)) = obj.get("defaultTerminalProfile") {
+            // An empty name clears the default
+            result_obj.insert("defaultTerminalProfile".to_string(), json!(s.trim()));
+        }
+
         // Skill catalogs (array of objects)
         if let Some(Value::Array(arr)) = obj.get("skillCatalogs") {
             let mut seen: HashSet<String> = HashSet::new();
@@ -238,6 +294,290 @@ fn sanitize_settings_update(payload: &Value) -> Value {
     result
 }
 
+/// Sanitize OpenCode launch profiles keyed by project directory
+fn sanitize_launch_profiles(profiles: &serde_json::Map<String, Value>) -> Value {
+    let mut result = serde_json::Map::new();
+
+    for (directory, entry) in profiles {
+        let directory = directory.trim();
+        let Some(entry) = entry.as_object() else { continue };
+        if directory.is_empty() {
+            continue;
+        }
+
+        let mut profile = serde_json::Map::new();
+
+        if let Some(Value::Array(args)) = entry.get("args") {
+            let args: Vec<&str> = args.iter().filter_map(|v| v.as_str()).collect();
+            if !args.is_empty() {
+                profile.insert("args".to_string(), json!(args));
+            }
+        }
+        for key in ["configPath", "binary"] {
+            if let Some(Value::String(s)) = entry.get(key) {
+                let trimmed = s.trim();
+                if !trimmed.is_empty() {
+                    let expanded = expand_tilde_path(trimmed).to_string_lossy().to_string();
+                    profile.insert(key.to_string(), json!(expanded));
+                }
+            }
+        }
+        if let Some(Value::Object(env)) = entry.get("env") {
+            let mut sanitized_env = serde_json::Map::new();
+            for (key, value) in env {
+                let key = key.trim();
+                if key.is_empty() || key.contains('=') {
+                    continue;
+                }
+                if let Some(value) = value.as_str() {
+                    sanitized_env.insert(key.to_string(), json!(value));
+                }
+            }
+            if !sanitized_env.is_empty() {
+                profile.insert("env".to_string(), Value::Object(sanitized_env));
+            }
+        }
+        if let Some(port) = entry.get("port").and_then(|v| v.as_u64()) {
+            if (1..=u16::MAX as u64).contains(&port) {
+                profile.insert("port".to_string(), json!(port));
+            }
+        }
+
+        if !profile.is_empty() {
+            let key = expand_tilde_path(directory).to_string_lossy().to_string();
+            result.insert(key, Value::Object(profile));
+        }
+    }
+
+    Value::Object(result)
+}
+
+/// Sanitize the remote access block (enabled flag, IP bind address, port)
+fn sanitize_remote_access(remote: &serde_json::Map<String, Value>) -> Value {
+    let mut result = serde_json::Map::new();
+
+    if let Some(Value::Bool(enabled)) = remote.get("enabled") {
+        result.insert("enabled".to_string(), json!(enabled));
+    }
+    if let Some(Value::String(address)) = remote.get("bindAddress") {
+        let trimmed = address.trim();
+        if trimmed.parse::<std::net::IpAddr>().is_ok() {
+            result.insert("bindAddress".to_string(), json!(trimmed));
+        }
+    }
+    if let Some(port) = remote.get("port").and_then(|v| v.as_u64()) {
+        if (1..=u16::MAX as u64).contains(&port) {
+            result.insert("port".to_string(), json!(port));
+        }
+    }
+
+    Value::Object(result)
+}
+
+/// Sanitize the notifications block; rules with an unknown trigger are dropped
+fn sanitize_notifications(notifications: &serde_json::Map<String, Value>) -> Value {
+    let mut result = serde_json::Map::new();
+
+    for key in ["enabled", "onlyWhenUnfocused"] {
+        if let Some(Value::Bool(b)) = notifications.get(key) {
+            result.insert(key.to_string(), json!(b));
+        }
+    }
+
+    if let Some(Value::Object(quiet)) = notifications.get("quietHours") {
+        let mut sanitized = serde_json::Map::new();
+        if let Some(Value::Bool(enabled)) = quiet.get("enabled") {
+            sanitized.insert("enabled".to_string(), json!(enabled));
+        }
+        for key in ["start", "end"] {
+            if let Some(time) = quiet
+                .get(key)
+                .and_then(|v| v.as_str())
+                .and_then(parse_clock)
+            {
+                sanitized.insert(key.to_string(), json!(time.format("%H:%M").to_string()));
+            }
+        }
+        result.insert("quietHours".to_string(), Value::Object(sanitized));
+    }
+
+    if let Some(arr) = notifications.get("mutedProjects") {
+        let muted: Vec<String> = extract_string_vec(arr)
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| expand_tilde_path(s).to_string_lossy().to_string())
+            .collect();
+        result.insert("mutedProjects".to_string(), json!(muted));
+    }
+
+    if let Some(Value::Array(rules)) = notifications.get("rules") {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut sanitized_rules: Vec<Value> = vec![];
+
+        for (index, entry) in rules.iter().enumerate() {
+            let Some(rule) = entry.as_object() else { continue };
+            let Some(trigger) = rule.get("trigger").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if serde_json::from_value::<RuleTrigger>(json!(trigger)).is_err() {
+                continue;
+            }
+
+            let id = rule
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("rule-{}", index + 1));
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            let mut sanitized = serde_json::Map::new();
+            sanitized.insert("id".to_string(), json!(id));
+            sanitized.insert("trigger".to_string(), json!(trigger));
+            if let Some(Value::Bool(enabled)) = rule.get("enabled") {
+                sanitized.insert("enabled".to_string(), json!(enabled));
+            }
+            if let Some(minutes) = rule.get("minutes").and_then(|v| v.as_u64()) {
+                sanitized.insert("minutes".to_string(), json!(minutes.clamp(1, 24 * 60)));
+            }
+            for key in ["agents", "models"] {
+                if let Some(arr) = rule.get(key) {
+                    sanitized.insert(key.to_string(), normalize_string_array(arr));
+                }
+            }
+            if let Some(Value::String(sound)) = rule.get("sound") {
+                sanitized.insert("sound".to_string(), json!(sound.trim()));
+            }
+
+            sanitized_rules.push(Value::Object(sanitized));
+        }
+
+        result.insert("rules".to_string(), Value::Array(sanitized_rules));
+    }
+
+    Value::Object(result)
+}
+
+/// Sanitize outgoing webhooks; entries without an id or an http(s) URL are dropped
+fn sanitize_webhooks(webhooks: &[Value]) -> Value {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut result: Vec<Value> = vec![];
+
+    for entry in webhooks {
+        let Some(obj) = entry.as_object() else { continue };
+
+        let id = obj.get("id").and_then(|v| v.as_str()).unwrap_or("").trim();
+        let url = obj.get("url").and_then(|v| v.as_str()).unwrap_or("").trim();
+        let valid_url = reqwest::Url::parse(url)
+            .map(|parsed| matches!(parsed.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if id.is_empty() || !valid_url || !seen.insert(id.to_string()) {
+            continue;
+        }
+
+        let mut webhook = serde_json::Map::new();
+        webhook.insert("id".to_string(), json!(id));
+        webhook.insert("url".to_string(), json!(url));
+        if let Some(Value::String(name)) = obj.get("name") {
+            webhook.insert("name".to_string(), json!(name.trim()));
+        }
+        if let Some(Value::Bool(enabled)) = obj.get("enabled") {
+            webhook.insert("enabled".to_string(), json!(enabled));
+        }
+        if let Some(Value::Object(headers)) = obj.get("headers") {
+            let mut sanitized = serde_json::Map::new();
+            for (name, value) in headers {
+                let name = name.trim();
+                let Some(value) = value.as_str() else { continue };
+                if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok()
+                    && reqwest::header::HeaderValue::from_str(value.trim()).is_ok()
+                {
+                    sanitized.insert(name.to_string(), json!(value.trim()));
+                }
+            }
+            webhook.insert("headers".to_string(), Value::Object(sanitized));
+        }
+        if let Some(Value::Array(events)) = obj.get("events") {
+            let events: Vec<&Value> = events
+                .iter()
+                .filter(|event| serde_json::from_value::<RuleTrigger>((*event).clone()).is_ok())
+                .collect();
+            webhook.insert("events".to_string(), json!(events));
+        }
+        if let Some(Value::String(template)) = obj.get("template") {
+            let template = template.trim();
+            // Keep only templates that will render; an empty string means the default payload
+            if template.is_empty() || serde_json::from_str::<Value>(template).is_ok() {
+                webhook.insert("template".to_string(), json!(template));
+            }
+        }
+
+        result.push(Value::Object(webhook));
+    }
+
+    Value::Array(result)
+}
+
+/// Sanitize terminal shell profiles; entries without a unique name are dropped
+fn sanitize_terminal_profiles(profiles: &[Value]) -> Value {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut result: Vec<Value> = vec![];
+
+    for entry in profiles {
+        let Some(obj) = entry.as_object() else { continue };
+
+        let name = obj.get("name").and_then(|v| v.as_str()).unwrap_or("").trim();
+        if name.is_empty() || !seen.insert(name.to_string()) {
+            continue;
+        }
+
+        let mut profile = serde_json::Map::new();
+        profile.insert("name".to_string(), json!(name));
+        for key in ["shell", "initialCommand"] {
+            if let Some(Value::String(s)) = obj.get(key) {
+                let trimmed = s.trim();
+                if !trimmed.is_empty() {
+                    profile.insert(key.to_string(), json!(trimmed));
+                }
+            }
+        }
+        if let Some(Value::Array(args)) = obj.get("args") {
+            let args: Vec<&str> = args.iter().filter_map(|v| v.as_str()).collect();
+            if !args.is_empty() {
+                profile.insert("args".to_string(), json!(args));
+            }
+        }
+        for key in ["login", "shellIntegration"] {
+            if let Some(Value::Bool(b)) = obj.get(key) {
+                profile.insert(key.to_string(), json!(b));
+            }
+        }
+        if let Some(Value::Object(env)) = obj.get("env") {
+            let mut sanitized_env = serde_json::Map::new();
+            for (key, value) in env {
+                let key = key.trim();
+                if key.is_empty() || key.contains('=') {
+                    continue;
+                }
+                if let Some(value) = value.as_str() {
+                    sanitized_env.insert(key.to_string(), json!(value));
+                }
+            }
+            if !sanitized_env.is_empty() {
+                profile.insert("env".to_string(), Value::Object(sanitized_env));
+            }
+        }
+
+        result.push(Value::Object(profile));
+    }
+
+    Value::Array(result)
+}
+
 /// Merge persisted settings (port of Express mergePersistedSettings)
 fn merge_persisted_settings(current: &Value, changes: &Value) -> Value {
     let mut result = current.clone();
@@ -307,6 +647,35 @@ fn merge_persisted_settings(current: &Value, changes: &Value) -> Value {
             }
             result_obj.insert("typographySizes".to_string(), json!(merged_typo));
         }
+
+        // Remote access updates may be partial (e.g. only toggling `enabled`)
+        if let Some(Value::Object(changes_remote)) = changes_obj.get("remoteAccess") {
+            let mut merged_remote = current
+                .get("remoteAccess")
+                .and_then(|v| v.as_object())
+                .cloned()
+                .unwrap_or_default();
+            for (key, value) in changes_remote {
+                merged_remote.insert(key.clone(), value.clone());
+            }
+            result_obj.insert("remoteAccess".to_string(), Value::Object(merged_remote));
+        }
+
+        // Notification updates may touch a single key (e.g. only `mutedProjects`); `rules` is replaced whole
+        if let Some(Value::Object(changes_notifications)) = changes_obj.get("notifications") {
+            let mut merged_notifications = current
+                .get("notifications")
+                .and_then(|v| v.as_object())
+                .cloned()
+                .unwrap_or_default();
+            for (key, value) in changes_notifications {
+                merged_notifications.insert(key.clone(), value.clone());
+            }
+            result_obj.insert(
+                "notifications".to_string(),
+                Value::Object(merged_notifications),
+            );
+        }
     }
 
     result
@@ -1,15 +1,31 @@
-use log::error;
-use portable_pty::{Child, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
+use chrono::{DateTime, Utc};
+use log::{error, warn};
+use portable_pty::{ChildKiller, MasterPty, NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     env,
     io::{Read, Write},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{mpsc, Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
-use tauri::{Emitter, State, Window};
+use tauri::{
+    ipc::{Channel, InvokeResponseBody},
+    Emitter, State, Window,
+};
+
+use crate::shell_integration::{self, OscScanner, ShellMark};
+#[cfg(unix)]
+use crate::terminal_daemon::{DaemonClient, DaemonEvent};
+use crate::terminal_process::{self, ProcessInfo, TerminalSignal};
+use crate::terminal_profile::{
+    builtin_profiles, is_posix_shell, posix_shell, resolve_shell_path, ShellProfile,
+};
+use crate::terminal_pty::{plain_text, Scrollback, SpawnSpec, Utf8Stream};
+use crate::terminal_recording::{self, Recorder, RecordingInfo};
+use crate::DesktopRuntime;
 
 const DEFAULT_SHELL: &str = "/bin/zsh";
 const DEFAULT_TERM: &str = "xterm-256color";
@@ -17,23 +33,479 @@ const DEFAULT_COLORTERM: &str = "truecolor";
 const DEFAULT_LOCALE: &str = "en_US.UTF-8";
 const TERM_PROGRAM_NAME: &str = "OpenChamber";
 const TERM_PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DEFAULT_TAIL_LINES: usize = 100;
+/// Finished command runs kept for callers that attach after the process exited
+const FINISHED_RUN_LIMIT: usize = 16;
+/// How long the exit watcher waits for buffered output after the shell ends
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+const READ_BUFFER_SIZE: usize = 16384;
+/// Under heavy output, chunks arriving within this window go out as one event
+const FLUSH_INTERVAL: Duration = Duration::from_millis(16);
+const MAX_BATCH_BYTES: usize = 256 * 1024;
 
 pub struct TerminalSession {
-    pub master: Box<dyn MasterPty + Send>,
-    pub writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    pub child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    pub backend: TerminalBackend,
+    pub output: Arc<Mutex<TerminalOutput>>,
+    /// Shell process id, used to inspect and signal the jobs it runs
+    pub pid: Option<u32>,
+    /// Profile the shell was started from, reused on restart
+    pub profile: ShellProfile,
+    pub cwd: PathBuf,
+    /// Git checkout or worktree the terminal was opened in; `cwd` itself outside a repository
+    pub project: PathBuf,
+    /// `project` is a linked git worktree
+    pub worktree: bool,
+    pub shell: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub created_at: DateTime<Utc>,
+    /// Readable and writable through the agent terminal bridge; not kept across app restarts
+    pub agent_access: bool,
+}
+
+/// Where a session's shell runs
+pub enum TerminalBackend {
+    /// PTY owned by the app; the shell ends when the app quits
+    Local {
+        master: Box<dyn MasterPty + Send>,
+        writer: Arc<Mutex<Box<dyn Write + Send>>>,
+        killer: Box<dyn ChildKiller + Send + Sync>,
+    },
+    /// Shell hosted by the terminal session daemon; survives app restarts
+    #[cfg(unix)]
+    Detached {
+        daemon: DaemonClient,
+        /// Whether this app is receiving the session's output yet
+        streaming: bool,
+    },
+}
+
+impl TerminalSession {
+    pub fn is_detached(&self) -> bool {
+        !matches!(self.backend, TerminalBackend::Local { .. })
+    }
+
+    fn write_input(&self, session_id: &str, data: &str) -> Result<(), String> {
+        match &self.backend {
+            TerminalBackend::Local { writer, .. } => {
+                let mut writer = writer.lock().map_err(|_| "Terminal busy".to_string())?;
+                writer
+                    .write_all(data.as_bytes())
+                    .map_err(|e| format!("Failed to write to terminal: {e}"))?;
+                writer
+                    .flush()
+                    .map_err(|e| format!("Failed to flush terminal input: {e}"))
+            }
+            #[cfg(unix)]
+            TerminalBackend::Detached { daemon, .. } => daemon
+                .input(session_id, data)
+                .map_err(|e| format!("Failed to write to terminal: {e}")),
+        }
+    }
+
+    fn resize(&mut self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        match &self.backend {
+            TerminalBackend::Local { master, .. } => master
+                .resize(PtySize {
+                    rows,
+                    cols,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                })
+                .map_err(|e| format!("Failed to resize terminal: {e}"))?,
+            #[cfg(unix)]
+            TerminalBackend::Detached { daemon, .. } => daemon
+                .resize(session_id, cols, rows)
+                .map_err(|e| format!("Failed to resize terminal: {e}"))?,
+        }
+        self.output.lock().unwrap().record_resize(cols, rows);
+        self.cols = cols;
+        self.rows = rows;
+        Ok(())
+    }
+
+    fn kill(&mut self, session_id: &str) {
+        let result = match &mut self.backend {
+            TerminalBackend::Local { killer, .. } => killer.kill().map_err(anyhow::Error::from),
+            #[cfg(unix)]
+            TerminalBackend::Detached { daemon, .. } => daemon.kill(session_id),
+        };
+        if let Err(err) = result {
+            warn!("Failed to kill terminal {session_id}: {err}");
+        }
+    }
+
+    fn info(&self, session_id: &str) -> TerminalSessionInfo {
+        let output = self.output.lock().unwrap();
+        TerminalSessionInfo {
+            session_id: session_id.to_string(),
+            cwd: self.cwd.to_string_lossy().to_string(),
+            project: self.project.to_string_lossy().to_string(),
+            worktree: self.worktree,
+            shell: self.shell.clone(),
+            cols: self.cols,
+            rows: self.rows,
+            created_at: self.created_at,
+            window: output.window.label().to_string(),
+            detached: self.is_detached(),
+            pid: self.pid,
+            agent_access: self.agent_access,
+            command: output.run.as_ref().map(|run| run.command.clone()),
+            profile: Some(self.profile.name.clone()).filter(|name| !name.is_empty()),
+            recording: output
+                .recorder
+                .as_ref()
+                .map(|recorder| recorder.id().to_string()),
+        }
+    }
+}
+
+/// Where a session's output goes, plus the recent history for reattaching
+pub struct TerminalOutput {
+    window: Window,
+    /// Raw-bytes IPC channel used instead of JSON events when the client attached with one
+    channel: Option<Channel<InvokeResponseBody>>,
+    scrollback: Scrollback,
+    utf8: Utf8Stream,
+    /// Set when the session runs a single command instead of an interactive shell
+    run: Option<CommandRun>,
+    recorder: Option<Recorder>,
+    osc: OscScanner,
+    shell_state: ShellState,
+}
+
+/// What shell integration has reported so far
+#[derive(Default)]
+struct ShellState {
+    cwd: Option<PathBuf>,
+    command_running: bool,
+    last_exit_code: Option<i32>,
+}
+
+struct CommandRun {
+    command: String,
+    started: Instant,
+    tail_lines: usize,
 }
 
+/// How a command run ended, with the last lines it printed
+#[derive(Clone, Serialize)]
+pub struct CommandResult {
+    pub command: String,
+    pub exit_code: i32,
+    pub signal: Option<String>,
+    pub duration_ms: u64,
+    pub output_tail: String,
+}
+
+impl TerminalOutput {
+    fn new(window: Window) -> Self {
+        Self {
+            window,
+            channel: None,
+            scrollback: Scrollback::default(),
+            utf8: Utf8Stream::default(),
+            run: None,
+            recorder: None,
+            osc: OscScanner::default(),
+            shell_state: ShellState::default(),
+        }
+    }
+
+    fn for_command(window: Window, command: String, tail_lines: usize) -> Self {
+        Self {
+            run: Some(CommandRun {
+                command,
+                started: Instant::now(),
+                tail_lines,
+            }),
+            ..Self::new(window)
+        }
+    }
+
+    /// Record a chunk and forward it to the attached window, followed by any shell integration events
+    fn push(&mut self, event_name: &str, bytes: &[u8]) -> tauri::Result<()> {
+        self.scrollback.record(bytes);
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.output(bytes) {
+                warn!("Stopped terminal recording {}: {err}", recorder.id());
+                self.recorder = None;
+            }
+        }
+        self.forward(event_name, bytes)?;
+
+        for mark in self.osc.scan(bytes) {
+            if let Some(payload) = self.shell_state.apply(mark) {
+                self.emit(event_name, payload)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn forward(&mut self, event_name: &str, bytes: &[u8]) -> tauri::Result<()> {
+        if let Some(channel) = &self.channel {
+            return channel.send(InvokeResponseBody::Raw(bytes.to_vec()));
+        }
+
+        let data = self.utf8.decode(bytes);
+        if data.is_empty() {
+            return Ok(());
+        }
+        // Offsets count only bytes delivered as text, so a held-back partial character is excluded
+        let offset = self.scrollback.offset() - self.utf8.pending() as u64;
+        self.emit_to_window(
+            event_name,
+            serde_json::json!({
+                "type": "data",
+                "data": data,
+                "offset": offset,
+            }),
+        )
+    }
+
+    fn emit(&self, event_name: &str, payload: serde_json::Value) -> tauri::Result<()> {
+        match &self.channel {
+            Some(channel) => channel.send(InvokeResponseBody::Json(payload.to_string())),
+            None => self.emit_to_window(event_name, payload),
+        }
+    }
+
+    /// `Window::emit` reaches every window, so output is addressed to the attached one only
+    fn emit_to_window(&self, event_name: &str, payload: serde_json::Value) -> tauri::Result<()> {
+        self.window
+            .emit_to(self.window.label(), event_name, payload)
+    }
+
+    /// Send further output to a new client and return the history it should replay
+    fn attach(
+        &mut self,
+        window: Window,
+        channel: Option<Channel<InvokeResponseBody>>,
+    ) -> (String, u64) {
+        let (text, offset) = self.scrollback.replay();
+        let bytes = self.scrollback.bytes();
+        self.utf8.resume_after(&bytes);
+        if let Some(channel) = &channel {
+            // The replay stops short of a partial character; its bytes lead the channel instead
+            let held_back = (self.scrollback.offset() - offset) as usize;
+            if held_back > 0 {
+                let tail = bytes[bytes.len() - held_back..].to_vec();
+                let _ = channel.send(InvokeResponseBody::Raw(tail));
+            }
+        }
+        self.window = window;
+        self.channel = channel;
+        (text, offset)
+    }
+
+    fn record_resize(&mut self, cols: u16, rows: u16) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.resize(cols, rows) {
+                warn!("Stopped terminal recording {}: {err}", recorder.id());
+                self.recorder = None;
+            }
+        }
+    }
+
+    fn stop_recording(&mut self) -> Option<RecordingInfo> {
+        let recorder = self.recorder.take()?;
+        let id = recorder.id().to_string();
+        match recorder.finish() {
+            Ok(info) => Some(info),
+            Err(err) => {
+                warn!("Failed to finish terminal recording {id}: {err}");
+                None
+            }
+        }
+    }
+
+    fn restore(&mut self, bytes: &[u8], offset: u64) {
+        self.scrollback.restore(bytes, offset);
+        self.utf8.resume_after(bytes);
+    }
+
+    fn command_result(&self, exit_code: i32, signal: Option<String>) -> Option<CommandResult> {
+        let run = self.run.as_ref()?;
+        let text = plain_text(&self.scrollback.bytes());
+        let lines: Vec<&str> = text.trim_end().lines().collect();
+        let tail = &lines[lines.len().saturating_sub(run.tail_lines)..];
+        Some(CommandResult {
+            command: run.command.clone(),
+            exit_code,
+            signal,
+            duration_ms: run.started.elapsed().as_millis() as u64,
+            output_tail: tail.join("\n"),
+        })
+    }
+}
+
+impl ShellState {
+    /// Update from a reported mark and return the event to send, if the client should hear about it
+    fn apply(&mut self, mark: ShellMark) -> Option<serde_json::Value> {
+        match mark {
+            ShellMark::Cwd(cwd) => {
+                if self.cwd.as_ref() == Some(&cwd) {
+                    return None;
+                }
+                let payload = serde_json::json!({
+                    "type": "cwd",
+                    "cwd": cwd.to_string_lossy(),
+                });
+                self.cwd = Some(cwd);
+                Some(payload)
+            }
+            ShellMark::Prompt => None,
+            ShellMark::CommandStart => {
+                self.command_running = true;
+                Some(serde_json::json!({ "type": "command-start" }))
+            }
+            ShellMark::CommandFinish(exit_code) => {
+                self.command_running = false;
+                self.last_exit_code = exit_code;
+                Some(serde_json::json!({
+                    "type": "command-finish",
+                    "exitCode": exit_code,
+                }))
+            }
+        }
+    }
+}
+
+/// Output of a shared session, as read by an agent
+#[derive(Serialize)]
+pub struct SharedTerminalOutput {
+    /// Plain text with escape sequences removed
+    pub output: String,
+    /// Pass back as `since` to read only what comes after
+    pub offset: u64,
+    /// Output after `since` was already dropped from the scrollback
+    pub truncated: bool,
+}
+
+#[derive(Clone)]
 pub struct TerminalState {
     pub sessions: Arc<Mutex<HashMap<String, TerminalSession>>>,
+    pub finished_runs: Arc<Mutex<VecDeque<(String, CommandResult)>>>,
 }
 
 impl TerminalState {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            finished_runs: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
+
+    /// Sessions the user opted in to agent access
+    pub fn shared_sessions(&self) -> Vec<TerminalSessionInfo> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut shared: Vec<TerminalSessionInfo> = sessions
+            .iter()
+            .filter(|(_, session)| session.agent_access)
+            .map(|(session_id, session)| session.info(session_id))
+            .collect();
+        shared.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        shared
+    }
+
+    /// Output after `since`, or the last `lines` lines when it is absent; `None` unless the session is shared
+    pub fn read_shared_output(
+        &self,
+        session_id: &str,
+        since: Option<u64>,
+        lines: usize,
+    ) -> Option<SharedTerminalOutput> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get(session_id)
+            .filter(|session| session.agent_access)?;
+        let output = session.output.lock().unwrap();
+        let bytes = output.scrollback.bytes();
+        let offset = output.scrollback.offset();
+        let start = offset - bytes.len() as u64;
+
+        let Some(since) = since else {
+            let text = plain_text(&bytes);
+            let all: Vec<&str> = text.trim_end().lines().collect();
+            return Some(SharedTerminalOutput {
+                output: all[all.len().saturating_sub(lines)..].join("\n"),
+                offset,
+                truncated: false,
+            });
+        };
+        let skip = since.clamp(start, offset) - start;
+        Some(SharedTerminalOutput {
+            output: plain_text(&bytes[skip as usize..]),
+            offset,
+            truncated: since < start,
+        })
+    }
+
+    /// Sessions opened in the project or worktree at `root`, with how many jobs each is running
+    pub fn project_sessions(&self, root: &Path) -> Vec<(String, usize)> {
+        // Reading /proc is slow enough that it should not hold up every other terminal command
+        let pids: Vec<(String, Option<u32>)> = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions
+                .iter()
+                .filter(|(_, session)| same_directory(&session.project, root))
+                .map(|(session_id, session)| (session_id.clone(), session.pid))
+                .collect()
+        };
+        pids.into_iter()
+            .map(|(session_id, pid)| (session_id, running_jobs(pid).len()))
+            .collect()
+    }
+
+    /// Names of the jobs in app-owned sessions, which end when the app quits; daemon-hosted shells survive
+    pub fn jobs_ended_by_exit(&self) -> Vec<String> {
+        let pids: Vec<Option<u32>> = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions
+                .values()
+                .filter(|session| !session.is_detached())
+                .map(|session| session.pid)
+                .collect()
+        };
+        let jobs: Vec<ProcessInfo> = pids.into_iter().flat_map(running_jobs).collect();
+        job_names(&jobs)
+    }
+
+    /// Sessions whose stored project is one of `projects`, compared as paths only, so it still
+    /// works once the directory is gone
+    pub fn sessions_with_project(&self, projects: &[PathBuf]) -> Vec<String> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .iter()
+            .filter(|(_, session)| projects.contains(&session.project))
+            .map(|(session_id, _)| session_id.clone())
+            .collect()
+    }
+
+    /// End the given sessions outside the sessions lock; returns how many were still open
+    pub fn close_sessions(&self, session_ids: &[String]) -> usize {
+        let closed: Vec<(&String, TerminalSession)> = {
+            let mut sessions = self.sessions.lock().unwrap();
+            session_ids
+                .iter()
+                .filter_map(|id| sessions.remove(id).map(|session| (id, session)))
+                .collect()
+        };
+        let count = closed.len();
+        for (session_id, mut session) in closed {
+            session.kill(session_id);
+        }
+        count
+    }
+
+    /// Type into a shared session; `None` unless the session is shared
+    pub fn write_shared_input(&self, session_id: &str, data: &str) -> Option<Result<(), String>> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get(session_id)
+            .filter(|session| session.agent_access)?;
+        Some(session.write_input(session_id, data))
+    }
 }
 
 #[derive(Deserialize)]
@@ -41,6 +513,12 @@ pub struct CreateTerminalPayload {
     pub cols: u16,
     pub rows: u16,
     pub cwd: Option<String>,
+    /// Run under the session daemon; defaults to the `persistentTerminals` setting
+    pub detached: Option<bool>,
+    /// Named profile from settings or a built-in shell; defaults to `defaultTerminalProfile`
+    pub profile: Option<String>,
+    /// One-off profile that takes precedence over `profile`
+    pub shell_profile: Option<ShellProfile>,
 }
 
 #[derive(Serialize)]
@@ -52,63 +530,84 @@ pub struct CreateTerminalResponse {
 pub async fn create_terminal_session(
     payload: CreateTerminalPayload,
     state: State<'_, TerminalState>,
+    runtime: State<'_, DesktopRuntime>,
     window: Window,
 ) -> Result<CreateTerminalResponse, String> {
-    let pty_system = NativePtySystem::default();
-    let size = PtySize {
-        rows: payload.rows,
-        cols: payload.cols,
-        pixel_width: 0,
-        pixel_height: 0,
+    let detached = match payload.detached {
+        Some(detached) => detached,
+        None => runtime.settings().persistent_terminals().await,
+    };
+    let profile = match payload.shell_profile {
+        Some(profile) => profile,
+        None => resolve_profile(&runtime, payload.profile.as_deref()).await?,
     };
+    let spec = build_spawn_spec(payload.cols, payload.rows, payload.cwd.as_deref(), &profile)?;
+    let session_id = spawn_session(&state, window, spec, profile, detached)?;
+    Ok(CreateTerminalResponse { session_id })
+}
 
-    let working_dir = resolve_working_directory(payload.cwd.as_deref())?;
-    let shell_path = resolve_shell();
+#[derive(Deserialize)]
+pub struct RunTerminalCommandPayload {
+    pub command: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub cwd: Option<String>,
+    /// Lines of output kept in the result; defaults to 100
+    pub tail_lines: Option<usize>,
+    /// Shell, args and environment come from this profile; its initial command is skipped.
+    /// Profiles whose shell is not POSIX keep their environment but run the command in `sh`
+    pub profile: Option<String>,
+}
 
-    let mut cmd = CommandBuilder::new(&shell_path);
-    if shell_accepts_login_flag(&shell_path) {
-        cmd.arg("-l");
-    }
-    if let Some(cwd) = working_dir.to_str() {
-        cmd.cwd(cwd);
+/// Run one command line in a PTY; output streams like a shell session and the exit event carries a `CommandResult`
+#[tauri::command]
+pub async fn run_terminal_command(
+    payload: RunTerminalCommandPayload,
+    state: State<'_, TerminalState>,
+    runtime: State<'_, DesktopRuntime>,
+    window: Window,
+) -> Result<CreateTerminalResponse, String> {
+    let command = payload.command.trim().to_string();
+    if command.is_empty() {
+        return Err("Command is empty".to_string());
     }
-    apply_terminal_environment(&mut cmd, &shell_path);
-
-    let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
-    let child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
-    drop(pair.slave);
-
-    let reader = pair
-        .master
-        .try_clone_reader()
-        .map_err(|e| format!("Failed to clone PTY reader: {e}"))?;
-    let writer = Arc::new(Mutex::new(
-        pair.master
-            .take_writer()
-            .map_err(|e| format!("Failed to take PTY writer: {e}"))?,
-    ));
-    let master = pair.master;
-    let child = Arc::new(Mutex::new(child));
-
-    let session_id = uuid::Uuid::new_v4().to_string();
-    state.sessions.lock().unwrap().insert(
-        session_id.clone(),
-        TerminalSession {
-            master,
-            writer: writer.clone(),
-            child: child.clone(),
-        },
-    );
-
-    spawn_reader_thread(reader, window.clone(), session_id.clone());
-    spawn_exit_watcher(child, window, state.sessions.clone(), session_id.clone());
 
+    let mut profile = resolve_profile(&runtime, payload.profile.as_deref()).await?;
+    profile.shell_integration = Some(false);
+    let mut spec = build_spawn_spec(payload.cols, payload.rows, payload.cwd.as_deref(), &profile)?;
+    if !is_posix_shell(&spec.shell) {
+        // fish, nu or a plain program would read `-c` differently or not at all;
+        // the profile still supplies the working directory and environment
+        spec.shell = posix_shell()?;
+        spec.args.clear();
+    }
+    spec.args.push("-c".to_string());
+    spec.args.push(command.clone());
+    let tail_lines = payload.tail_lines.unwrap_or(DEFAULT_TAIL_LINES);
+    let output = TerminalOutput::for_command(window, command, tail_lines);
+    let session_id = spawn_local_session(&state, output, spec, profile)?;
     Ok(CreateTerminalResponse { session_id })
 }
 
+/// Result of a finished command run, or `None` while it is still running
+#[tauri::command]
+pub async fn get_terminal_command_result(
+    session_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<Option<CommandResult>, String> {
+    if state.sessions.lock().unwrap().contains_key(&session_id) {
+        return Ok(None);
+    }
+    state
+        .finished_runs
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(id, _)| id == &session_id)
+        .map(|(_, result)| Some(result.clone()))
+        .ok_or_else(|| "Terminal session not found".to_string())
+}
+
 #[tauri::command]
 pub async fn send_terminal_input(
     session_id: String,
@@ -119,18 +618,7 @@ pub async fn send_terminal_input(
     let Some(session) = sessions.get(&session_id) else {
         return Err("Terminal session not found".to_string());
     };
-
-    let mut writer = session
-        .writer
-        .lock()
-        .map_err(|_| "Terminal busy".to_string())?;
-    writer
-        .write_all(data.as_bytes())
-        .map_err(|e| format!("Failed to write to terminal: {e}"))?;
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush terminal input: {e}"))?;
-    Ok(())
+    session.write_input(&session_id, &data)
 }
 
 #[tauri::command]
@@ -144,38 +632,127 @@ pub async fn resize_terminal(
     let Some(session) = sessions.get_mut(&session_id) else {
         return Err("Terminal session not found".to_string());
     };
-
-    session
-        .master
-        .resize(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| format!("Failed to resize terminal: {e}"))?;
-    Ok(())
+    session.resize(&session_id, cols, rows)
 }
 
+/// Refuses while the shell runs jobs, listing them, unless `force` is set
 #[tauri::command]
 pub async fn close_terminal(
     session_id: String,
+    force: Option<bool>,
     state: State<'_, TerminalState>,
 ) -> Result<(), String> {
+    if !force.unwrap_or(false) {
+        let pid = state
+            .sessions
+            .lock()
+            .unwrap()
+            .get(&session_id)
+            .and_then(|session| session.pid);
+        let jobs = job_names(&running_jobs(pid));
+        if !jobs.is_empty() {
+            return Err(format!(
+                "Terminal is still running {}; close it with force to stop them",
+                jobs.join(", ")
+            ));
+        }
+    }
+
     let session = {
         let mut sessions = state.sessions.lock().unwrap();
         sessions.remove(&session_id)
     };
 
-    if let Some(session) = session {
-        if let Ok(mut child) = session.child.lock() {
-            let _ = child.kill();
-        }
+    if let Some(mut session) = session {
+        session.kill(&session_id);
     }
 
     Ok(())
 }
 
+/// Let agents read and type into this terminal through the bridge on the local server
+#[tauri::command]
+pub async fn set_terminal_agent_access(
+    session_id: String,
+    enabled: bool,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let mut sessions = state.sessions.lock().unwrap();
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or_else(|| "Terminal session not found".to_string())?;
+    session.agent_access = enabled;
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct TerminalProcessesResponse {
+    pub shell_pid: u32,
+    /// The shell first, then everything it started in tree order
+    pub processes: Vec<ProcessInfo>,
+    /// Processes that closing the terminal would end, not counting the shell
+    pub running_jobs: usize,
+}
+
+/// The foreground job and the rest of the shell's process tree, so the UI can warn before closing
+#[tauri::command]
+pub async fn list_terminal_processes(
+    session_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<TerminalProcessesResponse, String> {
+    let shell_pid = shell_pid(&state, &session_id)?;
+    let processes = terminal_process::process_tree(shell_pid).map_err(|e| e.to_string())?;
+    let running_jobs = processes.iter().filter(|process| process.is_job()).count();
+    Ok(TerminalProcessesResponse {
+        shell_pid,
+        processes,
+        running_jobs,
+    })
+}
+
+/// Send SIGINT, SIGTERM or SIGKILL to the shell or one of its descendants
+#[tauri::command]
+pub async fn signal_terminal_process(
+    session_id: String,
+    pid: u32,
+    signal: String,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let shell_pid = shell_pid(&state, &session_id)?;
+    let signal = TerminalSignal::parse(&signal).map_err(|e| e.to_string())?;
+    terminal_process::signal_process(shell_pid, pid, signal)
+        .map_err(|e| format!("Failed to signal process {pid}: {e}"))
+}
+
+/// Jobs under a shell; none when its pid is unknown or it has already exited
+fn running_jobs(shell_pid: Option<u32>) -> Vec<ProcessInfo> {
+    shell_pid
+        .and_then(|pid| terminal_process::process_tree(pid).ok())
+        .map(|tree| tree.into_iter().filter(ProcessInfo::is_job).collect())
+        .unwrap_or_default()
+}
+
+/// Distinct process names, in the order they were found
+fn job_names(jobs: &[ProcessInfo]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for job in jobs {
+        if !names.contains(&job.name) {
+            names.push(job.name.clone());
+        }
+    }
+    names
+}
+
+fn shell_pid(state: &TerminalState, session_id: &str) -> Result<u32, String> {
+    let sessions = state.sessions.lock().unwrap();
+    let session = sessions
+        .get(session_id)
+        .ok_or_else(|| "Terminal session not found".to_string())?;
+    session
+        .pid
+        .ok_or_else(|| "Shell process id is unknown for this terminal".to_string())
+}
+
 #[derive(Deserialize)]
 pub struct RestartTerminalPayload {
     pub session_id: String,
@@ -188,41 +765,488 @@ pub struct RestartTerminalPayload {
 pub async fn restart_terminal_session(
     payload: RestartTerminalPayload,
     state: State<'_, TerminalState>,
+    runtime: State<'_, DesktopRuntime>,
     window: Window,
 ) -> Result<CreateTerminalResponse, String> {
+    // The replacement keeps the old session's mode and profile
+    let previous = state.sessions.lock().unwrap().remove(&payload.session_id);
+    let (detached, profile) = match previous {
+        Some(mut session) => {
+            session.kill(&payload.session_id);
+            (session.is_detached(), session.profile)
+        }
+        None => (
+            runtime.settings().persistent_terminals().await,
+            resolve_profile(&runtime, None).await?,
+        ),
+    };
+
+    let spec = build_spawn_spec(payload.cols, payload.rows, Some(&payload.cwd), &profile)?;
+    let session_id = spawn_session(&state, window, spec, profile, detached)?;
+    Ok(CreateTerminalResponse { session_id })
+}
+
+#[derive(Deserialize)]
+pub struct ForceKillPayload {
+    pub session_id: Option<String>,
+    pub cwd: Option<String>,
+}
+
+/// Kill one session, every session opened in `cwd`, or all of them
+#[tauri::command]
+pub async fn force_kill_terminal(
+    payload: ForceKillPayload,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let ids: Vec<String> = {
+        let sessions = state.sessions.lock().unwrap();
+        match (payload.session_id, payload.cwd) {
+            (Some(session_id), _) => vec![session_id],
+            (None, Some(cwd)) => {
+                let cwd = Path::new(&cwd);
+                sessions
+                    .iter()
+                    .filter(|(_, session)| {
+                        same_directory(&session.cwd, cwd) || same_directory(&session.project, cwd)
+                    })
+                    .map(|(session_id, _)| session_id.clone())
+                    .collect()
+            }
+            (None, None) => sessions.keys().cloned().collect(),
+        }
+    };
+    state.close_sessions(&ids);
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct TerminalSessionInfo {
+    pub session_id: String,
+    pub cwd: String,
+    /// Project directory or git worktree the terminal belongs to
+    pub project: String,
+    pub worktree: bool,
+    pub shell: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub created_at: DateTime<Utc>,
+    /// Label of the window currently receiving output
+    pub window: String,
+    /// Hosted by the session daemon rather than the app
+    pub detached: bool,
+    pub pid: Option<u32>,
+    /// Shared with agents through the terminal bridge
+    pub agent_access: bool,
+    /// Command line for sessions started by `run_terminal_command`
+    pub command: Option<String>,
+    /// Name of the shell profile, if one was used
+    pub profile: Option<String>,
+    /// Id of the recording in progress
+    pub recording: Option<String>,
+}
+
+/// Live sessions, oldest first, so a reloaded or restarted app can find its terminals again
+#[tauri::command]
+pub async fn list_terminal_sessions(
+    project: Option<String>,
+    state: State<'_, TerminalState>,
+    window: Window,
+) -> Result<Vec<TerminalSessionInfo>, String> {
+    #[cfg(unix)]
+    sync_daemon_sessions(&state, &window);
+    #[cfg(not(unix))]
+    let _ = window;
+
+    let sessions = state.sessions.lock().unwrap();
+    let project = project.as_deref().map(Path::new);
+    let mut list: Vec<TerminalSessionInfo> = sessions
+        .iter()
+        .filter(|(_, session)| project.map_or(true, |root| same_directory(&session.project, root)))
+        .map(|(session_id, session)| session.info(session_id))
+        .collect();
+    list.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(list)
+}
+
+#[derive(Serialize)]
+pub struct AttachTerminalResponse {
+    pub session_id: String,
+    pub scrollback: String,
+    /// Output offset at the end of `scrollback`; data events at or below it are already included
+    pub offset: u64,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Route a session's output to the calling window and return its scrollback for replay
+#[tauri::command]
+pub async fn attach_terminal_session(
+    session_id: String,
+    state: State<'_, TerminalState>,
+    window: Window,
+) -> Result<AttachTerminalResponse, String> {
+    attach_session(&state, session_id, window, None)
+}
+
+/// Like `attach_terminal_session`, but output arrives as raw bytes on `channel` instead of JSON events
+#[tauri::command]
+pub async fn attach_terminal_channel(
+    session_id: String,
+    channel: Channel<InvokeResponseBody>,
+    state: State<'_, TerminalState>,
+    window: Window,
+) -> Result<AttachTerminalResponse, String> {
+    attach_session(&state, session_id, window, Some(channel))
+}
+
+/// Start writing a session's output to an asciicast v2 file in the recordings directory
+#[tauri::command]
+pub async fn start_terminal_recording(
+    session_id: String,
+    title: Option<String>,
+    state: State<'_, TerminalState>,
+) -> Result<RecordingInfo, String> {
+    let sessions = state.sessions.lock().unwrap();
+    let Some(session) = sessions.get(&session_id) else {
+        return Err("Terminal session not found".to_string());
+    };
+    let mut output = session.output.lock().unwrap();
+    if output.recorder.is_some() {
+        return Err("Terminal is already being recorded".to_string());
+    }
+
+    let title = title.filter(|title| !title.trim().is_empty());
+    let recorder = Recorder::start(
+        &session_id,
+        session.cols,
+        session.rows,
+        &session.shell,
+        title,
+    )
+    .map_err(|e| format!("Failed to start recording: {e}"))?;
+    let info = recorder.info();
+    output.recorder = Some(recorder);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn stop_terminal_recording(
+    session_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<RecordingInfo, String> {
+    let sessions = state.sessions.lock().unwrap();
+    let Some(session) = sessions.get(&session_id) else {
+        return Err("Terminal session not found".to_string());
+    };
+    let mut output = session.output.lock().unwrap();
+    if output.recorder.is_none() {
+        return Err("Terminal is not being recorded".to_string());
+    }
+    output
+        .stop_recording()
+        .ok_or_else(|| "Failed to finish recording".to_string())
+}
+
+/// Saved recordings, newest first
+#[tauri::command]
+pub async fn list_terminal_recordings() -> Result<Vec<RecordingInfo>, String> {
+    terminal_recording::list().map_err(|e| e.to_string())
+}
+
+/// A recording's output as plain text with ANSI escapes stripped
+#[tauri::command]
+pub async fn export_terminal_recording_text(recording_id: String) -> Result<String, String> {
+    terminal_recording::export_plain_text(&recording_id).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+pub struct TerminalCwdResponse {
+    pub cwd: String,
+    /// False when the shell has no integration and `cwd` is where it started
+    pub reported: bool,
+    pub command_running: bool,
+    pub last_exit_code: Option<i32>,
+}
+
+/// The session's current directory as last reported by its shell
+#[tauri::command]
+pub async fn get_terminal_cwd(
+    session_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<TerminalCwdResponse, String> {
+    let sessions = state.sessions.lock().unwrap();
+    let Some(session) = sessions.get(&session_id) else {
+        return Err("Terminal session not found".to_string());
+    };
+    let output = session.output.lock().unwrap();
+    let shell_state = &output.shell_state;
+    Ok(TerminalCwdResponse {
+        cwd: shell_state
+            .cwd
+            .as_ref()
+            .unwrap_or(&session.cwd)
+            .to_string_lossy()
+            .to_string(),
+        reported: shell_state.cwd.is_some(),
+        command_running: shell_state.command_running,
+        last_exit_code: shell_state.last_exit_code,
+    })
+}
+
+fn attach_session(
+    state: &TerminalState,
+    session_id: String,
+    window: Window,
+    channel: Option<Channel<InvokeResponseBody>>,
+) -> Result<AttachTerminalResponse, String> {
+    let mut sessions = state.sessions.lock().unwrap();
+    let Some(session) = sessions.get_mut(&session_id) else {
+        return Err("Terminal session not found".to_string());
+    };
+
+    #[cfg(unix)]
+    if let TerminalBackend::Detached { daemon, streaming } = &mut session.backend {
+        if !*streaming {
+            stream_daemon_session(
+                daemon,
+                &session_id,
+                session.output.clone(),
+                state.sessions.clone(),
+            )?;
+            *streaming = true;
+        }
+    }
+
+    // Swap the target and snapshot under one lock so no output falls between the two
+    let (scrollback, offset) = session.output.lock().unwrap().attach(window, channel);
+    Ok(AttachTerminalResponse {
+        session_id,
+        scrollback,
+        offset,
+        cols: session.cols,
+        rows: session.rows,
+    })
+}
+
+/// Daemon-hosted shells that no app window is attached to, e.g. left over from an earlier run
+#[tauri::command]
+pub async fn list_orphaned_terminal_sessions() -> Result<Vec<TerminalSessionInfo>, String> {
+    #[cfg(unix)]
     {
-        let mut sessions = state.sessions.lock().unwrap();
-        if let Some(session) = sessions.remove(&payload.session_id) {
-            if let Ok(mut child) = session.child.lock() {
-                let _ = child.kill();
+        orphaned_sessions()
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(Vec::new())
+    }
+}
+
+/// Kill the given orphaned sessions, or all of them when `session_ids` is omitted; returns how many died
+#[tauri::command]
+pub async fn kill_orphaned_terminal_sessions(
+    session_ids: Option<Vec<String>>,
+    state: State<'_, TerminalState>,
+) -> Result<usize, String> {
+    #[cfg(unix)]
+    {
+        let daemon = DaemonClient::new().map_err(|e| e.to_string())?;
+        let mut killed = 0;
+        for orphan in orphaned_sessions()? {
+            if session_ids
+                .as_ref()
+                .is_some_and(|ids| !ids.contains(&orphan.session_id))
+            {
+                continue;
+            }
+            match daemon.kill(&orphan.session_id) {
+                Ok(()) => {
+                    state.sessions.lock().unwrap().remove(&orphan.session_id);
+                    killed += 1;
+                }
+                Err(err) => warn!("Failed to kill terminal {}: {err}", orphan.session_id),
             }
         }
+        Ok(killed)
     }
+    #[cfg(not(unix))]
+    {
+        let _ = (session_ids, state);
+        Ok(0)
+    }
+}
 
-    let pty_system = NativePtySystem::default();
-    let size = PtySize {
-        rows: payload.rows,
-        cols: payload.cols,
-        pixel_width: 0,
-        pixel_height: 0,
+#[derive(Serialize)]
+pub struct TerminalProfileInfo {
+    pub name: String,
+    pub shell: Option<String>,
+    /// Offered because the shell is installed, not configured in settings
+    pub builtin: bool,
+    pub is_default: bool,
+}
+
+/// Profiles from settings followed by installed shells they don't shadow
+#[tauri::command]
+pub async fn list_terminal_profiles(
+    runtime: State<'_, DesktopRuntime>,
+) -> Result<Vec<TerminalProfileInfo>, String> {
+    let settings = runtime.settings();
+    let configured = settings
+        .terminal_profiles()
+        .await
+        .map_err(|e| e.to_string())?;
+    let default_name = settings.default_terminal_profile().await;
+
+    let builtins: Vec<ShellProfile> = builtin_profiles()
+        .into_iter()
+        .filter(|builtin| {
+            !configured
+                .iter()
+                .any(|profile| profile.name == builtin.name)
+        })
+        .collect();
+    Ok(configured
+        .into_iter()
+        .map(|profile| (profile, false))
+        .chain(builtins.into_iter().map(|profile| (profile, true)))
+        .map(|(profile, builtin)| TerminalProfileInfo {
+            is_default: default_name.as_deref() == Some(profile.name.as_str()),
+            name: profile.name,
+            shell: profile.shell,
+            builtin,
+        })
+        .collect())
+}
+
+/// Find a profile by name, falling back to `defaultTerminalProfile` and then to `$SHELL`
+async fn resolve_profile(
+    runtime: &DesktopRuntime,
+    name: Option<&str>,
+) -> Result<ShellProfile, String> {
+    let settings = runtime.settings();
+    let requested = name.map(str::to_string);
+    let Some(wanted) = requested
+        .clone()
+        .or(settings.default_terminal_profile().await)
+    else {
+        return Ok(ShellProfile::default());
     };
 
-    let working_dir = resolve_working_directory(Some(&payload.cwd))?;
-    let shell_path = resolve_shell();
+    let configured = settings
+        .terminal_profiles()
+        .await
+        .map_err(|e| e.to_string())?;
+    let found = configured
+        .into_iter()
+        .chain(builtin_profiles())
+        .find(|profile| profile.name == wanted);
+    match (found, requested) {
+        (Some(profile), _) => Ok(profile),
+        (None, Some(_)) => Err(format!("Unknown terminal profile: {wanted}")),
+        (None, None) => {
+            warn!("Default terminal profile {wanted} not found; using the login shell");
+            Ok(ShellProfile::default())
+        }
+    }
+}
+
+fn build_spawn_spec(
+    cols: u16,
+    rows: u16,
+    cwd: Option<&str>,
+    profile: &ShellProfile,
+) -> Result<SpawnSpec, String> {
+    let cwd = resolve_working_directory(cwd)?;
+    let shell = match &profile.shell {
+        Some(shell) => resolve_shell_path(shell)?,
+        None => resolve_shell(),
+    };
+    let mut args = Vec::new();
+    if profile
+        .login
+        .unwrap_or_else(|| shell_accepts_login_flag(&shell))
+    {
+        args.push("-l".to_string());
+    }
+    args.extend(profile.args.iter().cloned());
+    let mut env = terminal_environment(&shell);
+    for (key, value) in &profile.env {
+        set_env(&mut env, key, value);
+    }
+    if profile.shell_integration != Some(false) {
+        match shell_integration::environment(&shell, &env) {
+            Ok(vars) => {
+                for (key, value) in vars {
+                    set_env(&mut env, &key, &value);
+                }
+            }
+            Err(err) => warn!("Shell integration unavailable: {err}"),
+        }
+    }
+    Ok(SpawnSpec {
+        shell,
+        args,
+        cwd,
+        env,
+        cols,
+        rows,
+    })
+}
 
-    let mut cmd = CommandBuilder::new(&shell_path);
-    if shell_accepts_login_flag(&shell_path) {
-        cmd.arg("-l");
+fn set_env(env: &mut Vec<(String, String)>, key: &str, value: &str) {
+    match env.iter_mut().find(|(existing, _)| existing == key) {
+        Some(entry) => entry.1 = value.to_string(),
+        None => env.push((key.to_string(), value.to_string())),
     }
-    if let Some(cwd) = working_dir.to_str() {
-        cmd.cwd(cwd);
+}
+
+/// Start an interactive shell and type the profile's initial command into it
+fn spawn_session(
+    state: &TerminalState,
+    window: Window,
+    spec: SpawnSpec,
+    profile: ShellProfile,
+    detached: bool,
+) -> Result<String, String> {
+    let initial_command = profile
+        .initial_command()
+        .map(|command| format!("{command}\n"));
+
+    #[cfg(unix)]
+    let session_id = if detached {
+        spawn_detached_session(state, window, spec, profile)?
+    } else {
+        spawn_local_session(state, TerminalOutput::new(window), spec, profile)?
+    };
+    #[cfg(not(unix))]
+    let session_id = {
+        if detached {
+            warn!("Detached terminals need Unix sockets; starting a regular terminal");
+        }
+        spawn_local_session(state, TerminalOutput::new(window), spec, profile)?
+    };
+
+    if let Some(command) = initial_command {
+        if let Some(session) = state.sessions.lock().unwrap().get(&session_id) {
+            if let Err(err) = session.write_input(&session_id, &command) {
+                warn!("Failed to send initial command to terminal {session_id}: {err}");
+            }
+        }
     }
-    apply_terminal_environment(&mut cmd, &shell_path);
+    Ok(session_id)
+}
 
-    let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
+fn spawn_local_session(
+    state: &TerminalState,
+    output: TerminalOutput,
+    spec: SpawnSpec,
+    profile: ShellProfile,
+) -> Result<String, String> {
+    let pair = NativePtySystem::default()
+        .openpty(spec.size())
+        .map_err(|e| e.to_string())?;
     let child = pair
         .slave
-        .spawn_command(cmd)
+        .spawn_command(spec.command())
         .map_err(|e| format!("Failed to spawn shell: {e}"))?;
     drop(pair.slave);
 
@@ -235,86 +1259,263 @@ pub async fn restart_terminal_session(
             .take_writer()
             .map_err(|e| format!("Failed to take PTY writer: {e}"))?,
     ));
-    let master = pair.master;
-    let child = Arc::new(Mutex::new(child));
+    let killer = child.clone_killer();
+    let pid = child.process_id();
+    let (project, worktree) = project_binding(&spec.cwd);
+    let output = Arc::new(Mutex::new(output));
 
     let session_id = uuid::Uuid::new_v4().to_string();
     state.sessions.lock().unwrap().insert(
         session_id.clone(),
         TerminalSession {
-            master,
-            writer: writer.clone(),
-            child: child.clone(),
+            backend: TerminalBackend::Local {
+                master: pair.master,
+                writer,
+                killer,
+            },
+            output: output.clone(),
+            pid,
+            profile,
+            project,
+            worktree,
+            cwd: spec.cwd,
+            shell: spec.shell,
+            cols: spec.cols,
+            rows: spec.rows,
+            created_at: Utc::now(),
+            agent_access: false,
         },
     );
 
-    spawn_reader_thread(reader, window.clone(), session_id.clone());
-    spawn_exit_watcher(child, window, state.sessions.clone(), session_id.clone());
+    let reader_done = spawn_reader_thread(reader, output.clone(), session_id.clone());
+    spawn_exit_watcher(child, reader_done, output, state, session_id.clone());
 
-    Ok(CreateTerminalResponse { session_id })
+    Ok(session_id)
 }
 
-#[derive(Deserialize)]
-pub struct ForceKillPayload {
-    pub session_id: Option<String>,
-    pub cwd: Option<String>,
+#[cfg(unix)]
+fn spawn_detached_session(
+    state: &TerminalState,
+    window: Window,
+    spec: SpawnSpec,
+    profile: ShellProfile,
+) -> Result<String, String> {
+    let daemon = DaemonClient::new().map_err(|e| e.to_string())?;
+    daemon
+        .ensure_running()
+        .map_err(|e| format!("Failed to start terminal daemon: {e}"))?;
+
+    let cwd = spec.cwd.clone();
+    let (project, worktree) = project_binding(&cwd);
+    let shell = spec.shell.clone();
+    let (cols, rows) = (spec.cols, spec.rows);
+    let (session_id, pid) = daemon
+        .create(spec)
+        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
+
+    let output = Arc::new(Mutex::new(TerminalOutput::new(window)));
+    stream_daemon_session(&daemon, &session_id, output.clone(), state.sessions.clone())?;
+    state.sessions.lock().unwrap().insert(
+        session_id.clone(),
+        TerminalSession {
+            backend: TerminalBackend::Detached {
+                daemon,
+                streaming: true,
+            },
+            output,
+            pid,
+            profile,
+            cwd,
+            project,
+            worktree,
+            shell,
+            cols,
+            rows,
+            created_at: Utc::now(),
+            agent_access: false,
+        },
+    );
+
+    Ok(session_id)
 }
 
-#[tauri::command]
-pub async fn force_kill_terminal(
-    payload: ForceKillPayload,
-    state: State<'_, TerminalState>,
+/// Subscribe to a daemon session and forward its output like a local PTY's
+#[cfg(unix)]
+fn stream_daemon_session(
+    daemon: &DaemonClient,
+    session_id: &str,
+    output: Arc<Mutex<TerminalOutput>>,
+    sessions: Arc<Mutex<HashMap<String, TerminalSession>>>,
 ) -> Result<(), String> {
-    let mut sessions = state.sessions.lock().unwrap();
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
 
-    if let Some(session_id) = payload.session_id {
-        // Kill by session_id
-        if let Some(session) = sessions.remove(&session_id) {
-            if let Ok(mut child) = session.child.lock() {
-                let _ = child.kill();
-            }
-        }
-    } else if let Some(cwd) = payload.cwd {
-        let ids: Vec<String> = sessions.keys().cloned().collect();
-        for id in ids {
-            if let Some(session) = sessions.remove(&id) {
-                if let Ok(mut child) = session.child.lock() {
-                    let _ = child.kill();
+    let (scrollback, offset, mut events) = daemon
+        .attach(session_id)
+        .map_err(|e| format!("Failed to attach to terminal: {e}"))?;
+    output.lock().unwrap().restore(&scrollback, offset);
+
+    let session_id = session_id.to_string();
+    let (chunks, flushed) = spawn_output_pump(output.clone(), &session_id);
+    thread::spawn(move || {
+        let event_name = format!("terminal://{}", session_id);
+        let (exit_code, signal) = loop {
+            match events.next_event() {
+                Some(DaemonEvent::Data { data, .. }) => {
+                    if let Ok(bytes) = STANDARD.decode(data) {
+                        let _ = chunks.send(bytes);
+                    }
                 }
+                Some(DaemonEvent::Exit { exit_code, signal }) => break (exit_code, signal),
+                None => break (1, Some("Terminal daemon stopped".to_string())),
             }
+        };
+        drop(chunks);
+        let _ = flushed.recv_timeout(OUTPUT_DRAIN_TIMEOUT);
+
+        let payload = serde_json::json!({
+            "type": "exit",
+            "exitCode": exit_code,
+            "signal": signal
+        });
+        let mut output = output.lock().unwrap();
+        output.stop_recording();
+        let _ = output.emit(&event_name, payload);
+        drop(output);
+        sessions.lock().unwrap().remove(&session_id);
+    });
+    Ok(())
+}
+
+/// Pick up shells the daemon hosts from an earlier run and drop ones it no longer has
+#[cfg(unix)]
+fn sync_daemon_sessions(state: &TerminalState, window: &Window) {
+    let Ok(daemon) = DaemonClient::new() else {
+        return;
+    };
+    if !daemon.is_running() {
+        return;
+    }
+    let hosted = match daemon.list() {
+        Ok(hosted) => hosted,
+        Err(err) => {
+            warn!("Failed to list daemon terminals: {err}");
+            return;
         }
-        let _ = cwd;
-    } else {
-        let ids: Vec<String> = sessions.keys().cloned().collect();
-        for id in ids {
-            if let Some(session) = sessions.remove(&id) {
-                if let Ok(mut child) = session.child.lock() {
-                    let _ = child.kill();
-                }
+    };
+
+    let mut sessions = state.sessions.lock().unwrap();
+    sessions.retain(|session_id, session| {
+        !session.is_detached() || hosted.iter().any(|h| &h.session_id == session_id)
+    });
+    for hosted in hosted {
+        sessions.entry(hosted.session_id).or_insert_with(|| {
+            let (project, worktree) = project_binding(&hosted.cwd);
+            TerminalSession {
+                backend: TerminalBackend::Detached {
+                    daemon: daemon.clone(),
+                    streaming: false,
+                },
+                output: Arc::new(Mutex::new(TerminalOutput::new(window.clone()))),
+                pid: hosted.pid,
+                profile: ShellProfile {
+                    shell: Some(hosted.shell.clone()),
+                    ..ShellProfile::default()
+                },
+                cwd: hosted.cwd,
+                project,
+                worktree,
+                shell: hosted.shell,
+                cols: hosted.cols,
+                rows: hosted.rows,
+                created_at: hosted.created_at,
+                agent_access: false,
             }
-        }
+        });
     }
+}
 
-    Ok(())
+#[cfg(unix)]
+fn orphaned_sessions() -> Result<Vec<TerminalSessionInfo>, String> {
+    let daemon = DaemonClient::new().map_err(|e| e.to_string())?;
+    if !daemon.is_running() {
+        return Ok(Vec::new());
+    }
+    let hosted = daemon
+        .list()
+        .map_err(|e| format!("Failed to list daemon terminals: {e}"))?;
+    Ok(hosted
+        .into_iter()
+        .filter(|session| session.attached_clients == 0)
+        .map(|session| {
+            let (project, worktree) = project_binding(&session.cwd);
+            TerminalSessionInfo {
+                session_id: session.session_id,
+                cwd: session.cwd.to_string_lossy().to_string(),
+                project: project.to_string_lossy().to_string(),
+                worktree,
+                shell: session.shell,
+                cols: session.cols,
+                rows: session.rows,
+                created_at: session.created_at,
+                window: String::new(),
+                detached: true,
+                pid: session.pid,
+                agent_access: false,
+                command: None,
+                profile: None,
+                recording: None,
+            }
+        })
+        .collect())
 }
 
-fn spawn_reader_thread(mut reader: Box<dyn Read + Send>, window: Window, session_id: String) {
+/// Forward chunks sent to the returned sender, batching bursts into fewer events.
+/// The receiver disconnects once the sender is dropped and everything has been delivered.
+fn spawn_output_pump(
+    output: Arc<Mutex<TerminalOutput>>,
+    session_id: &str,
+) -> (mpsc::Sender<Vec<u8>>, mpsc::Receiver<()>) {
+    let (sender, chunks) = mpsc::channel::<Vec<u8>>();
+    let (done, flushed) = mpsc::channel::<()>();
+    let event_name = format!("terminal://{}", session_id);
     thread::spawn(move || {
-        let mut buffer = [0u8; 16384];
-        let event_name = format!("terminal://{}", session_id);
+        let _done = done;
+        while let Ok(mut batch) = chunks.recv() {
+            // A full read means more is waiting; interactive echo goes out without delay
+            let deadline =
+                (batch.len() >= READ_BUFFER_SIZE).then(|| Instant::now() + FLUSH_INTERVAL);
+            while batch.len() < MAX_BATCH_BYTES {
+                let next = match deadline {
+                    Some(deadline) => chunks
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                        .ok(),
+                    None => chunks.try_recv().ok(),
+                };
+                let Some(chunk) = next else { break };
+                batch.extend_from_slice(&chunk);
+            }
+            if let Err(error) = output.lock().unwrap().push(&event_name, &batch) {
+                error!("Failed to emit terminal data: {error}");
+            }
+        }
+    });
+    (sender, flushed)
+}
+
+/// The returned receiver disconnects once the PTY reaches end of output and all of it was forwarded
+fn spawn_reader_thread(
+    mut reader: Box<dyn Read + Send>,
+    output: Arc<Mutex<TerminalOutput>>,
+    session_id: String,
+) -> mpsc::Receiver<()> {
+    let (chunks, flushed) = spawn_output_pump(output, &session_id);
+    thread::spawn(move || {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
         loop {
             match reader.read(&mut buffer) {
                 Ok(0) => break,
                 Ok(n) => {
-                    let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    if data.is_empty() {
-                        continue;
-                    }
-
-                    if let Err(error) =
-                        window.emit(&event_name, serde_json::json!({ "type": "data", "data": data }))
-                    {
-                        error!("Failed to emit terminal data: {error}");
+                    if chunks.send(buffer[..n].to_vec()).is_err() {
                         break;
                     }
                 }
@@ -325,19 +1526,20 @@ fn spawn_reader_thread(mut reader: Box<dyn Read + Send>, window: Window, session
             }
         }
     });
+    flushed
 }
 
 fn spawn_exit_watcher(
-    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
-    window: Window,
-    sessions: Arc<Mutex<HashMap<String, TerminalSession>>>,
+    mut child: Box<dyn portable_pty::Child + Send + Sync>,
+    reader_done: mpsc::Receiver<()>,
+    output: Arc<Mutex<TerminalOutput>>,
+    state: &TerminalState,
     session_id: String,
 ) {
+    let sessions = state.sessions.clone();
+    let finished_runs = state.finished_runs.clone();
     thread::spawn(move || {
-        let status = {
-            let mut guard = child.lock().expect("terminal child poisoned");
-            guard.wait()
-        };
+        let status = child.wait();
 
         let (exit_code, signal) = match status {
             Ok(status) => (
@@ -349,15 +1551,34 @@ fn spawn_exit_watcher(
                 (1, Some("Terminal crashed".to_string()))
             }
         };
+        // Let the last output land before the exit event; background jobs may hold the PTY open
+        let _ = reader_done.recv_timeout(OUTPUT_DRAIN_TIMEOUT);
 
         let event_name = format!("terminal://{}", session_id);
-        let payload = serde_json::json!({
+        let mut output = output.lock().unwrap();
+        output.stop_recording();
+        let result = output.command_result(exit_code, signal.clone());
+        let mut payload = serde_json::json!({
             "type": "exit",
             "exitCode": exit_code,
             "signal": signal
         });
-        let _ = window.emit(&event_name, payload);
+        if let Some(result) = &result {
+            payload["command"] = result.command.clone().into();
+            payload["durationMs"] = result.duration_ms.into();
+            payload["outputTail"] = result.output_tail.clone().into();
+        }
+        let _ = output.emit(&event_name, payload);
+        drop(output);
 
+        // Record the result before the session disappears so pollers never see neither
+        if let Some(result) = result {
+            let mut finished_runs = finished_runs.lock().unwrap();
+            finished_runs.push_back((session_id.clone(), result));
+            if finished_runs.len() > FINISHED_RUN_LIMIT {
+                finished_runs.pop_front();
+            }
+        }
         let mut sessions = sessions.lock().unwrap();
         sessions.remove(&session_id);
     });
@@ -387,6 +1608,33 @@ fn shell_accepts_login_flag(shell_path: &str) -> bool {
     )
 }
 
+/// The git checkout or worktree containing `cwd`, and whether it is a linked worktree.
+/// Outside a repository the terminal belongs to `cwd` itself.
+fn project_binding(cwd: &Path) -> (PathBuf, bool) {
+    for dir in cwd.ancestors() {
+        let git = dir.join(".git");
+        if git.is_dir() {
+            return (dir.to_path_buf(), false);
+        }
+        // Linked worktrees and submodules have a `.git` file pointing into the main repository
+        if git.is_file() {
+            let worktree = std::fs::read_to_string(&git)
+                .map(|contents| contents.contains("/worktrees/"))
+                .unwrap_or(false);
+            return (dir.to_path_buf(), worktree);
+        }
+    }
+    (cwd.to_path_buf(), false)
+}
+
+fn same_directory(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (std::fs::canonicalize(a), std::fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
 fn resolve_working_directory(input: Option<&str>) -> Result<PathBuf, String> {
     let maybe_path = input
         .map(|value| PathBuf::from(value))
@@ -406,25 +1654,30 @@ fn resolve_working_directory(input: Option<&str>) -> Result<PathBuf, String> {
     Ok(path)
 }
 
-fn apply_terminal_environment(cmd: &mut CommandBuilder, shell_path: &str) {
-    cmd.env(
-        "TERM",
-        env::var("TERM").unwrap_or_else(|_| DEFAULT_TERM.to_string()),
-    );
-    cmd.env(
-        "COLORTERM",
-        env::var("COLORTERM").unwrap_or_else(|_| DEFAULT_COLORTERM.to_string()),
-    );
-    cmd.env(
-        "LC_ALL",
-        env::var("LC_ALL").unwrap_or_else(|_| DEFAULT_LOCALE.to_string()),
-    );
-    cmd.env(
-        "LANG",
-        env::var("LANG").unwrap_or_else(|_| DEFAULT_LOCALE.to_string()),
-    );
-    cmd.env("TERM_PROGRAM", TERM_PROGRAM_NAME);
-    cmd.env("TERM_PROGRAM_VERSION", TERM_PROGRAM_VERSION);
-    cmd.env("OPENCHAMBER_DESKTOP", "1");
-    cmd.env("SHELL", shell_path);
+fn terminal_environment(shell_path: &str) -> Vec<(String, String)> {
+    vec![
+        (
+            "TERM".to_string(),
+            env::var("TERM").unwrap_or_else(|_| DEFAULT_TERM.to_string()),
+        ),
+        (
+            "COLORTERM".to_string(),
+            env::var("COLORTERM").unwrap_or_else(|_| DEFAULT_COLORTERM.to_string()),
+        ),
+        (
+            "LC_ALL".to_string(),
+            env::var("LC_ALL").unwrap_or_else(|_| DEFAULT_LOCALE.to_string()),
+        ),
+        (
+            "LANG".to_string(),
+            env::var("LANG").unwrap_or_else(|_| DEFAULT_LOCALE.to_string()),
+        ),
+        ("TERM_PROGRAM".to_string(), TERM_PROGRAM_NAME.to_string()),
+        (
+            "TERM_PROGRAM_VERSION".to_string(),
+            TERM_PROGRAM_VERSION.to_string(),
+        ),
+        ("OPENCHAMBER_DESKTOP".to_string(), "1".to_string()),
+        ("SHELL".to_string(), shell_path.to_string()),
+    ]
 }
@@ -0,0 +1,39 @@
+use tauri::State;
+
+use crate::notification_rules::RuleTrigger;
+use crate::webhooks::{sample_candidate, WebhookDelivery};
+use crate::DesktopRuntime;
+
+/// Recent webhook deliveries, newest first
+#[tauri::command]
+pub async fn list_webhook_deliveries(
+    state: State<'_, DesktopRuntime>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    Ok(state.webhooks().deliveries())
+}
+
+/// Send a sample event to one configured webhook and wait for the result
+#[tauri::command]
+pub async fn test_webhook(
+    webhook_id: String,
+    event: Option<RuleTrigger>,
+    state: State<'_, DesktopRuntime>,
+) -> Result<WebhookDelivery, String> {
+    let webhooks = state
+        .settings()
+        .webhooks()
+        .await
+        .map_err(|e| format!("Failed to load webhook settings: {}", e))?;
+    let webhook = webhooks
+        .into_iter()
+        .find(|webhook| webhook.id == webhook_id)
+        .ok_or_else(|| format!("Unknown webhook: {}", webhook_id))?;
+
+    let directory = state
+        .opencode_manager()
+        .get_working_directory()
+        .to_string_lossy()
+        .to_string();
+    let candidate = sample_candidate(event.unwrap_or(RuleTrigger::AssistantReady), directory);
+    Ok(state.webhooks().deliver(&webhook, &candidate).await)
+}
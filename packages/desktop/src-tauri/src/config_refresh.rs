@@ -0,0 +1,289 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{oneshot, Mutex};
+
+use crate::opencode_manager::OpenCodeManager;
+use crate::session_activity::SessionActivityStore;
+
+pub const CONFIG_REFRESH_EVENT: &str = "openchamber:config-refreshed";
+
+/// Quiet period after the last config edit before OpenCode is refreshed
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(400);
+/// Upper bound on how long a steady stream of edits can postpone a refresh
+const REFRESH_MAX_DELAY: Duration = Duration::from_secs(3);
+/// How often a postponed refresh checks whether the project's sessions have finished
+const DEFERRED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// A session can look busy forever after a missed idle event, so postponing stops here
+const DEFERRED_MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshMode {
+    Reload,
+    Restart,
+    /// Postponed because reloading would abort sessions that are still running; the
+    /// outcome of the refresh itself follows as another event once it has run
+    Deferred,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshOutcome {
+    pub success: bool,
+    pub mode: Option<RefreshMode>,
+    pub reasons: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl RefreshOutcome {
+    pub fn is_deferred(&self) -> bool {
+        self.mode == Some(RefreshMode::Deferred)
+    }
+}
+
+#[derive(Default)]
+struct PendingRefresh {
+    reasons: Vec<String>,
+    waiters: Vec<oneshot::Sender<RefreshOutcome>>,
+    first_request: Option<Instant>,
+    last_request: Option<Instant>,
+    scheduled: bool,
+    /// Refresh even if sessions are busy, once a postponed refresh has waited long enough
+    force: bool,
+}
+
+/// Reasons held back for a directory until its sessions finish, with the one task polling for that
+struct DeferredRefresh {
+    reasons: Vec<String>,
+    poll: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Coalesces config-change refreshes and prefers an in-place reload over a process restart
+#[derive(Clone)]
+pub struct ConfigRefresher {
+    app: AppHandle,
+    opencode: Arc<OpenCodeManager>,
+    activity: SessionActivityStore,
+    pending: Arc<Mutex<PendingRefresh>>,
+    deferred: Arc<Mutex<HashMap<String, DeferredRefresh>>>,
+    run_lock: Arc<Mutex<()>>,
+}
+
+impl ConfigRefresher {
+    pub fn new(
+        app: AppHandle,
+        opencode: Arc<OpenCodeManager>,
+        activity: SessionActivityStore,
+    ) -> Self {
+        Self {
+            app,
+            opencode,
+            activity,
+            pending: Arc::new(Mutex::new(PendingRefresh::default())),
+            deferred: Arc::new(Mutex::new(HashMap::new())),
+            run_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Queue a refresh and wait for the batch it lands in to complete
+    pub async fn request(&self, reason: &str) -> RefreshOutcome {
+        let (tx, rx) = oneshot::channel();
+        self.enqueue(vec![reason.to_string()], Some(tx), false)
+            .await;
+        rx.await.unwrap_or_else(|_| RefreshOutcome {
+            success: false,
+            mode: None,
+            reasons: vec![reason.to_string()],
+            error: Some("Config refresh was cancelled".to_string()),
+        })
+    }
+
+    async fn enqueue(
+        &self,
+        reasons: Vec<String>,
+        waiter: Option<oneshot::Sender<RefreshOutcome>>,
+        force: bool,
+    ) {
+        let mut pending = self.pending.lock().await;
+        let now = Instant::now();
+        pending.reasons.extend(reasons);
+        pending.waiters.extend(waiter);
+        pending.force |= force;
+        pending.first_request.get_or_insert(now);
+        pending.last_request = Some(now);
+
+        if !pending.scheduled {
+            pending.scheduled = true;
+            let refresher = self.clone();
+            tauri::async_runtime::spawn(async move {
+                refresher.run_debounced().await;
+            });
+        }
+    }
+
+    async fn run_debounced(&self) {
+        loop {
+            let wait = {
+                let pending = self.pending.lock().await;
+                let now = Instant::now();
+                let since_last = pending
+                    .last_request
+                    .map(|t| now.saturating_duration_since(t))
+                    .unwrap_or(REFRESH_DEBOUNCE);
+                let since_first = pending
+                    .first_request
+                    .map(|t| now.saturating_duration_since(t))
+                    .unwrap_or_default();
+
+                if since_last >= REFRESH_DEBOUNCE || since_first >= REFRESH_MAX_DELAY {
+                    None
+                } else {
+                    Some(
+                        (REFRESH_DEBOUNCE - since_last)
+                            .min(REFRESH_MAX_DELAY.saturating_sub(since_first)),
+                    )
+                }
+            };
+
+            match wait {
+                Some(duration) => tokio::time::sleep(duration).await,
+                None => break,
+            }
+        }
+
+        let run = self.run_lock.lock().await;
+        let (mut reasons, waiters, force) = {
+            let mut pending = self.pending.lock().await;
+            let batch = std::mem::take(&mut *pending);
+            (batch.reasons, batch.waiters, batch.force)
+        };
+
+        // Disposing the instance aborts whatever the project's sessions are doing
+        let directory = self
+            .opencode
+            .get_working_directory()
+            .to_string_lossy()
+            .to_string();
+        let busy = self.activity.busy_sessions(&directory).await;
+        if busy > 0 && !force {
+            info!(
+                "[desktop:config] Postponing OpenCode refresh after {} until {} running session(s) finish",
+                reasons.join(", "),
+                busy
+            );
+            let outcome = RefreshOutcome {
+                success: false,
+                mode: Some(RefreshMode::Deferred),
+                reasons: reasons.clone(),
+                error: None,
+            };
+            self.defer(&directory, reasons).await;
+            let _ = self.app.emit(CONFIG_REFRESH_EVENT, &outcome);
+            for waiter in waiters {
+                let _ = waiter.send(outcome.clone());
+            }
+            return;
+        }
+
+        // This refresh also applies whatever was postponed for the directory
+        if let Some(deferred) = self.deferred.lock().await.remove(&directory) {
+            deferred.poll.abort();
+            for reason in deferred.reasons {
+                if !reasons.contains(&reason) {
+                    reasons.push(reason);
+                }
+            }
+        }
+        drop(run);
+
+        info!(
+            "[desktop:config] Refreshing OpenCode after {}",
+            reasons.join(", ")
+        );
+
+        let outcome = match self.refresh().await {
+            Ok(mode) => RefreshOutcome {
+                success: true,
+                mode: Some(mode),
+                reasons,
+                error: None,
+            },
+            Err(err) => RefreshOutcome {
+                success: false,
+                mode: None,
+                reasons,
+                error: Some(err),
+            },
+        };
+
+        let _ = self.app.emit(CONFIG_REFRESH_EVENT, &outcome);
+        for waiter in waiters {
+            let _ = waiter.send(outcome.clone());
+        }
+    }
+
+    /// Hold `reasons` for `directory`, merged into its pending deferral if there is one
+    async fn defer(&self, directory: &str, reasons: Vec<String>) {
+        let mut deferred = self.deferred.lock().await;
+        if let Some(existing) = deferred.get_mut(directory) {
+            for reason in reasons {
+                if !existing.reasons.contains(&reason) {
+                    existing.reasons.push(reason);
+                }
+            }
+            return;
+        }
+
+        let refresher = self.clone();
+        let dir = directory.to_string();
+        let poll = tauri::async_runtime::spawn(async move {
+            let started = Instant::now();
+            loop {
+                tokio::time::sleep(DEFERRED_POLL_INTERVAL).await;
+                let busy = refresher.activity.busy_sessions(&dir).await;
+                if busy > 0 && started.elapsed() < DEFERRED_MAX_WAIT {
+                    continue;
+                }
+                if busy > 0 {
+                    warn!(
+                        "[desktop:config] {busy} session(s) in {dir} still look busy after {}s; refreshing OpenCode anyway",
+                        DEFERRED_MAX_WAIT.as_secs()
+                    );
+                }
+                let Some(deferred) = refresher.deferred.lock().await.remove(&dir) else {
+                    return;
+                };
+                refresher.enqueue(deferred.reasons, None, true).await;
+                return;
+            }
+        });
+        deferred.insert(directory.to_string(), DeferredRefresh { reasons, poll });
+    }
+
+    async fn refresh(&self) -> Result<RefreshMode, String> {
+        if self.opencode.is_ready() {
+            match self.opencode.reload_config().await {
+                Ok(true) => return Ok(RefreshMode::Reload),
+                Ok(false) => {
+                    info!("[desktop:config] Config reload not supported; restarting OpenCode");
+                }
+                Err(err) => {
+                    warn!("[desktop:config] Config reload failed, restarting OpenCode: {err}");
+                }
+            }
+        }
+
+        self.opencode
+            .restart()
+            .await
+            .map(|_| RefreshMode::Restart)
+            .map_err(|err| format!("Failed to restart OpenCode: {}", err))
+    }
+}
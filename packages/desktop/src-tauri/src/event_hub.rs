@@ -0,0 +1,360 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use axum::response::{
+    sse::{Event as SseEvent, KeepAlive, Sse},
+    IntoResponse, Response,
+};
+use futures_util::{stream, StreamExt};
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::opencode_manager::OpenCodeManager;
+use crate::sse::{ReconnectBackoff, SseParser};
+
+/// Local SSE endpoint the webview subscribes to instead of opening its own upstream stream
+pub const HUB_EVENTS_ROUTE: &str = "/api/openchamber/events";
+
+const HUB_CHANNEL_CAPACITY: usize = 1024;
+const PORT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WORKING_DIR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Sent to each webview client on attach; the upstream's own greeting only reaches the first one
+const CONNECTED_PAYLOAD: &str = r#"{"type":"server.connected","properties":{}}"#;
+
+#[derive(Deserialize)]
+struct EventEnvelope {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    properties: Value,
+}
+
+/// A single OpenCode event, parsed once and shared by every subscriber
+#[derive(Debug, Clone)]
+pub struct OpenCodeEvent {
+    pub directory: String,
+    pub event_type: String,
+    pub properties: Value,
+    /// SSE `id:` in effect for this event, if the server sends ids
+    pub id: Option<String>,
+    /// Original `data:` payload, forwarded verbatim to webview clients
+    pub data: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum HubEvent {
+    /// The upstream stream for `directory` (re)connected; anything before it may have been
+    /// missed, since sending Last-Event-ID does not guarantee a replay
+    Connected {
+        directory: String,
+    },
+    Event(OpenCodeEvent),
+}
+
+/// Resume position and reconnect pacing carried across reconnects of one upstream
+#[derive(Default)]
+struct UpstreamCursor {
+    port: Option<u16>,
+    last_event_id: Option<String>,
+    backoff: ReconnectBackoff,
+}
+
+struct Upstream {
+    watchers: usize,
+    pinned: bool,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+struct HubInner {
+    opencode: Arc<OpenCodeManager>,
+    client: Client,
+    sender: broadcast::Sender<Arc<HubEvent>>,
+    upstreams: Mutex<HashMap<String, Upstream>>,
+}
+
+/// One upstream `/event` connection per watched directory, fanned out to in-process and webview subscribers
+#[derive(Clone)]
+pub struct EventHub {
+    inner: Arc<HubInner>,
+}
+
+/// Keeps a directory's upstream connection alive while held
+pub struct WatchGuard {
+    hub: EventHub,
+    directory: String,
+}
+
+impl WatchGuard {
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        self.hub.release(&self.directory);
+    }
+}
+
+impl EventHub {
+    pub fn new(opencode: Arc<OpenCodeManager>) -> Result<Self> {
+        let client = Client::builder()
+            // Give SSE a very long overall timeout so idle periods don't abort the stream.
+            .timeout(Duration::from_secs(24 * 60 * 60))
+            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .build()?;
+        let (sender, _) = broadcast::channel(HUB_CHANNEL_CAPACITY);
+        Ok(Self {
+            inner: Arc::new(HubInner {
+                opencode,
+                client,
+                sender,
+                upstreams: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<HubEvent>> {
+        self.inner.sender.subscribe()
+    }
+
+    /// Ensure an upstream connection exists for `directory` until the guard is dropped
+    pub fn watch(&self, directory: &str) -> WatchGuard {
+        let mut upstreams = self.inner.upstreams.lock();
+        match upstreams.get_mut(directory) {
+            Some(upstream) => upstream.watchers += 1,
+            None => {
+                upstreams.insert(
+                    directory.to_string(),
+                    Upstream {
+                        watchers: 1,
+                        pinned: false,
+                        task: self.spawn_upstream(directory.to_string()),
+                    },
+                );
+            }
+        }
+        WatchGuard {
+            hub: self.clone(),
+            directory: directory.to_string(),
+        }
+    }
+
+    fn release(&self, directory: &str) {
+        let mut upstreams = self.inner.upstreams.lock();
+        let Some(upstream) = upstreams.get_mut(directory) else {
+            return;
+        };
+        upstream.watchers = upstream.watchers.saturating_sub(1);
+        if upstream.watchers == 0 && !upstream.pinned {
+            if let Some(upstream) = upstreams.remove(directory) {
+                upstream.task.abort();
+                debug!("[desktop:events] Closed upstream for {directory}");
+            }
+        }
+    }
+
+    /// Keep exactly one pinned upstream: the manager's current working directory
+    fn pin_working_directory(&self, directory: &str) {
+        let mut upstreams = self.inner.upstreams.lock();
+        upstreams.retain(|key, upstream| {
+            if key == directory {
+                return true;
+            }
+            upstream.pinned = false;
+            if upstream.watchers == 0 {
+                upstream.task.abort();
+                return false;
+            }
+            true
+        });
+        match upstreams.get_mut(directory) {
+            Some(upstream) => upstream.pinned = true,
+            None => {
+                upstreams.insert(
+                    directory.to_string(),
+                    Upstream {
+                        watchers: 0,
+                        pinned: true,
+                        task: self.spawn_upstream(directory.to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Follow the working directory and tear everything down on shutdown
+    pub fn spawn(&self, mut shutdown_rx: broadcast::Receiver<()>) {
+        let hub = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut pinned: Option<String> = None;
+            loop {
+                let current = hub
+                    .inner
+                    .opencode
+                    .get_working_directory()
+                    .to_string_lossy()
+                    .to_string();
+                if pinned.as_deref() != Some(current.as_str()) {
+                    hub.pin_working_directory(&current);
+                    pinned = Some(current);
+                }
+
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = tokio::time::sleep(WORKING_DIR_POLL_INTERVAL) => {}
+                }
+            }
+
+            info!("[desktop:events] Shutdown received, closing upstream streams");
+            for (_, upstream) in hub.inner.upstreams.lock().drain() {
+                upstream.task.abort();
+            }
+        });
+    }
+
+    fn spawn_upstream(&self, directory: String) -> tauri::async_runtime::JoinHandle<()> {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut cursor = UpstreamCursor::default();
+            loop {
+                let Some(port) = inner.opencode.current_port() else {
+                    tokio::time::sleep(PORT_POLL_INTERVAL).await;
+                    continue;
+                };
+                if let Err(err) = run_upstream(&inner, &directory, port, &mut cursor).await {
+                    warn!("[desktop:events] SSE loop error for {directory}: {err:?}");
+                }
+                tokio::time::sleep(cursor.backoff.next_delay()).await;
+            }
+        })
+    }
+
+    /// SSE response for a webview client; `directory` defaults to the working directory
+    pub fn sse_response(&self, directory: Option<String>) -> Response {
+        let directory = directory
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| {
+                self.inner
+                    .opencode
+                    .get_working_directory()
+                    .to_string_lossy()
+                    .to_string()
+            });
+
+        // Subscribe before watching so nothing from a freshly opened upstream is missed
+        let receiver = self.subscribe();
+        let guard = self.watch(&directory);
+
+        let greeting = stream::once(async {
+            Ok::<_, Infallible>(SseEvent::default().data(CONNECTED_PAYLOAD))
+        });
+        let events = stream::unfold((receiver, guard), |(mut receiver, guard)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if let HubEvent::Event(event) = event.as_ref() {
+                            // Each client already got its own greeting above
+                            if event.directory == guard.directory()
+                                && event.event_type != "server.connected"
+                            {
+                                let mut sse = SseEvent::default().data(event.data.clone());
+                                if let Some(id) = event.id.as_deref() {
+                                    sse = sse.id(id);
+                                }
+                                return Some((Ok(sse), (receiver, guard)));
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "[desktop:events] Webview subscriber lagged; dropped {skipped} events"
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Sse::new(greeting.chain(events))
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    }
+}
+
+async fn run_upstream(
+    inner: &HubInner,
+    directory: &str,
+    port: u16,
+    cursor: &mut UpstreamCursor,
+) -> Result<()> {
+    // Event ids from a previous OpenCode process mean nothing to a new one
+    if cursor.port != Some(port) {
+        cursor.port = Some(port);
+        cursor.last_event_id = None;
+    }
+
+    let prefix = inner.opencode.api_prefix();
+    let mut url = reqwest::Url::parse(&format!("http://127.0.0.1:{port}{prefix}/event"))?;
+    url.query_pairs_mut().append_pair("directory", directory);
+
+    debug!("[desktop:events] Connecting SSE: {url}");
+    let mut request = inner
+        .client
+        .get(url.clone())
+        .header("accept", "text/event-stream")
+        .header("accept-encoding", "identity");
+    if let Some(last_event_id) = cursor.last_event_id.as_deref() {
+        request = request.header("last-event-id", last_event_id);
+    }
+    let response = request.send().await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("SSE connect failed with status {}", response.status());
+    }
+
+    let _ = inner.sender.send(Arc::new(HubEvent::Connected {
+        directory: directory.to_string(),
+    }));
+
+    let mut parser = SseParser::with_last_event_id(cursor.last_event_id.clone());
+    let mut stream = response.bytes_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        for message in parser.feed(&chunk) {
+            cursor.backoff.reset();
+            // OpenCode only sends unnamed events; anything else is not an event envelope
+            if message.event != "message" {
+                debug!("[desktop:events] Ignoring SSE event {:?}", message.event);
+                continue;
+            }
+            match serde_json::from_str::<EventEnvelope>(&message.data) {
+                Ok(envelope) => {
+                    let _ = inner.sender.send(Arc::new(HubEvent::Event(OpenCodeEvent {
+                        directory: directory.to_string(),
+                        event_type: envelope.event_type,
+                        properties: envelope.properties,
+                        id: message.id,
+                        data: message.data,
+                    })));
+                }
+                Err(err) => warn!(
+                    "[desktop:events] Failed to parse SSE data: {err}; raw={}",
+                    message.data
+                ),
+            }
+        }
+        cursor.last_event_id = parser.last_event_id().map(str::to_string);
+        if let Some(retry) = parser.retry() {
+            cursor.backoff.set_retry_hint(retry);
+        }
+    }
+
+    Ok(())
+}
@@ -2,18 +2,37 @@
 
 mod assistant_notifications;
 mod commands;
+mod config_refresh;
+mod event_hub;
 mod logging;
+mod notification_history;
+mod notification_rules;
 mod opencode_auth;
 mod opencode_config;
+mod opencode_installer;
 mod opencode_manager;
 mod path_utils;
+mod permission_requests;
+mod remote_access;
+mod server_auth;
 mod session_activity;
+mod shell_integration;
 mod skills_catalog;
+mod sse;
+mod terminal_bridge;
+#[cfg(unix)]
+mod terminal_daemon;
+mod terminal_process;
+mod terminal_profile;
+mod terminal_pty;
+mod terminal_recording;
+mod tray;
+mod webhooks;
 mod window_state;
 
 use std::{
     collections::HashMap,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -21,10 +40,14 @@ use std::{
 use anyhow::{anyhow, Result};
 use assistant_notifications::spawn_assistant_notifications;
 use axum::{
-    body::{to_bytes, Body},
-    extract::{OriginalUri, State},
-    http::{Method, Request, Response, StatusCode},
+    body::{to_bytes, Body, HttpBody},
+    extract::{
+        ws::{CloseFrame as WsCloseFrame, Message as WsMessage, WebSocketUpgrade},
+        FromRequestParts, OriginalUri, Query, State,
+    },
+    http::{HeaderMap, HeaderName, Method, Request, Response, StatusCode},
     response::IntoResponse,
+    middleware,
     routing::{any, get, post},
     Json, Router,
 };
@@ -39,43 +62,76 @@ use commands::git::{
     update_git_identity,
 };
 use commands::logs::fetch_desktop_logs;
-use commands::notifications::desktop_notify;
+use commands::notifications::{
+    clear_notification_history, desktop_notify, list_notification_history,
+    mark_notifications_read,
+};
+use commands::opencode::{
+    get_opencode_installations, install_opencode_version, rollback_opencode_version,
+    select_opencode_version,
+};
+use commands::permission_requests::{list_permission_requests, reply_permission_request};
 use commands::permissions::{
     pick_directory, process_directory_selection, request_directory_access,
     restore_bookmarks_on_startup, start_accessing_directory, stop_accessing_directory,
 };
+use commands::remote_access::{
+    get_remote_access_status, list_paired_devices, revoke_paired_device, set_remote_access,
+    start_remote_pairing,
+};
+use commands::session_activity::get_session_activity;
 use commands::settings::{load_settings, restart_opencode, save_settings};
 use commands::terminal::{
-    close_terminal, create_terminal_session, force_kill_terminal, resize_terminal,
-    restart_terminal_session, send_terminal_input, TerminalState,
+    attach_terminal_channel, attach_terminal_session, close_terminal, create_terminal_session,
+    export_terminal_recording_text, force_kill_terminal, get_terminal_command_result,
+    get_terminal_cwd, kill_orphaned_terminal_sessions, list_orphaned_terminal_sessions,
+    list_terminal_processes, list_terminal_profiles, list_terminal_recordings,
+    list_terminal_sessions, resize_terminal, restart_terminal_session, run_terminal_command,
+    send_terminal_input, set_terminal_agent_access, signal_terminal_process,
+    start_terminal_recording, stop_terminal_recording, TerminalState,
 };
-use futures_util::StreamExt as FuturesStreamExt;
+use commands::webhooks::{list_webhook_deliveries, test_webhook};
+use config_refresh::ConfigRefresher;
+use event_hub::EventHub;
+use futures_util::{SinkExt, StreamExt as FuturesStreamExt};
 use log::{error, info, warn};
-use opencode_manager::OpenCodeManager;
+use notification_history::NotificationHistory;
+use notification_rules::NotificationSettings;
+use opencode_manager::{LaunchProfile, OpenCodeManager};
 use path_utils::expand_tilde_path;
+use permission_requests::PendingPermissions;
 use portpicker::pick_unused_port;
+use remote_access::{DeviceRegistry, RemoteAccessConfig, RemoteAccessServer};
 use reqwest::{header, Body as ReqwestBody, Client};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use session_activity::spawn_session_activity_tracker;
+use session_activity::{spawn_session_activity_tracker, SessionActivityStore};
 #[cfg(feature = "devtools")]
 use tauri::WebviewWindow;
 use tauri::{Emitter, Manager};
 use tauri_plugin_dialog::init as dialog_plugin;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_fs::init as fs_plugin;
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_notification::init as notification_plugin;
 use tauri_plugin_shell::init as shell_plugin;
+use terminal_profile::ShellProfile;
 use tokio::{
     fs,
     net::TcpListener,
-    sync::{broadcast, Mutex},
+    sync::{broadcast, oneshot, Mutex},
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        client::IntoClientRequest, protocol::CloseFrame as TungsteniteCloseFrame,
+        Message as TungsteniteMessage,
+    },
 };
-use tower_http::cors::CorsLayer;
+use webhooks::{WebhookConfig, WebhookDispatcher};
 use window_state::{load_window_state, persist_window_state, WindowStateManager};
 
-#[cfg(target_os = "macos")]
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 #[cfg(target_os = "macos")]
 use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
@@ -83,6 +139,9 @@ use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 #[cfg(target_os = "macos")]
 static NEEDS_TRAFFIC_LIGHT_FIX: AtomicBool = AtomicBool::new(false);
 
+/// Set while a quit is being confirmed, so repeated Cmd+Q or close clicks ask only once
+static QUIT_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
 const PROXY_BODY_LIMIT: usize = 32 * 1024 * 1024; // 32MB
 const CLIENT_RELOAD_DELAY_MS: u64 = 800;
 const MODELS_DEV_API_URL: &str = "https://models.dev/api.json";
@@ -149,39 +208,83 @@ const DISCORD_INVITE_URL: &str = "https://discord.gg/ZYRSdnwwKA";
 #[derive(Clone)]
 pub(crate) struct DesktopRuntime {
     server_port: u16,
+    auth_token: Arc<str>,
     shutdown_tx: broadcast::Sender<()>,
     opencode: Arc<OpenCodeManager>,
     settings: Arc<SettingsStore>,
+    remote: Arc<RemoteAccessServer>,
+    event_hub: EventHub,
+    session_activity: SessionActivityStore,
+    permission_requests: PendingPermissions,
+    notification_history: Arc<NotificationHistory>,
+    webhooks: WebhookDispatcher,
 }
 
 impl DesktopRuntime {
-    fn initialize_sync() -> Result<Self> {
+    fn initialize_sync(app: &tauri::AppHandle) -> Result<Self> {
         let settings = Arc::new(SettingsStore::new()?);
         let initial_dir = tauri::async_runtime::block_on(settings.last_directory())
             .ok()
             .flatten();
-        let opencode = Arc::new(OpenCodeManager::new_with_directory(initial_dir.clone()));
+        let server_port =
+            pick_unused_port().ok_or_else(|| anyhow!("No free port available"))? as u16;
+        let bridge_token = server_auth::generate_token();
+        let opencode = Arc::new(
+            OpenCodeManager::new_with_directory(initial_dir.clone())
+                .with_env(terminal_bridge::environment(server_port, &bridge_token)),
+        );
+        if let Some(dir) = initial_dir.as_deref() {
+            tauri::async_runtime::block_on(apply_launch_profile(&opencode, &settings, dir));
+        }
 
         let client = Client::builder().build()?;
 
         let (shutdown_tx, shutdown_rx) = broadcast::channel(2);
-        let server_port =
-            pick_unused_port().ok_or_else(|| anyhow!("No free port available"))? as u16;
+        let event_hub = EventHub::new(opencode.clone())?;
+        event_hub.spawn(shutdown_tx.subscribe());
+        let auth_token = server_auth::generate_token();
+        let session_activity = SessionActivityStore::default();
         let server_state = ServerState {
             client,
             opencode: opencode.clone(),
             server_port,
+            settings: settings.clone(),
+            config_refresher: ConfigRefresher::new(
+                app.clone(),
+                opencode.clone(),
+                session_activity.clone(),
+            ),
+            event_hub: event_hub.clone(),
             directory_change_lock: Arc::new(Mutex::new(())),
             models_metadata_cache: Arc::new(Mutex::new(ModelsMetadataCache::default())),
         };
 
-        spawn_http_server(server_port, server_state, shutdown_rx);
+        let remote = Arc::new(RemoteAccessServer::new(
+            Arc::new(DeviceRegistry::load()?),
+            api_router(server_state.clone()),
+        ));
+        let server_access = ServerAccess {
+            auth_token: auth_token.clone(),
+            cors: server_auth::cors_layer(app),
+        };
+        let bridge = terminal_bridge::router(
+            app.state::<TerminalState>().inner().clone(),
+            bridge_token,
+        );
+        spawn_http_server(server_port, server_state, server_access, bridge, shutdown_rx);
 
         Ok(Self {
             server_port,
+            auth_token,
             shutdown_tx,
             opencode,
             settings,
+            remote,
+            event_hub,
+            session_activity,
+            permission_requests: PendingPermissions::default(),
+            notification_history: Arc::new(NotificationHistory::load()?),
+            webhooks: WebhookDispatcher::new()?,
         })
     }
 
@@ -195,8 +298,19 @@ impl DesktopRuntime {
         }
     }
 
+    /// Bring the LAN listener in line with the `remoteAccess` settings
+    pub(crate) async fn apply_remote_access(&self) -> Result<()> {
+        let config = self.settings.remote_access().await?;
+        self.remote.apply(&config).await
+    }
+
+    pub(crate) fn remote_access(&self) -> &RemoteAccessServer {
+        self.remote.as_ref()
+    }
+
     async fn shutdown(&self) {
         let _ = self.shutdown_tx.send(());
+        self.remote.shutdown().await;
         let _ = self.opencode.shutdown().await;
     }
 
@@ -211,6 +325,38 @@ impl DesktopRuntime {
     pub(crate) fn opencode_manager(&self) -> Arc<OpenCodeManager> {
         self.opencode.clone()
     }
+
+    pub(crate) fn event_hub(&self) -> &EventHub {
+        &self.event_hub
+    }
+
+    pub(crate) fn session_activity(&self) -> &SessionActivityStore {
+        &self.session_activity
+    }
+
+    pub(crate) fn permission_requests(&self) -> &PendingPermissions {
+        &self.permission_requests
+    }
+
+    pub(crate) fn notification_history(&self) -> &NotificationHistory {
+        self.notification_history.as_ref()
+    }
+
+    pub(crate) fn webhooks(&self) -> &WebhookDispatcher {
+        &self.webhooks
+    }
+
+    /// Re-read the launch profile for the current directory so settings edits apply on restart
+    pub(crate) async fn reload_launch_profile(&self) {
+        let directory = self.opencode.get_working_directory();
+        apply_launch_profile(&self.opencode, &self.settings, &directory).await;
+    }
+
+    /// Restart OpenCode with the launch profile re-read from settings
+    pub(crate) async fn restart_opencode(&self) -> Result<()> {
+        self.reload_launch_profile().await;
+        self.opencode.restart().await
+    }
 }
 
 #[derive(Clone)]
@@ -218,10 +364,19 @@ struct ServerState {
     client: Client,
     opencode: Arc<OpenCodeManager>,
     server_port: u16,
+    settings: Arc<SettingsStore>,
+    config_refresher: ConfigRefresher,
+    event_hub: EventHub,
     directory_change_lock: Arc<Mutex<()>>,
     models_metadata_cache: Arc<Mutex<ModelsMetadataCache>>,
 }
 
+/// Access control applied in front of every route on the embedded server
+struct ServerAccess {
+    auth_token: Arc<str>,
+    cors: tower_http::cors::CorsLayer,
+}
+
 #[derive(Default)]
 struct ModelsMetadataCache {
     payload: Option<Value>,
@@ -265,6 +420,7 @@ struct HealthResponse {
 #[derive(Serialize)]
 struct ServerInfoPayload {
     server_port: u16,
+    auth_token: String,
     opencode_port: Option<u16>,
     api_prefix: String,
     cli_available: bool,
@@ -284,6 +440,7 @@ async fn desktop_server_info(
         .is_some();
     Ok(ServerInfoPayload {
         server_port: state.server_port,
+        auth_token: state.auth_token.to_string(),
         opencode_port: state.opencode.current_port(),
         api_prefix: state.opencode.api_prefix(),
         cli_available: state.opencode.is_cli_available(),
@@ -294,8 +451,7 @@ async fn desktop_server_info(
 #[tauri::command]
 async fn desktop_restart_opencode(state: tauri::State<'_, DesktopRuntime>) -> Result<(), String> {
     state
-        .opencode
-        .restart()
+        .restart_opencode()
         .await
         .map_err(|err| err.to_string())
 }
@@ -634,6 +790,12 @@ fn build_macos_menu<R: tauri::Runtime>(
 }
 
 fn main() {
+    // The same binary doubles as the terminal session daemon
+    #[cfg(unix)]
+    if let Some(code) = terminal_daemon::run_from_args() {
+        std::process::exit(code);
+    }
+
     let mut log_builder = tauri_plugin_log::Builder::default()
         .level(log::LevelFilter::Info)
         .clear_targets()
@@ -713,7 +875,7 @@ fn main() {
                 let _ = window.set_focus();
             }
 
-            let runtime = DesktopRuntime::initialize_sync()?;
+            let runtime = DesktopRuntime::initialize_sync(app.app_handle())?;
             app.manage(runtime.clone());
 
             let app_handle = app.app_handle().clone();
@@ -731,6 +893,10 @@ fn main() {
                     info!("[desktop] No saved directory - waiting for user to select one");
                 }
 
+                if let Err(e) = runtime_clone.apply_remote_access().await {
+                    warn!("[desktop:remote] Failed to start remote access: {}", e);
+                }
+
                 if let Err(e) =
                     restore_bookmarks_on_startup(app_handle.state::<DesktopRuntime>().clone()).await
                 {
@@ -839,6 +1005,11 @@ fn main() {
                 });
             }
 
+            if let Err(e) = tray::init(app.app_handle()) {
+                warn!("[desktop:tray] Failed to create tray icon: {}", e);
+            }
+            notification_history::sync_badge(app.app_handle(), runtime.notification_history());
+
             spawn_assistant_notifications(app.app_handle().clone(), runtime.clone());
             spawn_session_activity_tracker(app.app_handle().clone(), runtime.clone());
 
@@ -897,6 +1068,39 @@ fn main() {
             force_kill_terminal,
             fetch_desktop_logs,
             desktop_notify,
+            get_opencode_installations,
+            install_opencode_version,
+            select_opencode_version,
+            rollback_opencode_version,
+            get_remote_access_status,
+            set_remote_access,
+            start_remote_pairing,
+            list_paired_devices,
+            revoke_paired_device,
+            get_session_activity,
+            list_permission_requests,
+            reply_permission_request,
+            list_notification_history,
+            mark_notifications_read,
+            clear_notification_history,
+            list_webhook_deliveries,
+            test_webhook,
+            list_terminal_sessions,
+            attach_terminal_session,
+            list_orphaned_terminal_sessions,
+            kill_orphaned_terminal_sessions,
+            run_terminal_command,
+            get_terminal_command_result,
+            list_terminal_profiles,
+            attach_terminal_channel,
+            start_terminal_recording,
+            stop_terminal_recording,
+            list_terminal_recordings,
+            export_terminal_recording_text,
+            get_terminal_cwd,
+            list_terminal_processes,
+            signal_terminal_process,
+            set_terminal_agent_access,
         ])
         .on_menu_event(|app, event| {
             #[cfg(target_os = "macos")]
@@ -1057,17 +1261,10 @@ fn main() {
                 }
                 tauri::WindowEvent::CloseRequested { api, .. } => {
                     api.prevent_close();
-                    let runtime = window.state::<DesktopRuntime>().inner().clone();
-                    let window_handle = window.clone();
-                    let manager_clone = window_state_manager.clone();
-                    tauri::async_runtime::spawn(async move {
-                        if let Err(err) = persist_window_state(&window_handle, &manager_clone).await
-                        {
-                            warn!("Failed to persist window state: {}", err);
-                        }
-                        runtime.shutdown().await;
-                        let _ = window_handle.app_handle().exit(0);
-                    });
+                    tauri::async_runtime::spawn(quit_app(
+                        window.app_handle().clone(),
+                        Some(window.clone()),
+                    ));
                 }
                 _ => {}
             }
@@ -1075,33 +1272,112 @@ fn main() {
         .build(tauri::generate_context!())
         .expect("failed to build Tauri application");
 
-    app.run(|_app_handle, _event| {});
+    app.run(|app_handle, event| {
+        // Cmd+Q and the app menu's Quit arrive without a code; our own exit(0) carries one
+        if let tauri::RunEvent::ExitRequested {
+            code: None, api, ..
+        } = event
+        {
+            api.prevent_exit();
+            let window = app_handle
+                .get_webview_window("main")
+                .map(|window| window.as_ref().window());
+            tauri::async_runtime::spawn(quit_app(app_handle.clone(), window));
+        }
+    });
+}
+
+/// Confirm running terminal jobs, save the window state and shut down before exiting
+async fn quit_app(app: tauri::AppHandle, window: Option<tauri::Window>) {
+    if QUIT_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    // Walking the process table of every shell is blocking work
+    let terminals = app.state::<TerminalState>().inner().clone();
+    let jobs = tauri::async_runtime::spawn_blocking(move || terminals.jobs_ended_by_exit())
+        .await
+        .unwrap_or_default();
+    if !jobs.is_empty() && !confirm_quit_with_jobs(&app, &jobs).await {
+        QUIT_IN_PROGRESS.store(false, Ordering::SeqCst);
+        return;
+    }
+
+    if let Some(window) = window {
+        let manager = app.state::<WindowStateManager>().inner().clone();
+        if let Err(err) = persist_window_state(&window, &manager).await {
+            warn!("Failed to persist window state: {}", err);
+        }
+    }
+    let runtime = app.state::<DesktopRuntime>().inner().clone();
+    runtime.shutdown().await;
+    app.exit(0);
 }
 
-fn spawn_http_server(port: u16, state: ServerState, shutdown_rx: broadcast::Receiver<()>) {
+/// Quitting ends the shells the app owns, so ask first when they are running jobs
+async fn confirm_quit_with_jobs(app: &tauri::AppHandle, jobs: &[String]) -> bool {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .message(format!(
+            "Terminals are still running {}.\n\nQuitting will stop them.",
+            jobs.join(", ")
+        ))
+        .title("Quit OpenChamber?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Quit".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+fn spawn_http_server(
+    port: u16,
+    state: ServerState,
+    access: ServerAccess,
+    bridge: Router,
+    shutdown_rx: broadcast::Receiver<()>,
+) {
     tauri::async_runtime::spawn(async move {
-        if let Err(error) = run_http_server(port, state, shutdown_rx).await {
+        if let Err(error) = run_http_server(port, state, access, bridge, shutdown_rx).await {
             error!("[desktop:http] server stopped: {error:?}");
         }
     });
 }
 
-async fn run_http_server(
-    port: u16,
-    state: ServerState,
-    mut shutdown_rx: broadcast::Receiver<()>,
-) -> Result<()> {
-    let router = Router::new()
+/// Routes shared by the loopback server and the LAN listener; each adds its own auth layer
+fn api_router(state: ServerState) -> Router {
+    Router::new()
         .route("/health", get(health_handler))
         .route(
             "/api/openchamber/models-metadata",
             get(models_metadata_handler),
         )
+        .route(event_hub::HUB_EVENTS_ROUTE, get(hub_events_handler))
         .route("/api/opencode/directory", post(change_directory_handler))
         .route("/api", any(proxy_to_opencode))
         .route("/api/{*rest}", any(proxy_to_opencode))
         .with_state(state)
-        .layer(CorsLayer::permissive());
+}
+
+async fn run_http_server(
+    port: u16,
+    state: ServerState,
+    access: ServerAccess,
+    bridge: Router,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) -> Result<()> {
+    let router = api_router(state)
+        .layer(middleware::from_fn_with_state(
+            access.auth_token,
+            server_auth::require_token,
+        ))
+        // Loopback only and checked against its own token; never served to paired devices
+        .merge(bridge)
+        .layer(access.cors);
 
     let addr = format!("127.0.0.1:{port}");
     let listener = TcpListener::bind(&addr).await?;
@@ -1116,6 +1392,18 @@ async fn run_http_server(
     Ok(())
 }
 
+#[derive(Deserialize)]
+struct EventsQuery {
+    directory: Option<String>,
+}
+
+async fn hub_events_handler(
+    State(state): State<ServerState>,
+    Query(query): Query<EventsQuery>,
+) -> Response<Body> {
+    state.event_hub.sse_response(query.directory)
+}
+
 async fn health_handler(State(state): State<ServerState>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok",
@@ -1219,17 +1507,32 @@ async fn parse_request_payload(
         .map_err(|_| config_error_response(StatusCode::BAD_REQUEST, "Malformed JSON payload"))
 }
 
+/// `Err` carries the response to send instead of the caller's own, including a postponed refresh
 async fn refresh_opencode_after_config_change(
     state: &ServerState,
     reason: &str,
 ) -> Result<(), Response<Body>> {
-    info!("[desktop:config] Restarting OpenCode after {}", reason);
-    state.opencode.restart().await.map_err(|err| {
-        config_error_response(
+    let outcome = state.config_refresher.request(reason).await;
+    if outcome.is_deferred() {
+        return Err(json_response(
+            StatusCode::ACCEPTED,
+            ConfigActionResponse {
+                success: true,
+                requires_reload: false,
+                message: "Saved. OpenCode will reload once the sessions running in this project finish"
+                    .to_string(),
+                reload_delay_ms: 0,
+            },
+        ));
+    }
+    if !outcome.success {
+        return Err(config_error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to restart OpenCode: {}", err),
-        )
-    })?;
+            outcome
+                .error
+                .unwrap_or_else(|| "Failed to refresh OpenCode".to_string()),
+        ));
+    }
     Ok(())
 }
 
@@ -2199,6 +2502,8 @@ async fn change_directory_handler(
 
     info!("[desktop:http] Changing directory to {:?}", resolved_path);
 
+    apply_launch_profile(&state.opencode, &state.settings, &resolved_path).await;
+
     // Update working directory and restart OpenCode
     state
         .opencode
@@ -2248,6 +2553,14 @@ async fn proxy_to_opencode(
         return handle_config_routes(state, &origin_path, method, req).await;
     }
 
+    // Clients still asking for the upstream stream get the shared hub too, never a new upstream connection
+    if method == Method::GET && origin_path == "/api/event" {
+        let directory = Query::<EventsQuery>::try_from_uri(&original.0)
+            .ok()
+            .and_then(|Query(query)| query.directory);
+        return Ok(state.event_hub.sse_response(directory));
+    }
+
     let port = state.opencode.current_port().ok_or_else(|| {
         error!("[desktop:http] PROXY FAILED: OpenCode not running (no port)");
         StatusCode::SERVICE_UNAVAILABLE
@@ -2261,51 +2574,47 @@ async fn proxy_to_opencode(
         target.push_str(q);
     }
 
-    let (parts, body) = req.into_parts();
+    let (mut parts, body) = req.into_parts();
+
+    if is_websocket_upgrade(&parts.headers) {
+        let ws = WebSocketUpgrade::from_request_parts(&mut parts, &state)
+            .await
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+        let ws_target = target.replacen("http://", "ws://", 1);
+        return proxy_websocket(ws, &parts.headers, ws_target).await;
+    }
+
     let method = parts.method.clone();
     let mut builder = state.client.request(method, &target);
 
     let mut headers = parts.headers;
+    strip_hop_by_hop_headers(&mut headers);
     headers.insert(header::HOST, format!("127.0.0.1:{port}").parse().unwrap());
-    if headers
-        .get(header::ACCEPT)
-        .and_then(|v| v.to_str().ok())
-        .map(|val| val.contains("text/event-stream"))
-        .unwrap_or(false)
-    {
-        headers.insert(header::CONNECTION, "keep-alive".parse().unwrap());
-    }
+    builder = builder.headers(headers);
 
-    for (key, value) in headers.iter() {
-        if key == &header::CONTENT_LENGTH {
-            continue;
-        }
-        builder = builder.header(key, value);
+    // Stream the request body straight through; if the client goes away mid-upload the body
+    // stream errors and reqwest aborts the upstream request.
+    if !body.is_end_stream() {
+        builder = builder.body(ReqwestBody::wrap_stream(body.into_data_stream()));
     }
 
-    let body_bytes = to_bytes(body, PROXY_BODY_LIMIT)
-        .await
-        .map_err(|_| StatusCode::BAD_GATEWAY)?;
-
-    let response = if body_bytes.is_empty() {
-        builder.send().await.map_err(|_| StatusCode::BAD_GATEWAY)?
-    } else {
-        builder
-            .body(ReqwestBody::from(body_bytes))
-            .send()
-            .await
-            .map_err(|_| StatusCode::BAD_GATEWAY)?
-    };
+    // Dropping this future (client disconnected before headers) drops the upstream request too.
+    let response = builder.send().await.map_err(|err| {
+        warn!("[desktop:http] PROXY FAILED: {target}: {err}");
+        StatusCode::BAD_GATEWAY
+    })?;
 
     let status = response.status();
+    let mut response_headers = response.headers().clone();
+    strip_hop_by_hop_headers(&mut response_headers);
+
     let mut resp_builder = Response::builder().status(status);
-    for (key, value) in response.headers() {
-        if key.as_str().eq_ignore_ascii_case("connection") {
-            continue;
-        }
-        resp_builder = resp_builder.header(key, value);
+    if let Some(resp_headers) = resp_builder.headers_mut() {
+        resp_headers.extend(response_headers);
     }
 
+    // The upstream response is owned by the body stream, so a client disconnect (body dropped)
+    // closes the upstream connection instead of returning it to the pool half-read.
     let stream = response.bytes_stream().map(|chunk| {
         chunk
             .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
@@ -2315,10 +2624,150 @@ async fn proxy_to_opencode(
     resp_builder.body(body).map_err(|_| StatusCode::BAD_GATEWAY)
 }
 
+/// Hop-by-hop headers (RFC 9110 §7.6.1) apply to a single connection and must not be forwarded
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+fn strip_hop_by_hop_headers(headers: &mut HeaderMap) {
+    let listed: Vec<HeaderName> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+    for name in listed {
+        headers.remove(name);
+    }
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove(*name);
+    }
+}
+
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    let upgrade = headers
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+    let connection_upgrade = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+    upgrade && connection_upgrade
+}
+
+/// Connect to OpenCode first so a refused upstream handshake surfaces as 502, then pump frames both ways
+async fn proxy_websocket(
+    ws: WebSocketUpgrade,
+    client_headers: &HeaderMap,
+    target: String,
+) -> Result<Response<Body>, StatusCode> {
+    let mut upstream_request = target.as_str().into_client_request().map_err(|err| {
+        warn!("[desktop:http] Invalid WebSocket target {target}: {err}");
+        StatusCode::BAD_GATEWAY
+    })?;
+    for name in [header::SEC_WEBSOCKET_PROTOCOL, header::COOKIE, header::AUTHORIZATION] {
+        if let Some(value) = client_headers.get(&name) {
+            upstream_request.headers_mut().insert(name, value.clone());
+        }
+    }
+
+    let (upstream, upstream_response) = connect_async(upstream_request).await.map_err(|err| {
+        warn!("[desktop:http] WebSocket upstream connect failed for {target}: {err}");
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    let ws = match upstream_response
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(protocol) => ws.protocols([protocol.to_string()]),
+        None => ws,
+    };
+
+    Ok(ws.on_upgrade(move |client| async move {
+        let (mut client_tx, mut client_rx) = client.split();
+        let (mut upstream_tx, mut upstream_rx) = upstream.split();
+
+        let client_to_upstream = async {
+            while let Some(Ok(message)) = client_rx.next().await {
+                let is_close = matches!(message, WsMessage::Close(_));
+                if upstream_tx.send(ws_message_to_upstream(message)).await.is_err() || is_close {
+                    break;
+                }
+            }
+            let _ = upstream_tx.close().await;
+        };
+
+        let upstream_to_client = async {
+            while let Some(Ok(message)) = upstream_rx.next().await {
+                let Some(message) = ws_message_from_upstream(message) else {
+                    continue;
+                };
+                let is_close = matches!(message, WsMessage::Close(_));
+                if client_tx.send(message).await.is_err() || is_close {
+                    break;
+                }
+            }
+            let _ = client_tx.close().await;
+        };
+
+        // Whichever side closes first tears down the other
+        tokio::select! {
+            _ = client_to_upstream => {}
+            _ = upstream_to_client => {}
+        }
+    }))
+}
+
+fn ws_message_to_upstream(message: WsMessage) -> TungsteniteMessage {
+    match message {
+        WsMessage::Text(text) => TungsteniteMessage::Text(text.as_str().into()),
+        WsMessage::Binary(data) => TungsteniteMessage::Binary(data),
+        WsMessage::Ping(data) => TungsteniteMessage::Ping(data),
+        WsMessage::Pong(data) => TungsteniteMessage::Pong(data),
+        WsMessage::Close(frame) => {
+            TungsteniteMessage::Close(frame.map(|frame| TungsteniteCloseFrame {
+                code: frame.code.into(),
+                reason: frame.reason.as_str().into(),
+            }))
+        }
+    }
+}
+
+fn ws_message_from_upstream(message: TungsteniteMessage) -> Option<WsMessage> {
+    match message {
+        TungsteniteMessage::Text(text) => Some(WsMessage::Text(text.as_str().into())),
+        TungsteniteMessage::Binary(data) => Some(WsMessage::Binary(data)),
+        TungsteniteMessage::Ping(data) => Some(WsMessage::Ping(data)),
+        TungsteniteMessage::Pong(data) => Some(WsMessage::Pong(data)),
+        TungsteniteMessage::Close(frame) => Some(WsMessage::Close(frame.map(|frame| WsCloseFrame {
+            code: frame.code.into(),
+            reason: frame.reason.as_str().into(),
+        }))),
+        TungsteniteMessage::Frame(_) => None,
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct SettingsStore {
     path: PathBuf,
     guard: Arc<Mutex<()>>,
+    /// Bumped on every save so readers can cache parsed settings
+    revision: Arc<AtomicU64>,
 }
 
 impl SettingsStore {
@@ -2333,9 +2782,14 @@ impl SettingsStore {
         Ok(Self {
             path: dir,
             guard: Arc::new(Mutex::new(())),
+            revision: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision.load(Ordering::SeqCst)
+    }
+
     pub(crate) async fn load(&self) -> Result<Value> {
         let _lock = self.guard.lock().await;
         match fs::read(&self.path).await {
@@ -2358,17 +2812,150 @@ impl SettingsStore {
         }
         let bytes = serde_json::to_vec_pretty(&payload)?;
         fs::write(&self.path, bytes).await?;
+        self.revision.fetch_add(1, Ordering::SeqCst);
         Ok(())
     }
 
     pub(crate) async fn last_directory(&self) -> Result<Option<PathBuf>> {
         let settings = self.load().await?;
-        let candidate = settings
+        Ok(Self::last_directory_in(&settings))
+    }
+
+    /// `lastDirectory` from settings that were already loaded
+    pub(crate) fn last_directory_in(settings: &Value) -> Option<PathBuf> {
+        settings
             .get("lastDirectory")
             .and_then(|value| value.as_str())
             .map(str::trim)
             .filter(|value| !value.is_empty())
-            .map(expand_tilde_path);
-        Ok(candidate)
+            .map(expand_tilde_path)
+    }
+
+    /// Last opened directory followed by pinned ones, without duplicates
+    pub(crate) fn recent_projects_in(settings: &Value) -> Vec<PathBuf> {
+        let pinned = settings
+            .get("pinnedDirectories")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str());
+        let mut projects: Vec<PathBuf> = Vec::new();
+        for candidate in settings
+            .get("lastDirectory")
+            .and_then(|value| value.as_str())
+            .into_iter()
+            .chain(pinned)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(expand_tilde_path)
+        {
+            if !projects.contains(&candidate) {
+                projects.push(candidate);
+            }
+        }
+        projects
+    }
+
+    /// OpenCode launch profile configured for a project directory, if any
+    pub(crate) async fn launch_profile(&self, directory: &Path) -> Result<Option<LaunchProfile>> {
+        let settings = self.load().await?;
+        let Some(profiles) = settings
+            .get("opencodeLaunchProfiles")
+            .and_then(|value| value.as_object())
+        else {
+            return Ok(None);
+        };
+
+        let profile = profiles
+            .iter()
+            .find(|(key, _)| {
+                let candidate = expand_tilde_path(key);
+                candidate == directory
+                    || std::fs::canonicalize(&candidate)
+                        .map(|resolved| resolved == directory)
+                        .unwrap_or(false)
+            })
+            .and_then(|(_, value)| serde_json::from_value::<LaunchProfile>(value.clone()).ok());
+        Ok(profile)
+    }
+
+    /// Whether new terminals run under the session daemon and outlive the app
+    pub(crate) async fn persistent_terminals(&self) -> bool {
+        self.load()
+            .await
+            .ok()
+            .and_then(|settings| settings.get("persistentTerminals").and_then(Value::as_bool))
+            .unwrap_or(false)
+    }
+
+    /// Shell profiles configured under `terminalProfiles`, in settings order
+    pub(crate) async fn terminal_profiles(&self) -> Result<Vec<ShellProfile>> {
+        let settings = self.load().await?;
+        Ok(settings
+            .get("terminalProfiles")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| serde_json::from_value::<ShellProfile>(value.clone()).ok())
+            .collect())
+    }
+
+    /// Name of the profile new terminals use when none is requested
+    pub(crate) async fn default_terminal_profile(&self) -> Option<String> {
+        self.load()
+            .await
+            .ok()
+            .and_then(|settings| {
+                settings
+                    .get("defaultTerminalProfile")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .filter(|name| !name.is_empty())
+    }
+
+    /// LAN access settings; missing or malformed values fall back to disabled
+    pub(crate) async fn remote_access(&self) -> Result<RemoteAccessConfig> {
+        let settings = self.load().await?;
+        Ok(settings
+            .get("remoteAccess")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default())
+    }
+
+    /// Notification rules; a missing or malformed block keeps the default "agent is ready" rule
+    pub(crate) async fn notification_settings(&self) -> Result<NotificationSettings> {
+        let settings = self.load().await?;
+        Ok(settings
+            .get("notifications")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default())
+    }
+
+    /// Outgoing webhooks; entries that fail to parse are skipped
+    pub(crate) async fn webhooks(&self) -> Result<Vec<WebhookConfig>> {
+        let settings = self.load().await?;
+        Ok(settings
+            .get("webhooks")
+            .and_then(|value| value.as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+/// Load the launch profile for `directory` from settings and hand it to the manager
+async fn apply_launch_profile(
+    opencode: &OpenCodeManager,
+    settings: &SettingsStore,
+    directory: &Path,
+) {
+    match settings.launch_profile(directory).await {
+        Ok(profile) => opencode.set_launch_profile(profile),
+        Err(err) => warn!("[desktop] Failed to load launch profile: {}", err),
     }
 }
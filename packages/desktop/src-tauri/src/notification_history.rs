@@ -0,0 +1,181 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::{fs, sync::Mutex};
+use uuid::Uuid;
+
+const HISTORY_FILE: &str = "notification-history.json";
+/// Oldest entries are dropped beyond this
+const MAX_HISTORY_ENTRIES: usize = 200;
+const HISTORY_CHANGED_EVENT: &str = "openchamber:notification-history";
+
+/// One notification shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub directory: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub read: bool,
+}
+
+/// Fields of a notification about to be recorded
+#[derive(Debug, Clone, Default)]
+pub struct NewNotification {
+    pub title: String,
+    pub body: String,
+    pub session_id: Option<String>,
+    pub directory: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    entries: Vec<NotificationRecord>,
+}
+
+/// Bounded on-disk log of shown notifications, newest last
+pub struct NotificationHistory {
+    path: PathBuf,
+    entries: RwLock<VecDeque<NotificationRecord>>,
+    write_lock: Mutex<()>,
+}
+
+impl NotificationHistory {
+    pub fn load() -> Result<Self> {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+        let path = home.join(".config").join("openchamber").join(HISTORY_FILE);
+        let mut entries: VecDeque<NotificationRecord> = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<HistoryFile>(&bytes).ok())
+            .unwrap_or_default()
+            .entries
+            .into();
+        while entries.len() > MAX_HISTORY_ENTRIES {
+            entries.pop_front();
+        }
+        Ok(Self {
+            path,
+            entries: RwLock::new(entries),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Newest first
+    pub fn list(&self) -> Vec<NotificationRecord> {
+        self.entries.read().iter().rev().cloned().collect()
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.entries
+            .read()
+            .iter()
+            .filter(|entry| !entry.read)
+            .count()
+    }
+
+    pub async fn record(&self, notification: NewNotification) -> Result<NotificationRecord> {
+        let record = NotificationRecord {
+            id: Uuid::new_v4().to_string(),
+            title: notification.title,
+            body: notification.body,
+            session_id: notification.session_id,
+            directory: notification.directory,
+            created_at: Utc::now(),
+            read: false,
+        };
+        {
+            let mut entries = self.entries.write();
+            entries.push_back(record.clone());
+            while entries.len() > MAX_HISTORY_ENTRIES {
+                entries.pop_front();
+            }
+        }
+        self.persist().await?;
+        Ok(record)
+    }
+
+    /// Mark the given entries read, or all of them when `ids` is `None`; returns how many changed
+    pub async fn mark_read(&self, ids: Option<&[String]>) -> Result<usize> {
+        let changed = {
+            let wanted: Option<HashSet<&str>> =
+                ids.map(|ids| ids.iter().map(String::as_str).collect());
+            let mut entries = self.entries.write();
+            let mut changed = 0;
+            for entry in entries.iter_mut().filter(|entry| !entry.read) {
+                if wanted
+                    .as_ref()
+                    .map_or(true, |wanted| wanted.contains(entry.id.as_str()))
+                {
+                    entry.read = true;
+                    changed += 1;
+                }
+            }
+            changed
+        };
+        if changed > 0 {
+            self.persist().await?;
+        }
+        Ok(changed)
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        self.entries.write().clear();
+        self.persist().await
+    }
+
+    async fn persist(&self) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let file = HistoryFile {
+            entries: self.entries.read().iter().cloned().collect(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", Uuid::new_v4()));
+        fs::write(&tmp, serde_json::to_vec_pretty(&file)?).await?;
+        fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Push the unread count to the dock/taskbar badge and the webview
+pub fn sync_badge(app: &AppHandle, history: &NotificationHistory) {
+    let unread = history.unread_count();
+    if let Some(window) = app.get_webview_window("main") {
+        // Unsupported on Windows; the count still reaches the UI below
+        let _ = window.set_badge_count((unread > 0).then_some(unread as i64));
+    }
+    let _ = app.emit(
+        HISTORY_CHANGED_EVENT,
+        serde_json::json!({ "unreadCount": unread }),
+    );
+}
+
+/// Record a shown notification and refresh the badge; failures are only logged
+pub async fn record_and_sync(
+    app: &AppHandle,
+    history: &NotificationHistory,
+    notification: NewNotification,
+) {
+    if let Err(err) = history.record(notification).await {
+        warn!("[desktop:notify] Failed to save notification history: {err}");
+    }
+    sync_badge(app, history);
+}
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use crate::path_utils::expand_tilde_path;
+
+pub const DEFAULT_SOUND: &str = "Glass";
+pub const DEFAULT_LONG_RUNNING_MINUTES: u64 = 10;
+
+/// Event kinds a rule can react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleTrigger {
+    /// Assistant message finished with `stop`
+    AssistantReady,
+    PermissionRequest,
+    ToolError,
+    SessionError,
+    /// Session has been busy for longer than the rule's `minutes`
+    LongRunning,
+}
+
+/// `notifications.rules[]` entry in settings.json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub trigger: RuleTrigger,
+    /// Threshold for `longRunning`
+    #[serde(default)]
+    pub minutes: Option<u64>,
+    /// Only match these agents (OpenCode mode names); empty matches all
+    #[serde(default)]
+    pub agents: Vec<String>,
+    /// Only match these model ids; empty matches all
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Sound name passed to the OS; empty string plays none
+    #[serde(default = "default_sound")]
+    pub sound: String,
+}
+
+impl NotificationRule {
+    pub fn long_running_minutes(&self) -> u64 {
+        self.minutes
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_LONG_RUNNING_MINUTES)
+    }
+
+    pub fn matches(&self, candidate: &NotificationCandidate) -> bool {
+        if !self.enabled || self.trigger != candidate.trigger {
+            return false;
+        }
+        if let Some(busy_minutes) = candidate.busy_minutes {
+            if busy_minutes < self.long_running_minutes() {
+                return false;
+            }
+        }
+        matches_filter(&self.agents, candidate.agent.as_deref())
+            && matches_filter(&self.models, candidate.model.as_deref())
+    }
+}
+
+/// `notifications.quietHours`; `start` after `end` spans midnight
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub start: String,
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+        }
+    }
+}
+
+impl QuietHours {
+    pub fn contains(&self, now: NaiveTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_clock(&self.start), parse_clock(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+/// `notifications` block in settings.json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Skip notifications while the main window is focused and visible
+    pub only_when_unfocused: bool,
+    pub quiet_hours: QuietHours,
+    /// Project directories (and everything below them) that never notify
+    pub muted_projects: Vec<String>,
+    pub rules: Vec<NotificationRule>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_unfocused: true,
+            quiet_hours: QuietHours::default(),
+            muted_projects: Vec::new(),
+            rules: vec![
+                NotificationRule {
+                    id: "assistant-ready".to_string(),
+                    enabled: true,
+                    trigger: RuleTrigger::AssistantReady,
+                    minutes: None,
+                    agents: Vec::new(),
+                    models: Vec::new(),
+                    sound: DEFAULT_SOUND.to_string(),
+                },
+                // A blocked agent waits until someone answers, so this is on out of the box
+                NotificationRule {
+                    id: "permission-request".to_string(),
+                    enabled: true,
+                    trigger: RuleTrigger::PermissionRequest,
+                    minutes: None,
+                    agents: Vec::new(),
+                    models: Vec::new(),
+                    sound: DEFAULT_SOUND.to_string(),
+                },
+            ],
+        }
+    }
+}
+
+/// Something that happened on the event stream and may deserve a notification
+#[derive(Debug, Clone)]
+pub struct NotificationCandidate {
+    pub trigger: RuleTrigger,
+    pub directory: String,
+    pub session_id: Option<String>,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    /// How long the session has been busy, for `longRunning`
+    pub busy_minutes: Option<u64>,
+    pub title: String,
+    pub body: String,
+}
+
+/// Outcome of a rule match: which rule fired and how to present it
+#[derive(Debug, Clone)]
+pub struct NotificationDecision {
+    pub rule_id: String,
+    pub sound: Option<String>,
+}
+
+impl NotificationSettings {
+    /// First enabled rule matching `candidate`, unless muted or inside quiet hours
+    pub fn evaluate(
+        &self,
+        candidate: &NotificationCandidate,
+        now: NaiveTime,
+    ) -> Option<NotificationDecision> {
+        let rule = self.rules.iter().find(|rule| rule.matches(candidate))?;
+        self.decide(rule, candidate, now)
+    }
+
+    /// Decision for a rule the caller already matched, unless muted or inside quiet hours
+    pub fn decide(
+        &self,
+        rule: &NotificationRule,
+        candidate: &NotificationCandidate,
+        now: NaiveTime,
+    ) -> Option<NotificationDecision> {
+        if !self.enabled || self.quiet_hours.contains(now) || self.is_muted(&candidate.directory) {
+            return None;
+        }
+        Some(NotificationDecision {
+            rule_id: rule.id.clone(),
+            sound: Some(rule.sound.trim())
+                .filter(|sound| !sound.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// Smallest `longRunning` threshold among enabled rules, if any
+    pub fn long_running_threshold(&self) -> Option<u64> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled && rule.trigger == RuleTrigger::LongRunning)
+            .map(NotificationRule::long_running_minutes)
+            .min()
+    }
+
+    fn is_muted(&self, directory: &str) -> bool {
+        if directory.is_empty() {
+            return false;
+        }
+        let directory = Path::new(directory);
+        self.muted_projects
+            .iter()
+            .any(|muted| directory.starts_with(expand_tilde_path(muted)))
+    }
+}
+
+/// Parse `HH:MM` in 24-hour time
+pub fn parse_clock(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+fn matches_filter(filter: &[String], value: Option<&str>) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    let Some(value) = value else {
+        return false;
+    };
+    filter
+        .iter()
+        .any(|wanted| wanted.trim().eq_ignore_ascii_case(value))
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_sound() -> String {
+    DEFAULT_SOUND.to_string()
+}
@@ -0,0 +1,680 @@
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// OpenCode release installed when no explicit version is requested
+pub const PINNED_OPENCODE_VERSION: &str = "1.1.11";
+
+const DEFAULT_RELEASE_MIRROR: &str = "https://github.com/sst/opencode/releases/download";
+/// GitHub publishes a SHA-256 `digest` for every release asset; it is read from here even
+/// when the archive itself comes from a mirror, so a mirror cannot vouch for its own files
+const RELEASE_METADATA_URL: &str = "https://api.github.com/repos/sst/opencode/releases/tags";
+const METADATA_TIMEOUT: Duration = Duration::from_secs(30);
+const INSTALL_STATE_FILE: &str = "installs.json";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const EXTRACT_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(15);
+
+static VERSION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[0-9A-Za-z][0-9A-Za-z.+-]*$").expect("valid version regex"));
+
+#[cfg(windows)]
+const BINARY_NAME: &str = "opencode.exe";
+#[cfg(not(windows))]
+const BINARY_NAME: &str = "opencode";
+
+/// Persisted selection of managed OpenCode versions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallState {
+    #[serde(default)]
+    current: Option<String>,
+    #[serde(default)]
+    previous: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledVersion {
+    pub version: String,
+    pub path: String,
+    pub is_current: bool,
+    pub is_previous: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallerStatus {
+    pub pinned_version: String,
+    pub mirror: String,
+    pub current: Option<String>,
+    pub previous: Option<String>,
+    pub versions: Vec<InstalledVersion>,
+}
+
+#[derive(Debug, Default)]
+pub struct InstallRequest {
+    pub version: Option<String>,
+    pub mirror: Option<String>,
+    pub sha256: Option<String>,
+    pub activate: bool,
+}
+
+/// Managed OpenCode versions under one root directory
+pub struct Installer {
+    root: PathBuf,
+}
+
+impl Installer {
+    /// Installs under ~/.config/openchamber/opencode
+    pub fn new() -> Result<Self> {
+        let mut root = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+        root.push(".config");
+        root.push("openchamber");
+        root.push("opencode");
+        Ok(Self::at(root))
+    }
+
+    pub fn at(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn versions_dir(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    fn state_file_path(&self) -> PathBuf {
+        self.root.join(INSTALL_STATE_FILE)
+    }
+
+    fn version_binary_path(&self, version: &str) -> PathBuf {
+        self.versions_dir().join(version).join(BINARY_NAME)
+    }
+
+    fn load_state_sync(&self) -> InstallState {
+        std::fs::read(self.state_file_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    async fn load_state(&self) -> Result<InstallState> {
+        match fs::read(self.state_file_path()).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).unwrap_or_default()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(InstallState::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write state through a temp file + rename so a crash never leaves a half-written selection
+    async fn save_state(&self, state: &InstallState) -> Result<()> {
+        let path = self.state_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let tmp = path.with_extension(format!("json.{}.tmp", Uuid::new_v4()));
+        fs::write(&tmp, serde_json::to_vec_pretty(state)?).await?;
+        fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    /// Binary of the currently selected managed version, if any (sync for binary resolution)
+    pub fn managed_binary_path(&self) -> Option<PathBuf> {
+        let version = self.load_state_sync().current?;
+        let path = self.version_binary_path(&version);
+        if path.exists() {
+            Some(path)
+        } else {
+            warn!(
+                "[desktop:installer] selected OpenCode {} is missing at {:?}",
+                version, path
+            );
+            None
+        }
+    }
+
+    pub async fn status(&self) -> Result<InstallerStatus> {
+        let state = self.load_state().await?;
+        let mut versions = Vec::new();
+
+        if let Ok(mut entries) = fs::read_dir(self.versions_dir()).await {
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') || !VERSION_RE.is_match(&name) {
+                    continue;
+                }
+                let binary = entry.path().join(BINARY_NAME);
+                if !binary.exists() {
+                    continue;
+                }
+                versions.push(InstalledVersion {
+                    is_current: state.current.as_deref() == Some(name.as_str()),
+                    is_previous: state.previous.as_deref() == Some(name.as_str()),
+                    path: binary.to_string_lossy().to_string(),
+                    version: name,
+                });
+            }
+        }
+        versions.sort_by(|a, b| a.version.cmp(&b.version));
+
+        Ok(InstallerStatus {
+            pinned_version: PINNED_OPENCODE_VERSION.to_string(),
+            mirror: resolve_mirror(None),
+            current: state.current,
+            previous: state.previous,
+            versions,
+        })
+    }
+
+    /// Download, verify and unpack an OpenCode release; optionally make it the current version
+    pub async fn install(&self, request: InstallRequest) -> Result<InstalledVersion> {
+        let version = normalize_version(
+            request
+                .version
+                .as_deref()
+                .unwrap_or(PINNED_OPENCODE_VERSION),
+        )?;
+        let mirror = resolve_mirror(request.mirror.as_deref());
+        let asset = release_asset_name()?;
+        let asset_url = format!("{mirror}/v{version}/{asset}");
+
+        info!(
+            "[desktop:installer] installing OpenCode {} from {}",
+            version, asset_url
+        );
+
+        let expected = match explicit_checksum(request.sha256.as_deref())? {
+            Some(digest) => digest,
+            None => published_checksum(&version, &asset).await?,
+        };
+        let archive = fetch_bytes(&asset_url).await?;
+        let actual = format!("{:x}", Sha256::digest(&archive));
+        if actual != expected {
+            return Err(anyhow!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                asset,
+                expected,
+                actual
+            ));
+        }
+
+        let versions = self.versions_dir();
+        fs::create_dir_all(&versions).await?;
+        let staging = versions.join(format!(".staging-{}", Uuid::new_v4()));
+        fs::create_dir_all(&staging).await?;
+
+        let result = stage_release(&staging, &asset, &archive, &version).await;
+        let staged_dir = match result {
+            Ok(dir) => dir,
+            Err(err) => {
+                safe_rm(&staging).await;
+                return Err(err);
+            }
+        };
+
+        // Swap the staged directory into place; an existing install is moved aside first
+        let target = versions.join(&version);
+        let trash = versions.join(format!(".trash-{}", Uuid::new_v4()));
+        if fs::metadata(&target).await.is_ok() {
+            fs::rename(&target, &trash).await?;
+        }
+        if let Err(err) = fs::rename(&staged_dir, &target).await {
+            if fs::metadata(&trash).await.is_ok() {
+                let _ = fs::rename(&trash, &target).await;
+            }
+            safe_rm(&staging).await;
+            return Err(err.into());
+        }
+        safe_rm(&trash).await;
+        safe_rm(&staging).await;
+
+        if request.activate {
+            self.select(&version).await?;
+        }
+
+        let state = self.load_state().await?;
+        info!("[desktop:installer] installed OpenCode {}", version);
+        Ok(InstalledVersion {
+            is_current: state.current.as_deref() == Some(version.as_str()),
+            is_previous: state.previous.as_deref() == Some(version.as_str()),
+            path: target.join(BINARY_NAME).to_string_lossy().to_string(),
+            version,
+        })
+    }
+
+    /// Make an installed version current, remembering the old one for rollback
+    pub async fn select(&self, version: &str) -> Result<()> {
+        let version = normalize_version(version)?;
+        let binary = self.version_binary_path(&version);
+        if fs::metadata(&binary).await.is_err() {
+            return Err(anyhow!("OpenCode {} is not installed", version));
+        }
+
+        let mut state = self.load_state().await?;
+        if state.current.as_deref() == Some(version.as_str()) {
+            return Ok(());
+        }
+        state.previous = state.current.take();
+        state.current = Some(version.clone());
+        self.save_state(&state).await?;
+        info!("[desktop:installer] selected OpenCode {}", version);
+        Ok(())
+    }
+
+    /// Swap back to the previously selected version
+    pub async fn rollback(&self) -> Result<String> {
+        let state = self.load_state().await?;
+        let previous = state
+            .previous
+            .clone()
+            .ok_or_else(|| anyhow!("No previous OpenCode version to roll back to"))?;
+        self.select(&previous).await?;
+        Ok(previous)
+    }
+}
+
+/// Binary of the currently selected managed version, if any (sync for binary resolution)
+pub fn managed_binary_path() -> Option<PathBuf> {
+    Installer::new().ok()?.managed_binary_path()
+}
+
+pub async fn status() -> Result<InstallerStatus> {
+    Installer::new()?.status().await
+}
+
+/// Download, verify and unpack an OpenCode release; optionally make it the current version
+pub async fn install(request: InstallRequest) -> Result<InstalledVersion> {
+    Installer::new()?.install(request).await
+}
+
+/// Make an installed version current, remembering the old one for rollback
+pub async fn select(version: &str) -> Result<()> {
+    Installer::new()?.select(version).await
+}
+
+/// Swap back to the previously selected version
+pub async fn rollback() -> Result<String> {
+    Installer::new()?.rollback().await
+}
+
+fn normalize_version(raw: &str) -> Result<String> {
+    let trimmed = raw.trim();
+    let version = trimmed.strip_prefix('v').unwrap_or(trimmed);
+    if !VERSION_RE.is_match(version) {
+        return Err(anyhow!("Invalid OpenCode version: {}", raw));
+    }
+    Ok(version.to_string())
+}
+
+/// Mirror used for downloads: explicit value, then OPENCHAMBER_OPENCODE_MIRROR, then GitHub releases
+fn resolve_mirror(explicit: Option<&str>) -> String {
+    explicit
+        .map(str::to_string)
+        .or_else(|| std::env::var("OPENCHAMBER_OPENCODE_MIRROR").ok())
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_RELEASE_MIRROR.to_string())
+}
+
+/// Release asset name for the current platform, matching the OpenCode release layout
+fn release_asset_name() -> Result<String> {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        "linux" => "linux",
+        "windows" => "windows",
+        other => return Err(anyhow!("Unsupported platform: {}", other)),
+    };
+    let arch = match std::env::consts::ARCH {
+        "aarch64" => "arm64",
+        "x86_64" => "x64",
+        other => return Err(anyhow!("Unsupported architecture: {}", other)),
+    };
+    let extension = if os == "linux" { "tar.gz" } else { "zip" };
+    Ok(format!("opencode-{os}-{arch}.{extension}"))
+}
+
+async fn stage_release(
+    staging: &Path,
+    asset: &str,
+    archive: &[u8],
+    version: &str,
+) -> Result<PathBuf> {
+    let archive_path = staging.join(asset);
+    fs::write(&archive_path, archive).await?;
+
+    let extract_dir = staging.join("extract");
+    fs::create_dir_all(&extract_dir).await?;
+    extract_archive(&archive_path, &extract_dir).await?;
+
+    let found = find_binary(&extract_dir)
+        .await?
+        .ok_or_else(|| anyhow!("Release archive {} does not contain {}", asset, BINARY_NAME))?;
+
+    let staged_dir = staging.join(version);
+    fs::create_dir_all(&staged_dir).await?;
+    let staged_binary = staged_dir.join(BINARY_NAME);
+    fs::rename(&found, &staged_binary).await?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged_binary, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+
+    verify_binary(&staged_binary).await?;
+    Ok(staged_dir)
+}
+
+async fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let name = archive
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+
+    let mut cmd = if name.ends_with(".zip") && !cfg!(windows) {
+        let mut cmd = Command::new("unzip");
+        cmd.arg("-q").arg("-o").arg(archive).arg("-d").arg(dest);
+        cmd
+    } else {
+        // bsdtar on Windows also handles zip archives
+        let mut cmd = Command::new("tar");
+        cmd.arg("-xf").arg(archive).arg("-C").arg(dest);
+        cmd
+    };
+
+    let output = tokio::time::timeout(EXTRACT_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| anyhow!("Timed out extracting {}", name))?
+        .with_context(|| format!("Failed to run extractor for {}", name))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to extract {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+async fn find_binary(root: &Path) -> Result<Option<PathBuf>> {
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_file() && entry.file_name() == BINARY_NAME {
+                return Ok(Some(entry.path()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Make sure the unpacked binary actually runs on this machine before it can be selected
+async fn verify_binary(binary: &Path) -> Result<()> {
+    let output = tokio::time::timeout(
+        VERIFY_TIMEOUT,
+        Command::new(binary).arg("--version").output(),
+    )
+    .await
+    .map_err(|_| anyhow!("Timed out verifying {:?}", binary))?
+    .with_context(|| format!("Failed to execute {:?}", binary))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Downloaded OpenCode binary failed to run: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Fetch a URL, supporting file:// mirrors for offline installs
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>> {
+    if url.starts_with("file://") {
+        let path = reqwest::Url::parse(url)?
+            .to_file_path()
+            .map_err(|_| anyhow!("Invalid file URL: {}", url))?;
+        return fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read {:?}", path));
+    }
+
+    let client = Client::builder().timeout(DOWNLOAD_TIMEOUT).build()?;
+    let response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Download of {} failed with status {}",
+            url,
+            response.status()
+        ));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// A SHA-256 the caller supplied, validated and lowercased
+fn explicit_checksum(value: Option<&str>) -> Result<Option<String>> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let digest = value.to_lowercase();
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("Malformed SHA-256 checksum: {}", value));
+    }
+    Ok(Some(digest))
+}
+
+/// SHA-256 GitHub recorded for the release asset when it was uploaded
+async fn published_checksum(version: &str, asset: &str) -> Result<String> {
+    let url = format!("{RELEASE_METADATA_URL}/v{version}");
+    let client = Client::builder().timeout(METADATA_TIMEOUT).build()?;
+    let response = client
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, "OpenChamber")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .with_context(|| {
+            format!("Failed to look up the checksum of OpenCode {version}; pass its SHA-256 to install offline")
+        })?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Looking up OpenCode {} failed with status {}",
+            version,
+            response.status()
+        ));
+    }
+    let release: Value = response.json().await?;
+    asset_digest(&release, asset).ok_or_else(|| {
+        anyhow!(
+            "OpenCode {} publishes no SHA-256 for {}; pass it to install",
+            version,
+            asset
+        )
+    })
+}
+
+/// The `sha256:` digest of `asset` in a GitHub release payload
+fn asset_digest(release: &Value, asset: &str) -> Option<String> {
+    release
+        .get("assets")?
+        .as_array()?
+        .iter()
+        .find(|entry| entry.get("name").and_then(Value::as_str) == Some(asset))?
+        .get("digest")?
+        .as_str()?
+        .strip_prefix("sha256:")
+        .filter(|digest| digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_lowercase)
+}
+
+async fn safe_rm(dir: &Path) {
+    let _ = fs::remove_dir_all(dir).await;
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Temp dir holding a release mirror next to the installer's own root
+    fn sandbox() -> (PathBuf, Installer) {
+        let root = std::env::temp_dir().join(format!("openchamber-installer-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let installer = Installer::at(root.join("install"));
+        (root, installer)
+    }
+
+    /// Publish a release whose `opencode` is a script printing its version; returns the archive hash
+    fn publish(mirror: &Path, version: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let asset = release_asset_name().unwrap();
+        let source = mirror.join(format!("src-{version}"));
+        std::fs::create_dir_all(source.join("bin")).unwrap();
+        let binary = source.join("bin").join(BINARY_NAME);
+        std::fs::write(&binary, format!("#!/bin/sh\necho {version}\n")).unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let release = mirror.join(format!("v{version}"));
+        std::fs::create_dir_all(&release).unwrap();
+        let archive = release.join(&asset);
+        let status = if asset.ends_with(".zip") {
+            std::process::Command::new("zip")
+                .arg("-qr")
+                .arg(&archive)
+                .arg("bin")
+                .current_dir(&source)
+                .status()
+        } else {
+            std::process::Command::new("tar")
+                .arg("-czf")
+                .arg(&archive)
+                .arg("-C")
+                .arg(&source)
+                .arg("bin")
+                .status()
+        };
+        assert!(status.unwrap().success());
+        format!("{:x}", Sha256::digest(std::fs::read(&archive).unwrap()))
+    }
+
+    fn request(mirror: &Path, version: &str, sha256: &str) -> InstallRequest {
+        InstallRequest {
+            version: Some(version.to_string()),
+            mirror: Some(format!("file://{}", mirror.display())),
+            sha256: Some(sha256.to_string()),
+            activate: true,
+        }
+    }
+
+    #[test]
+    fn explicit_checksum_is_validated() {
+        let digest = "AB".repeat(32);
+        assert_eq!(
+            explicit_checksum(Some(&digest)).unwrap(),
+            Some(digest.to_lowercase())
+        );
+        assert_eq!(explicit_checksum(Some("  ")).unwrap(), None);
+        assert_eq!(explicit_checksum(None).unwrap(), None);
+        assert!(explicit_checksum(Some("abc")).is_err());
+    }
+
+    #[test]
+    fn reads_the_published_digest_of_the_matching_asset() {
+        let digest = "cd".repeat(32);
+        let release = serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [
+                { "name": "opencode-darwin-arm64.zip", "digest": format!("sha256:{}", "ef".repeat(32)) },
+                { "name": "opencode-linux-x64.tar.gz", "digest": format!("sha256:{}", digest.to_uppercase()) },
+                { "name": "opencode-windows-x64.zip", "digest": null },
+            ],
+        });
+        assert_eq!(
+            asset_digest(&release, "opencode-linux-x64.tar.gz"),
+            Some(digest)
+        );
+        assert_eq!(asset_digest(&release, "opencode-windows-x64.zip"), None);
+        assert_eq!(asset_digest(&release, "opencode-linux-arm64.tar.gz"), None);
+        assert_eq!(
+            asset_digest(&serde_json::json!({}), "opencode-linux-x64.tar.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn ignores_digests_that_are_not_sha256() {
+        let release = serde_json::json!({
+            "assets": [{ "name": "opencode-linux-x64.tar.gz", "digest": "sha512:abcd" }],
+        });
+        assert_eq!(asset_digest(&release, "opencode-linux-x64.tar.gz"), None);
+    }
+
+    #[tokio::test]
+    async fn installs_selects_and_rolls_back_from_a_local_mirror() {
+        let (root, installer) = sandbox();
+        let mirror = root.join("mirror");
+        let first = publish(&mirror, "0.0.1");
+        let second = publish(&mirror, "0.0.2");
+
+        let err = installer
+            .install(request(&mirror, "0.0.1", &"0".repeat(64)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert!(installer.status().await.unwrap().versions.is_empty());
+
+        let installed = installer
+            .install(request(&mirror, "0.0.1", &first))
+            .await
+            .unwrap();
+        assert!(installed.is_current);
+        let output = std::process::Command::new(&installed.path)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "0.0.1");
+
+        installer
+            .install(request(&mirror, "v0.0.2", &second))
+            .await
+            .unwrap();
+        let current = installer.status().await.unwrap();
+        assert_eq!(current.current.as_deref(), Some("0.0.2"));
+        assert_eq!(current.previous.as_deref(), Some("0.0.1"));
+        assert_eq!(current.versions.len(), 2);
+        assert_eq!(
+            installer.managed_binary_path().unwrap(),
+            installer.version_binary_path("0.0.2")
+        );
+
+        assert_eq!(installer.rollback().await.unwrap(), "0.0.1");
+        assert_eq!(
+            installer.status().await.unwrap().current.as_deref(),
+            Some("0.0.1")
+        );
+        assert!(installer.select("9.9.9").await.is_err());
+
+        // Leftover staging directories would show up as bogus versions
+        let leftovers = std::fs::read_dir(installer.versions_dir())
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with('.'))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
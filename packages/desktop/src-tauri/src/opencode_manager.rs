@@ -4,6 +4,7 @@ use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use regex::Regex;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
@@ -20,6 +21,8 @@ use tokio::{
     time::timeout,
 };
 
+use crate::path_utils::expand_tilde_path;
+
 static URL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"https?://[^:\s]+:(?P<port>\d+)(?P<path>/[^\s"']*)?"#).expect("valid regex")
 });
@@ -28,18 +31,45 @@ const FIRST_SIGNAL_TIMEOUT_MS: u64 = 750;
 const READY_CHECK_TIMEOUT_MS: u64 = 20000;
 const READY_CHECK_INTERVAL_MS: u64 = 400;
 
+/// Per-project launch overrides, stored in settings under `opencodeLaunchProfiles`
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub config_path: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub binary: Option<String>,
+}
+
+/// Answer to a tool permission request, in OpenCode's wire format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionReply {
+    Once,
+    Always,
+    Reject,
+}
+
 #[derive(Clone)]
 pub struct OpenCodeManager {
-    binary: Option<String>,
-    args: Vec<String>,
+    binary: Arc<RwLock<Option<String>>>,
+    default_config: Option<String>,
     env: HashMap<String, String>,
     working_dir: Arc<RwLock<PathBuf>>,
-    desired_port: u16,
+    default_port: u16,
+    launch_profile: Arc<RwLock<Option<LaunchProfile>>>,
     child: Arc<Mutex<Option<Child>>>,
     port: Arc<RwLock<Option<u16>>>,
     api_prefix: Arc<RwLock<String>>,
     is_ready: Arc<AtomicBool>,
     shutting_down: Arc<AtomicBool>,
+    config_reload_supported: Arc<RwLock<Option<bool>>>,
     http_client: Client,
 }
 
@@ -58,7 +88,7 @@ fn normalize_api_prefix(prefix: &str) -> String {
 
 impl OpenCodeManager {
     pub fn new_with_directory(initial_dir: Option<PathBuf>) -> Self {
-        let desired_port = std::env::var("OPENCHAMBER_OPENCODE_PORT")
+        let default_port = std::env::var("OPENCHAMBER_OPENCODE_PORT")
             .ok()
             .and_then(|raw| raw.parse::<u16>().ok())
             .unwrap_or(0);
@@ -75,17 +105,9 @@ impl OpenCodeManager {
             warn!("[desktop:opencode] OpenCode CLI not found - app will run in limited mode");
         }
 
-        let mut args = vec![
-            "serve".to_string(),
-            "--port".to_string(),
-            desired_port.to_string(),
-        ];
-        if let Ok(config) = std::env::var("OPENCHAMBER_OPENCODE_CONFIG") {
-            if !config.is_empty() {
-                args.push("--config".to_string());
-                args.push(config);
-            }
-        }
+        let default_config = std::env::var("OPENCHAMBER_OPENCODE_CONFIG")
+            .ok()
+            .filter(|config| !config.is_empty());
 
         let env = build_augmented_env();
         let working_dir = initial_dir
@@ -97,16 +119,18 @@ impl OpenCodeManager {
         );
 
         Self {
-            binary,
-            args,
+            binary: Arc::new(RwLock::new(binary)),
+            default_config,
             env,
             working_dir: Arc::new(RwLock::new(working_dir)),
-            desired_port,
+            default_port,
+            launch_profile: Arc::new(RwLock::new(None)),
             child: Arc::new(Mutex::new(None)),
             port: Arc::new(RwLock::new(None)),
             api_prefix: Arc::new(RwLock::new(String::new())),
             is_ready: Arc::new(AtomicBool::new(false)),
             shutting_down: Arc::new(AtomicBool::new(false)),
+            config_reload_supported: Arc::new(RwLock::new(None)),
             http_client: Client::builder()
                 .timeout(Duration::from_secs(2))
                 .build()
@@ -114,14 +138,27 @@ impl OpenCodeManager {
         }
     }
 
+    /// Extra variables for the OpenCode process, on top of the augmented environment
+    pub fn with_env(mut self, vars: Vec<(String, String)>) -> Self {
+        self.env.extend(vars);
+        self
+    }
+
+    /// Whether the binary the next spawn would launch, including a profile override, exists
     pub fn is_cli_available(&self) -> bool {
-        self.binary.is_some()
+        self.launch_binary().is_ok()
+    }
+
+    /// Re-resolve the OpenCode binary (e.g. after a managed install); takes effect on next spawn
+    pub fn refresh_binary(&self) -> bool {
+        let binary = resolve_opencode_binary();
+        let available = binary.is_some();
+        *self.binary.write() = binary;
+        available
     }
 
     pub async fn ensure_running(&self) -> Result<()> {
-        if self.binary.is_none() {
-            return Err(anyhow!("OpenCode CLI is not available"));
-        }
+        self.launch_binary()?;
 
         let mut guard = self.child.lock().await;
         if let Some(child) = guard.as_mut() {
@@ -136,7 +173,7 @@ impl OpenCodeManager {
         drop(guard);
 
         // Wait for port detection from logs
-        if self.desired_port == 0 {
+        if self.desired_port() == 0 {
             self.wait_for_port_detection().await?;
         }
 
@@ -162,15 +199,105 @@ impl OpenCodeManager {
         // Brief delay to let OS release resources
         tokio::time::sleep(Duration::from_millis(250)).await;
 
-        // Reset state
-        if self.desired_port == 0 {
-            *self.port.write() = None;
-        }
+        // Reset state; the port is re-applied on spawn since the launch profile may have changed it
+        *self.port.write() = None;
         *self.api_prefix.write() = String::new();
+        *self.config_reload_supported.write() = None;
 
         self.ensure_running().await
     }
 
+    /// Ask the running OpenCode to drop its instance so config is re-read without a restart.
+    /// Returns Ok(false) when the server does not expose the dispose endpoint.
+    pub async fn reload_config(&self) -> Result<bool> {
+        if *self.config_reload_supported.read() == Some(false) {
+            return Ok(false);
+        }
+        let Some(port) = self.current_port() else {
+            return Ok(false);
+        };
+
+        let directory = self.get_working_directory().to_string_lossy().to_string();
+        let url = format!("http://127.0.0.1:{port}{}/instance/dispose", self.api_prefix());
+        let response = self
+            .http_client
+            .post(&url)
+            .query(&[("directory", directory.as_str())])
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        // Older servers answer unknown routes with 404/405 or the HTML app shell instead of `true`
+        if !status.is_success() || body.trim() != "true" {
+            if status.is_server_error() {
+                return Err(anyhow!("/instance/dispose returned {}", status));
+            }
+            debug!(
+                "[desktop:opencode] config reload unsupported (status {}, body {:?})",
+                status,
+                body.chars().take(64).collect::<String>()
+            );
+            *self.config_reload_supported.write() = Some(false);
+            return Ok(false);
+        }
+
+        *self.config_reload_supported.write() = Some(true);
+        self.wait_for_ready().await?;
+        info!("[desktop:opencode] configuration reloaded in place");
+        Ok(true)
+    }
+
+    /// Answer a pending tool permission request for `session_id` in `directory`
+    pub async fn reply_permission(
+        &self,
+        directory: &str,
+        session_id: &str,
+        permission_id: &str,
+        reply: PermissionReply,
+    ) -> Result<()> {
+        let port = self
+            .current_port()
+            .ok_or_else(|| anyhow!("OpenCode is not running"))?;
+        let base_url = format!("http://127.0.0.1:{port}{}", self.api_prefix());
+
+        let response = self
+            .http_client
+            .post(format!(
+                "{base_url}/session/{session_id}/permissions/{permission_id}"
+            ))
+            .query(&[("directory", directory)])
+            .json(&serde_json::json!({ "response": reply }))
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        // Newer servers moved replies to a session-independent route
+        if status == reqwest::StatusCode::NOT_FOUND
+            || status == reqwest::StatusCode::METHOD_NOT_ALLOWED
+        {
+            let response = self
+                .http_client
+                .post(format!("{base_url}/permission/{permission_id}/reply"))
+                .query(&[("directory", directory)])
+                .json(&serde_json::json!({ "reply": reply }))
+                .send()
+                .await?;
+            if response.status().is_success() {
+                return Ok(());
+            }
+            return Err(anyhow!(
+                "/permission/{permission_id}/reply returned {}",
+                response.status()
+            ));
+        }
+
+        Err(anyhow!("permission reply returned {}", status))
+    }
+
     pub async fn shutdown(&self) -> Result<()> {
         self.shutting_down.store(true, Ordering::SeqCst);
         self.is_ready.store(false, Ordering::SeqCst);
@@ -186,6 +313,74 @@ impl OpenCodeManager {
         self.working_dir.read().clone()
     }
 
+    /// Set launch overrides for the current project; applied on the next (re)start
+    pub fn set_launch_profile(&self, profile: Option<LaunchProfile>) {
+        let mut guard = self.launch_profile.write();
+        if *guard != profile {
+            info!("[desktop:opencode] launch profile updated: {:?}", profile);
+        }
+        *guard = profile;
+    }
+
+    pub fn launch_profile(&self) -> Option<LaunchProfile> {
+        self.launch_profile.read().clone()
+    }
+
+    fn desired_port(&self) -> u16 {
+        self.launch_profile
+            .read()
+            .as_ref()
+            .and_then(|profile| profile.port)
+            .unwrap_or(self.default_port)
+    }
+
+    /// The profile's binary when it sets one, never silently swapped for the default
+    fn launch_binary(&self) -> Result<String> {
+        let override_binary = self
+            .launch_profile
+            .read()
+            .as_ref()
+            .and_then(|profile| profile.binary.clone());
+        if let Some(value) = override_binary {
+            let path = expand_tilde_path(&value);
+            if !path.exists() {
+                return Err(anyhow!(
+                    "OpenCode binary from the launch profile does not exist: {}",
+                    path.display()
+                ));
+            }
+            return Ok(path.to_string_lossy().to_string());
+        }
+        self.binary
+            .read()
+            .clone()
+            .ok_or_else(|| anyhow!("OpenCode CLI is not available"))
+    }
+
+    fn launch_args(&self) -> Vec<String> {
+        let profile = self.launch_profile();
+        let mut args = vec![
+            "serve".to_string(),
+            "--port".to_string(),
+            self.desired_port().to_string(),
+        ];
+
+        let config = profile
+            .as_ref()
+            .and_then(|p| p.config_path.as_deref())
+            .map(|value| expand_tilde_path(value).to_string_lossy().to_string())
+            .or_else(|| self.default_config.clone());
+        if let Some(config) = config {
+            args.push("--config".to_string());
+            args.push(config);
+        }
+
+        if let Some(profile) = profile {
+            args.extend(profile.args);
+        }
+        args
+    }
+
     async fn detect_api_prefix(&self) -> Result<()> {
         let Some(port) = self.current_port() else {
             return Err(anyhow!("Cannot detect API prefix without port"));
@@ -268,18 +463,17 @@ impl OpenCodeManager {
     }
 
     async fn spawn_process(&self) -> Result<Child> {
-        let binary = self.binary.as_ref().ok_or_else(|| {
-            anyhow!("Cannot spawn process: OpenCode CLI is not available")
-        })?;
+        let binary = self.launch_binary()?;
+        let args = self.launch_args();
 
         info!(
             "[desktop:opencode] launching {} {:?}",
-            binary, self.args
+            binary, args
         );
 
         let working_dir = self.working_dir.read().clone();
-        let mut cmd = Command::new(binary);
-        cmd.args(&self.args)
+        let mut cmd = Command::new(&binary);
+        cmd.args(&args)
             .current_dir(&working_dir)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -288,6 +482,11 @@ impl OpenCodeManager {
         for (key, value) in &self.env {
             cmd.env(key, value);
         }
+        if let Some(profile) = self.launch_profile() {
+            for (key, value) in &profile.env {
+                cmd.env(key, value);
+            }
+        }
 
         let mut child = cmd.spawn().map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
@@ -301,8 +500,9 @@ impl OpenCodeManager {
         })?;
 
         // Set port immediately if pre-configured
-        if self.desired_port > 0 {
-            *self.port.write() = Some(self.desired_port);
+        let desired_port = self.desired_port();
+        if desired_port > 0 {
+            *self.port.write() = Some(desired_port);
         }
 
         // Wait for first signal (stdout/stderr) within 750ms to confirm startup
@@ -525,6 +725,11 @@ fn resolve_opencode_binary() -> Option<String> {
         }
     }
 
+    if let Some(managed) = crate::opencode_installer::managed_binary_path() {
+        info!("[desktop:opencode] using managed binary: {:?}", managed);
+        return Some(managed.to_string_lossy().to_string());
+    }
+
     let shell_env = detect_shell_env();
 
     if let Some(ref binary) = shell_env.opencode_binary {
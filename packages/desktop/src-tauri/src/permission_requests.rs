@@ -0,0 +1,160 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::event_hub::OpenCodeEvent;
+use crate::opencode_manager::{OpenCodeManager, PermissionReply};
+
+/// A tool call waiting for the user's approval
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingPermission {
+    pub id: String,
+    pub session_id: String,
+    pub directory: String,
+    /// Tool or permission kind, e.g. `bash` or `edit`
+    pub tool: String,
+    /// Command, path or title the tool wants to act on
+    pub summary: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl PendingPermission {
+    /// Build from a `permission.updated` (older OpenCode) or `permission.asked` (newer) event
+    pub fn from_event(event: &OpenCodeEvent) -> Option<Self> {
+        let properties = &event.properties;
+        let id = string_field(properties, "id")?;
+        let session_id = string_field(properties, "sessionID")?;
+        let tool = string_field(properties, "type")
+            .or_else(|| string_field(properties, "permission"))
+            .unwrap_or_else(|| "tool".to_string());
+
+        let summary = properties
+            .get("metadata")
+            .and_then(|metadata| string_field(metadata, "command"))
+            .or_else(|| string_field(properties, "title"))
+            .or_else(|| string_field(properties, "pattern"))
+            .or_else(|| {
+                let patterns: Vec<&str> = properties
+                    .get("patterns")
+                    .and_then(Value::as_array)?
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect();
+                (!patterns.is_empty()).then(|| patterns.join(", "))
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            id,
+            session_id,
+            directory: event.directory.clone(),
+            tool,
+            summary,
+            requested_at: Utc::now(),
+        })
+    }
+
+    /// One-line label for notifications and menus
+    pub fn label(&self) -> String {
+        if self.summary.is_empty() {
+            self.tool.clone()
+        } else {
+            format!("{}: {}", self.tool, self.summary)
+        }
+    }
+}
+
+/// Permission requests seen on the event stream that nobody has answered yet
+#[derive(Clone, Default)]
+pub struct PendingPermissions {
+    inner: Arc<Mutex<HashMap<String, PendingPermission>>>,
+}
+
+impl PendingPermissions {
+    /// Returns false when the request was already known
+    pub fn insert(&self, permission: PendingPermission) -> bool {
+        self.inner
+            .lock()
+            .insert(permission.id.clone(), permission)
+            .is_none()
+    }
+
+    pub fn remove(&self, permission_id: &str) -> Option<PendingPermission> {
+        self.inner.lock().remove(permission_id)
+    }
+
+    pub fn get(&self, permission_id: &str) -> Option<PendingPermission> {
+        self.inner.lock().get(permission_id).cloned()
+    }
+
+    /// Forget every request from `directory`; returns true when any were dropped.
+    /// Used when its event stream reconnects, since replies sent meanwhile were never seen.
+    pub fn clear_directory(&self, directory: &str) -> bool {
+        let mut inner = self.inner.lock();
+        let before = inner.len();
+        inner.retain(|_, permission| permission.directory != directory);
+        inner.len() != before
+    }
+
+    /// Oldest first
+    pub fn list(&self) -> Vec<PendingPermission> {
+        let mut pending: Vec<PendingPermission> = self.inner.lock().values().cloned().collect();
+        pending.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        pending
+    }
+
+    /// Track `permission.*` events; returns true when the pending set changed
+    pub fn observe(&self, event: &OpenCodeEvent) -> bool {
+        match event.event_type.as_str() {
+            "permission.updated" | "permission.asked" => PendingPermission::from_event(event)
+                .map(|permission| self.insert(permission))
+                .unwrap_or(false),
+            // Answered elsewhere (webview, another client, or us)
+            "permission.replied" => {
+                let properties = &event.properties;
+                string_field(properties, "permissionID")
+                    .or_else(|| string_field(properties, "requestID"))
+                    .or_else(|| string_field(properties, "id"))
+                    .map(|id| self.remove(&id).is_some())
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Forward `reply` to OpenCode and forget the request once it is accepted
+    pub async fn reply(
+        &self,
+        opencode: &OpenCodeManager,
+        permission_id: &str,
+        reply: PermissionReply,
+    ) -> Result<PendingPermission> {
+        let permission = self
+            .get(permission_id)
+            .ok_or_else(|| anyhow!("Unknown permission request: {permission_id}"))?;
+        opencode
+            .reply_permission(
+                &permission.directory,
+                &permission.session_id,
+                &permission.id,
+                reply,
+            )
+            .await?;
+        self.remove(permission_id);
+        Ok(permission)
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
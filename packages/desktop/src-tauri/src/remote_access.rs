@@ -0,0 +1,934 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr, UdpSocket},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context as TaskContext, Poll},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::{connect_info::Connected, ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::Response,
+    routing::post,
+    serve::IncomingStream,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, oneshot, Mutex},
+};
+use tokio_rustls::{
+    rustls::{
+        self,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    },
+    server::TlsStream,
+    TlsAcceptor,
+};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+use uuid::Uuid;
+
+use crate::server_auth::{constant_time_eq, presented_token, strip_credentials, unauthorized};
+
+pub const DEFAULT_REMOTE_BIND_ADDRESS: &str = "0.0.0.0";
+pub const DEFAULT_REMOTE_PORT: u16 = 57_123;
+
+const PAIR_ROUTE: &str = "/api/openchamber/pair";
+const DEVICES_FILE: &str = "remote-devices.json";
+const TLS_DIR: &str = "tls";
+const CERT_FILE: &str = "remote-cert.pem";
+const KEY_FILE: &str = "remote-key.pem";
+/// Subject alternative names the current certificate was issued for, one per line
+const CERT_NAMES_FILE: &str = "remote-cert.names";
+const DEFAULT_CERT_NAMES: &[&str] = &["localhost", "openchamber.local"];
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(5 * 60);
+const PAIRING_MAX_ATTEMPTS: u32 = 5;
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Avoid rewriting the device file on every request just to bump `lastSeenAt`
+const LAST_SEEN_PERSIST_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+
+/// `remoteAccess` block in settings.json
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RemoteAccessConfig {
+    pub enabled: bool,
+    pub bind_address: String,
+    pub port: u16,
+}
+
+impl Default for RemoteAccessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: DEFAULT_REMOTE_BIND_ADDRESS.to_string(),
+            port: DEFAULT_REMOTE_PORT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredDevice {
+    id: String,
+    name: String,
+    token_hash: String,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    last_seen_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DeviceFile {
+    #[serde(default)]
+    devices: Vec<StoredDevice>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+impl From<&StoredDevice> for PairedDevice {
+    fn from(device: &StoredDevice) -> Self {
+        Self {
+            id: device.id.clone(),
+            name: device.name.clone(),
+            created_at: device.created_at,
+            last_seen_at: device.last_seen_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+struct PendingPairing {
+    code: String,
+    expires: Instant,
+    attempts: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PairRequest {
+    code: String,
+    device_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PairResponse {
+    device_id: String,
+    token: String,
+    certificate_fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteAccessStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub bind_address: String,
+    pub port: u16,
+    pub url: Option<String>,
+    pub certificate_fingerprint: Option<String>,
+    pub pairing_active: bool,
+}
+
+/// Paired phones and the one-time code used to pair a new one
+pub struct DeviceRegistry {
+    path: PathBuf,
+    devices: RwLock<Vec<StoredDevice>>,
+    pairing: RwLock<Option<PendingPairing>>,
+    fingerprint: RwLock<Option<String>>,
+    /// Open connections per device id, so revoking a device also cuts its live streams
+    connections: RwLock<HashMap<String, HashMap<u64, CancellationToken>>>,
+}
+
+impl DeviceRegistry {
+    pub fn load() -> Result<Self> {
+        Ok(Self::open(config_dir()?.join(DEVICES_FILE)))
+    }
+
+    fn open(path: PathBuf) -> Self {
+        let devices = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<DeviceFile>(&bytes).ok())
+            .unwrap_or_default()
+            .devices;
+        Self {
+            path,
+            devices: RwLock::new(devices),
+            pairing: RwLock::new(None),
+            fingerprint: RwLock::new(None),
+            connections: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<PairedDevice> {
+        self.devices.read().iter().map(PairedDevice::from).collect()
+    }
+
+    /// Issue a fresh six-digit code; any previous unused code stops working
+    pub fn begin_pairing(&self) -> PairingCode {
+        let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+        *self.pairing.write() = Some(PendingPairing {
+            code: code.clone(),
+            expires: Instant::now() + PAIRING_CODE_TTL,
+            attempts: 0,
+        });
+        PairingCode {
+            code,
+            expires_at: Utc::now()
+                + chrono::TimeDelta::from_std(PAIRING_CODE_TTL).unwrap_or_default(),
+        }
+    }
+
+    pub fn cancel_pairing(&self) {
+        self.pairing.write().take();
+    }
+
+    fn pairing_active(&self) -> bool {
+        self.pairing
+            .read()
+            .as_ref()
+            .map(|pending| pending.expires > Instant::now())
+            .unwrap_or(false)
+    }
+
+    /// Exchange the one-time code for a long-lived device token (returned once, stored hashed)
+    async fn complete_pairing(&self, code: &str, name: &str) -> Result<(String, String)> {
+        {
+            let mut pairing = self.pairing.write();
+            let pending = pairing
+                .as_mut()
+                .ok_or_else(|| anyhow!("No pairing in progress"))?;
+            if pending.expires <= Instant::now() {
+                pairing.take();
+                return Err(anyhow!("Pairing code expired"));
+            }
+            if !constant_time_eq(pending.code.as_bytes(), code.trim().as_bytes()) {
+                pending.attempts += 1;
+                if pending.attempts >= PAIRING_MAX_ATTEMPTS {
+                    pairing.take();
+                }
+                return Err(anyhow!("Invalid pairing code"));
+            }
+            pairing.take();
+        }
+
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let device = StoredDevice {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            token_hash: hash_token(&token),
+            created_at: Utc::now(),
+            last_seen_at: None,
+        };
+        let id = device.id.clone();
+        self.devices.write().push(device);
+        self.persist().await?;
+        Ok((id, token))
+    }
+
+    pub async fn revoke(&self, id: &str) -> Result<bool> {
+        let removed = {
+            let mut devices = self.devices.write();
+            let before = devices.len();
+            devices.retain(|device| device.id != id);
+            devices.len() != before
+        };
+        if removed {
+            self.persist().await?;
+            let closed = self.disconnect(id);
+            info!("[desktop:remote] revoked device {id}, closed {closed} connection(s)");
+        }
+        Ok(removed)
+    }
+
+    /// Remember that `connection` carries requests from `device_id`
+    fn track(&self, device_id: &str, connection: &RemoteConnection) {
+        let mut connections = self.connections.write();
+        // Connections cancel their own token when they end, so this drops finished ones
+        for open in connections.values_mut() {
+            open.retain(|_, closed| !closed.is_cancelled());
+        }
+        connections.retain(|_, open| !open.is_empty());
+        connections
+            .entry(device_id.to_string())
+            .or_default()
+            .entry(connection.id)
+            .or_insert_with(|| connection.closed.clone());
+    }
+
+    /// Close every connection the device has open, including SSE streams and WebSockets
+    fn disconnect(&self, device_id: &str) -> usize {
+        let open = self
+            .connections
+            .write()
+            .remove(device_id)
+            .unwrap_or_default();
+        for closed in open.values() {
+            closed.cancel();
+        }
+        open.len()
+    }
+
+    /// Id of the paired device `token` belongs to, bumping its last-seen time
+    async fn authenticate(&self, token: &str) -> Option<String> {
+        let hash = hash_token(token);
+        let now = Utc::now();
+        let (device_id, needs_persist) = {
+            let mut devices = self.devices.write();
+            let device = devices
+                .iter_mut()
+                .find(|device| constant_time_eq(device.token_hash.as_bytes(), hash.as_bytes()))?;
+            let stale = device
+                .last_seen_at
+                .map(|seen| now - seen >= LAST_SEEN_PERSIST_INTERVAL)
+                .unwrap_or(true);
+            if stale {
+                device.last_seen_at = Some(now);
+            }
+            (device.id.clone(), stale)
+        };
+        if needs_persist {
+            if let Err(err) = self.persist().await {
+                warn!("[desktop:remote] Failed to record device activity: {err}");
+            }
+        }
+        Some(device_id)
+    }
+
+    async fn persist(&self) -> Result<()> {
+        let file = DeviceFile {
+            devices: self.devices.read().clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        write_private(&self.path, &serde_json::to_vec_pretty(&file)?).await
+    }
+}
+
+struct RunningServer {
+    config: RemoteAccessConfig,
+    shutdown: oneshot::Sender<()>,
+    /// Parent of every connection's token; graceful shutdown alone would wait on open streams
+    connections: CancellationToken,
+}
+
+impl RunningServer {
+    fn stop(self) {
+        let _ = self.shutdown.send(());
+        self.connections.cancel();
+    }
+}
+
+/// Optional TLS listener that exposes the `/api/*` router to paired devices on the network
+pub struct RemoteAccessServer {
+    registry: Arc<DeviceRegistry>,
+    router: Router,
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl RemoteAccessServer {
+    /// `api` is the same router served on loopback, before its local auth/CORS layers
+    pub fn new(registry: Arc<DeviceRegistry>, api: Router) -> Self {
+        let router = api
+            .route(PAIR_ROUTE, post(pair_handler).with_state(registry.clone()))
+            .layer(middleware::from_fn_with_state(
+                registry.clone(),
+                require_device_token,
+            ));
+        Self {
+            registry,
+            router,
+            running: Mutex::new(None),
+        }
+    }
+
+    pub fn registry(&self) -> &DeviceRegistry {
+        &self.registry
+    }
+
+    /// Start, stop or rebind the listener so it matches `config`
+    pub async fn apply(&self, config: &RemoteAccessConfig) -> Result<()> {
+        let mut running = self.running.lock().await;
+        if let Some(current) = running.as_ref() {
+            if config.enabled && current.config == *config {
+                return Ok(());
+            }
+        }
+        if let Some(previous) = running.take() {
+            previous.stop();
+            info!("[desktop:remote] stopped remote access listener");
+        }
+        if !config.enabled {
+            self.registry.cancel_pairing();
+            return Ok(());
+        }
+
+        let ip: IpAddr = config
+            .bind_address
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid bind address {}", config.bind_address))?;
+        let addr = SocketAddr::new(ip, config.port);
+
+        let (acceptor, fingerprint) = load_tls_acceptor(ip).await?;
+        *self.registry.fingerprint.write() = Some(fingerprint);
+
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind {addr}"))?;
+        let connections = CancellationToken::new();
+        let tls_listener = TlsListener::spawn(listener, acceptor, connections.clone())?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let service = self
+            .router
+            .clone()
+            .into_make_service_with_connect_info::<RemoteConnection>();
+
+        tauri::async_runtime::spawn(async move {
+            let result = axum::serve(tls_listener, service)
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(err) = result {
+                error!("[desktop:remote] server stopped: {err:?}");
+            }
+        });
+
+        info!("[desktop:remote] listening on https://{addr}");
+        *running = Some(RunningServer {
+            config: config.clone(),
+            shutdown: shutdown_tx,
+            connections,
+        });
+        Ok(())
+    }
+
+    pub async fn status(&self, config: &RemoteAccessConfig) -> RemoteAccessStatus {
+        let running = self.running.lock().await;
+        let active = running.as_ref().map(|server| &server.config);
+        let effective = active.unwrap_or(config);
+        RemoteAccessStatus {
+            enabled: config.enabled,
+            running: active.is_some(),
+            bind_address: effective.bind_address.clone(),
+            port: effective.port,
+            url: active.map(|active| format!("https://{}:{}", active.bind_address, active.port)),
+            certificate_fingerprint: self.registry.fingerprint.read().clone(),
+            pairing_active: self.registry.pairing_active(),
+        }
+    }
+
+    pub async fn shutdown(&self) {
+        if let Some(server) = self.running.lock().await.take() {
+            server.stop();
+        }
+    }
+}
+
+async fn pair_handler(
+    State(registry): State<Arc<DeviceRegistry>>,
+    Json(payload): Json<PairRequest>,
+) -> Result<Json<PairResponse>, (StatusCode, String)> {
+    let name = payload
+        .device_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("Unnamed device");
+
+    match registry.complete_pairing(&payload.code, name).await {
+        Ok((device_id, token)) => {
+            info!("[desktop:remote] paired device {name} ({device_id})");
+            Ok(Json(PairResponse {
+                device_id,
+                token,
+                certificate_fingerprint: registry.fingerprint.read().clone(),
+            }))
+        }
+        Err(err) => {
+            warn!("[desktop:remote] pairing attempt failed: {err}");
+            Err((StatusCode::FORBIDDEN, err.to_string()))
+        }
+    }
+}
+
+/// Only paired devices get through; the pairing endpoint itself is the one exception
+async fn require_device_token(
+    State(registry): State<Arc<DeviceRegistry>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if req.uri().path() == PAIR_ROUTE {
+        return next.run(req).await;
+    }
+
+    let device_id = match presented_token(&req) {
+        Some(token) => registry.authenticate(&token).await,
+        None => None,
+    };
+    let Some(device_id) = device_id else {
+        return unauthorized(&req);
+    };
+    if let Some(ConnectInfo(connection)) = req.extensions().get::<ConnectInfo<RemoteConnection>>() {
+        registry.track(&device_id, connection);
+    }
+
+    strip_credentials(&mut req);
+    next.run(req).await
+}
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Handle on one accepted connection, available to handlers as `ConnectInfo`
+#[derive(Clone)]
+struct RemoteConnection {
+    id: u64,
+    closed: CancellationToken,
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for RemoteConnection {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        stream.io().connection.clone()
+    }
+}
+
+/// TLS stream that fails all I/O once its connection is cancelled, which ends
+/// hyper's connection task along with any streaming body or upgraded WebSocket
+struct RemoteStream {
+    inner: TlsStream<TcpStream>,
+    connection: RemoteConnection,
+    closed: Pin<Box<WaitForCancellationFutureOwned>>,
+}
+
+impl RemoteStream {
+    fn new(inner: TlsStream<TcpStream>, connection: RemoteConnection) -> Self {
+        let closed = Box::pin(connection.closed.clone().cancelled_owned());
+        Self {
+            inner,
+            connection,
+            closed,
+        }
+    }
+
+    fn poll_closed(&mut self, cx: &mut TaskContext<'_>) -> Option<io::Error> {
+        let closed =
+            self.connection.closed.is_cancelled() || self.closed.as_mut().poll(cx).is_ready();
+        closed.then(|| io::Error::new(io::ErrorKind::ConnectionAborted, "connection closed"))
+    }
+}
+
+impl Drop for RemoteStream {
+    fn drop(&mut self) {
+        self.connection.closed.cancel();
+    }
+}
+
+impl AsyncRead for RemoteStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(err) = this.poll_closed(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for RemoteStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if let Some(err) = this.poll_closed(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Pin::new(&mut this.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(err) = this.poll_closed(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// Completes TLS handshakes off the accept loop so one slow client can't stall the others
+struct TlsListener {
+    incoming: mpsc::Receiver<(RemoteStream, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    fn spawn(
+        listener: TcpListener,
+        acceptor: TlsAcceptor,
+        connections: CancellationToken,
+    ) -> Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (tx, incoming) = mpsc::channel(32);
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let (stream, peer) = tokio::select! {
+                    _ = tx.closed() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(err) => {
+                            warn!("[desktop:remote] accept failed: {err}");
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    },
+                };
+
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                let connection = RemoteConnection {
+                    id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+                    closed: connections.child_token(),
+                };
+                tauri::async_runtime::spawn(async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                    {
+                        Ok(Ok(tls)) => {
+                            let _ = tx.send((RemoteStream::new(tls, connection), peer)).await;
+                        }
+                        Ok(Err(err)) => {
+                            warn!("[desktop:remote] TLS handshake with {peer} failed: {err}")
+                        }
+                        Err(_) => warn!("[desktop:remote] TLS handshake with {peer} timed out"),
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            incoming,
+            local_addr,
+        })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = RemoteStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(connection) => connection,
+            // The accept loop only ends once the server itself is gone
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Load the self-signed certificate, generating it when missing or when it does not name the
+/// address devices connect to, and return its SHA-256 fingerprint
+async fn load_tls_acceptor(bind: IpAddr) -> Result<(TlsAcceptor, String)> {
+    let dir = config_dir()?.join(TLS_DIR);
+    let cert_path = dir.join(CERT_FILE);
+    let key_path = dir.join(KEY_FILE);
+    let names_path = dir.join(CERT_NAMES_FILE);
+
+    let exists = cert_path.exists() && key_path.exists();
+    let mut names: Vec<String> = if exists {
+        fs::read_to_string(&names_path)
+            .await
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut covered = exists;
+    for name in DEFAULT_CERT_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(advertised_address(bind).map(|ip| ip.to_string()))
+    {
+        if !names.contains(&name) {
+            names.push(name);
+            covered = false;
+        }
+    }
+
+    if !covered {
+        fs::create_dir_all(&dir).await?;
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(names.clone())
+                .context("Failed to generate TLS certificate")?;
+        write_private(&key_path, key_pair.serialize_pem().as_bytes()).await?;
+        fs::write(&cert_path, cert.pem()).await?;
+        fs::write(&names_path, names.join("\n")).await?;
+        if exists {
+            warn!("[desktop:remote] reissued certificate for {names:?}; paired devices will see a new fingerprint");
+        } else {
+            info!("[desktop:remote] generated self-signed certificate at {cert_path:?}");
+        }
+    }
+
+    let certs = CertificateDer::pem_file_iter(&cert_path)
+        .context("Failed to read TLS certificate")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse TLS certificate")?;
+    let key = PrivateKeyDer::from_pem_file(&key_path).context("Failed to read TLS key")?;
+    let fingerprint = certs
+        .first()
+        .map(|cert| format_fingerprint(&Sha256::digest(cert.as_ref())))
+        .ok_or_else(|| anyhow!("TLS certificate file is empty"))?;
+
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)?;
+
+    Ok((TlsAcceptor::from(Arc::new(config)), fingerprint))
+}
+
+/// Address devices reach the listener on: the bind address, or the LAN address of the
+/// default route when bound to all interfaces
+fn advertised_address(bind: IpAddr) -> Option<IpAddr> {
+    if !bind.is_unspecified() {
+        return Some(bind);
+    }
+    // Connecting a UDP socket only picks a route; nothing is sent
+    let socket = UdpSocket::bind((bind, 0)).ok()?;
+    let probe: IpAddr = if bind.is_ipv4() {
+        [192, 0, 2, 1].into()
+    } else {
+        [0x2001, 0xdb8, 0, 0, 0, 0, 0, 1].into()
+    };
+    socket.connect((probe, 9)).ok()?;
+    let local = socket.local_addr().ok()?.ip();
+    (!local.is_unspecified() && !local.is_loopback()).then_some(local)
+}
+
+fn format_fingerprint(digest: &[u8]) -> String {
+    digest
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn config_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    Ok(home.join(".config").join("openchamber"))
+}
+
+/// Replace `path` through a temp file that is owner-only from the moment it is created,
+/// so tokens and keys are never readable by other users, not even briefly
+async fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", Uuid::new_v4()));
+    let tmp = PathBuf::from(tmp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let written = async {
+        let mut file = options.open(&tmp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+        fs::rename(&tmp, path).await
+    }
+    .await;
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp).await;
+    }
+    Ok(written?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> (PathBuf, DeviceRegistry) {
+        let dir = std::env::temp_dir().join(format!("openchamber-remote-{}", Uuid::new_v4()));
+        let registry = DeviceRegistry::open(dir.join(DEVICES_FILE));
+        (dir, registry)
+    }
+
+    async fn pair(registry: &DeviceRegistry) -> (String, String) {
+        let code = registry.begin_pairing().code;
+        registry.complete_pairing(&code, "Phone").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn pairing_issues_a_token_that_authenticates() {
+        let (dir, registry) = registry();
+        assert!(!registry.pairing_active());
+        let code = registry.begin_pairing();
+        assert!(registry.pairing_active());
+        assert_eq!(code.code.len(), 6);
+
+        let (device_id, token) = registry
+            .complete_pairing(&code.code, "Phone")
+            .await
+            .unwrap();
+        assert!(!registry.pairing_active());
+        assert_eq!(registry.authenticate(&token).await, Some(device_id.clone()));
+        assert_eq!(registry.authenticate("not-a-token").await, None);
+
+        // The code is single use
+        let err = registry
+            .complete_pairing(&code.code, "Tablet")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "No pairing in progress");
+
+        // Only the hash is stored, and it survives a reload
+        let stored = std::fs::read_to_string(dir.join(DEVICES_FILE)).unwrap();
+        assert!(!stored.contains(&token));
+        let reloaded = DeviceRegistry::open(dir.join(DEVICES_FILE));
+        assert_eq!(reloaded.authenticate(&token).await, Some(device_id));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn expired_pairing_code_is_rejected() {
+        let (dir, registry) = registry();
+        let code = registry.begin_pairing().code;
+        registry.pairing.write().as_mut().unwrap().expires = Instant::now();
+
+        assert!(!registry.pairing_active());
+        let err = registry.complete_pairing(&code, "Phone").await.unwrap_err();
+        assert_eq!(err.to_string(), "Pairing code expired");
+        assert!(registry.pairing.read().is_none());
+        assert!(registry.list().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn pairing_ends_after_too_many_wrong_codes() {
+        let (dir, registry) = registry();
+        let code = registry.begin_pairing().code;
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        for _ in 0..PAIRING_MAX_ATTEMPTS {
+            let err = registry.complete_pairing(wrong, "Phone").await.unwrap_err();
+            assert_eq!(err.to_string(), "Invalid pairing code");
+        }
+        let err = registry.complete_pairing(&code, "Phone").await.unwrap_err();
+        assert_eq!(err.to_string(), "No pairing in progress");
+        assert!(registry.list().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn revoked_device_is_rejected_and_disconnected() {
+        let (dir, registry) = registry();
+        let (device_id, token) = pair(&registry).await;
+        let (other_id, other_token) = pair(&registry).await;
+
+        let connection = RemoteConnection {
+            id: 1,
+            closed: CancellationToken::new(),
+        };
+        let other_connection = RemoteConnection {
+            id: 2,
+            closed: CancellationToken::new(),
+        };
+        registry.track(&device_id, &connection);
+        registry.track(&other_id, &other_connection);
+
+        assert!(registry.revoke(&device_id).await.unwrap());
+        assert!(connection.closed.is_cancelled());
+        assert!(!other_connection.closed.is_cancelled());
+        assert_eq!(registry.authenticate(&token).await, None);
+        assert_eq!(registry.authenticate(&other_token).await, Some(other_id));
+        assert!(!registry.revoke(&device_id).await.unwrap());
+
+        let reloaded = DeviceRegistry::open(dir.join(DEVICES_FILE));
+        assert_eq!(reloaded.authenticate(&token).await, None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn private_files_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, registry) = registry();
+        pair(&registry).await;
+        let key = dir.join(KEY_FILE);
+        write_private(&key, b"secret").await.unwrap();
+
+        for path in [dir.join(DEVICES_FILE), key] {
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{path:?}");
+        }
+        let leftovers = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request, State},
+    http::{header, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::warn;
+use tauri::Manager;
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
+use uuid::Uuid;
+
+/// Query parameter accepted in place of the Authorization header (EventSource/WebSocket can't set headers).
+/// Namespaced so stripping it never eats a `token` parameter meant for OpenCode.
+const TOKEN_QUERY_PARAM: &str = "oc_token";
+
+/// Origins the bundled frontend is served from (macOS/Linux use the custom scheme, Windows uses http(s))
+const APP_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+];
+
+/// Random per-launch token; never persisted, so it dies with the process
+pub fn generate_token() -> Arc<str> {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()).into()
+}
+
+/// Restrict cross-origin access to the app's own webview (plus the dev server in dev builds)
+pub fn cors_layer(app: &tauri::AppHandle) -> CorsLayer {
+    let mut origins: Vec<HeaderValue> = APP_ORIGINS
+        .iter()
+        .copied()
+        .map(HeaderValue::from_static)
+        .collect();
+
+    if let Some(dev_url) = app
+        .config()
+        .build
+        .dev_url
+        .as_ref()
+        .filter(|_| cfg!(debug_assertions))
+    {
+        let origin = dev_url.origin().ascii_serialization();
+        if let Ok(value) = HeaderValue::from_str(&origin) {
+            origins.push(value);
+        }
+    }
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(Any)
+        // A wildcard does not cover Authorization, so echo the requested headers instead
+        .allow_headers(AllowHeaders::mirror_request())
+}
+
+/// Reject requests without the launch token, then strip it so it never reaches OpenCode
+pub async fn require_token(
+    State(token): State<Arc<str>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    // Liveness probe only; exposes no project data
+    if req.uri().path() == "/health" {
+        return next.run(req).await;
+    }
+
+    let authorized = presented_token(&req)
+        .map(|candidate| constant_time_eq(candidate.as_bytes(), token.as_bytes()))
+        .unwrap_or(false);
+    if !authorized {
+        return unauthorized(&req);
+    }
+
+    strip_credentials(&mut req);
+    next.run(req).await
+}
+
+/// Token from `Authorization: Bearer` or, failing that, the `oc_token` query parameter
+pub(crate) fn presented_token(req: &Request) -> Option<String> {
+    let header_token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let query_token = req.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| match pair.split_once('=') {
+                Some((TOKEN_QUERY_PARAM, value)) => Some(value),
+                _ => None,
+            })
+    });
+    header_token.or(query_token).map(str::to_string)
+}
+
+pub(crate) fn unauthorized(req: &Request) -> Response {
+    warn!(
+        "[desktop:http] Rejected unauthenticated request to {}",
+        req.uri().path()
+    );
+    (StatusCode::UNAUTHORIZED, Body::from("Unauthorized")).into_response()
+}
+
+/// Drop our credentials from the request before it is handled or proxied
+pub(crate) fn strip_credentials(req: &mut Request) {
+    req.headers_mut().remove(header::AUTHORIZATION);
+    if req.uri().query().is_some() {
+        strip_token_query(req);
+    }
+}
+
+fn strip_token_query(req: &mut Request) {
+    let strip = |uri: &Uri| -> Option<Uri> {
+        let query = uri.query()?;
+        let remaining: Vec<&str> = query
+            .split('&')
+            .filter(|pair| {
+                pair.split_once('=').map(|(key, _)| key).unwrap_or(pair) != TOKEN_QUERY_PARAM
+            })
+            .collect();
+        let path_and_query = if remaining.is_empty() {
+            uri.path().to_string()
+        } else {
+            format!("{}?{}", uri.path(), remaining.join("&"))
+        };
+        let mut parts = uri.clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+        Uri::from_parts(parts).ok()
+    };
+
+    if let Some(uri) = strip(req.uri()) {
+        *req.uri_mut() = uri;
+    }
+    // The proxy builds its upstream URL from OriginalUri, which the router captured before us
+    if let Some(original) = req.extensions().get::<OriginalUri>().map(|o| o.0.clone()) {
+        if let Some(uri) = strip(&original) {
+            req.extensions_mut().insert(OriginalUri(uri));
+        }
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> Request {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn reads_the_namespaced_query_token() {
+        assert_eq!(
+            presented_token(&request("/api/event?oc_token=abc&token=other")).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(presented_token(&request("/api/event?token=abc")), None);
+    }
+
+    #[test]
+    fn strips_only_our_token() {
+        let mut req = request("/api/file?token=upstream&oc_token=abc&path=a");
+        strip_credentials(&mut req);
+        assert_eq!(req.uri().to_string(), "/api/file?token=upstream&path=a");
+
+        let mut req = request("/api/event?oc_token=abc");
+        strip_credentials(&mut req);
+        assert_eq!(req.uri().to_string(), "/api/event");
+    }
+}
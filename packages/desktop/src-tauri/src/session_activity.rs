@@ -1,47 +1,181 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::Arc,
     time::Duration,
 };
 
 use anyhow::Result;
-use futures_util::TryStreamExt;
+use chrono::{DateTime, Utc};
 use log::{debug, info, warn};
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
-use tokio_util::io::StreamReader;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
 
+use crate::event_hub::{EventHub, HubEvent, OpenCodeEvent, WatchGuard};
+use crate::opencode_manager::OpenCodeManager;
+use crate::sse::ReconnectBackoff;
+use crate::tray;
 use crate::DesktopRuntime;
 
-#[derive(Deserialize)]
-struct EventEnvelope {
-    #[serde(rename = "type")]
-    event_type: String,
-    #[serde(default)]
-    properties: Value,
-}
+const STATUS_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often other projects are checked for sessions started outside this app
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(30);
+/// Status fetches tried after a reconnect before waiting for the next one
+const RECONCILE_ATTEMPTS: u32 = 5;
 
-#[derive(Deserialize)]
-struct MultiplexedEventEnvelope {
-    #[serde(default)]
-    directory: Option<String>,
-    payload: EventEnvelope,
-}
+/// Sessions are tracked per directory; the same id reported by two project streams stays two entries
+type SessionKey = (String, String);
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ActivityPhase {
     Idle,
     Busy,
     Cooldown,
 }
 
+impl ActivityPhase {
+    fn as_str(self) -> &'static str {
+        match self {
+            ActivityPhase::Idle => "idle",
+            ActivityPhase::Busy => "busy",
+            ActivityPhase::Cooldown => "cooldown",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
-enum SseScope {
-    Global,
-    Directory(std::path::PathBuf),
+struct SessionActivity {
+    phase: ActivityPhase,
+    changed_at: DateTime<Utc>,
+    busy_since: Option<DateTime<Utc>>,
+    last_busy_ms: Option<u64>,
+    total_busy_ms: u64,
+}
+
+impl SessionActivity {
+    fn new() -> Self {
+        Self {
+            phase: ActivityPhase::Idle,
+            changed_at: Utc::now(),
+            busy_since: None,
+            last_busy_ms: None,
+            total_busy_ms: 0,
+        }
+    }
+
+    fn transition(&mut self, phase: ActivityPhase, now: DateTime<Utc>) {
+        if phase == ActivityPhase::Busy {
+            self.busy_since.get_or_insert(now);
+        } else if let Some(since) = self.busy_since.take() {
+            let elapsed = (now - since).num_milliseconds().max(0) as u64;
+            self.last_busy_ms = Some(elapsed);
+            self.total_busy_ms += elapsed;
+        }
+        self.phase = phase;
+        self.changed_at = now;
+    }
+}
+
+/// Current activity of one session as returned by `get_session_activity`
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionActivitySnapshot {
+    pub session_id: String,
+    pub directory: String,
+    pub phase: ActivityPhase,
+    pub changed_at: DateTime<Utc>,
+    pub busy_since: Option<DateTime<Utc>>,
+    /// Length of the most recent completed busy period
+    pub last_busy_duration_ms: Option<u64>,
+    /// Busy time accumulated since the app started, including the current busy period
+    pub total_busy_ms: u64,
+}
+
+/// Session phases shared between the tracker task and the `get_session_activity` command
+#[derive(Clone, Default)]
+pub struct SessionActivityStore {
+    sessions: Arc<Mutex<HashMap<SessionKey, SessionActivity>>>,
+    cooldowns: Arc<Mutex<HashMap<SessionKey, tauri::async_runtime::JoinHandle<()>>>>,
+}
+
+impl SessionActivityStore {
+    /// All tracked sessions, or only those under `directory`, most recently changed first
+    pub async fn snapshot(&self, directory: Option<&str>) -> Vec<SessionActivitySnapshot> {
+        let now = Utc::now();
+        let sessions = self.sessions.lock().await;
+        let mut snapshot: Vec<SessionActivitySnapshot> = sessions
+            .iter()
+            .filter(|((dir, _), _)| directory.map_or(true, |wanted| wanted == dir))
+            .map(|((dir, session_id), activity)| {
+                let running = activity
+                    .busy_since
+                    .map(|since| (now - since).num_milliseconds().max(0) as u64)
+                    .unwrap_or(0);
+                SessionActivitySnapshot {
+                    session_id: session_id.clone(),
+                    directory: dir.clone(),
+                    phase: activity.phase,
+                    changed_at: activity.changed_at,
+                    busy_since: activity.busy_since,
+                    last_busy_duration_ms: activity.last_busy_ms,
+                    total_busy_ms: activity.total_busy_ms + running,
+                }
+            })
+            .collect();
+        snapshot.sort_by(|a, b| b.changed_at.cmp(&a.changed_at));
+        snapshot
+    }
+
+    async fn phase(&self, key: &SessionKey) -> Option<ActivityPhase> {
+        self.sessions.lock().await.get(key).map(|a| a.phase)
+    }
+
+    /// Sessions in `directory` that are generating right now
+    pub async fn busy_sessions(&self, directory: &str) -> usize {
+        self.sessions
+            .lock()
+            .await
+            .iter()
+            .filter(|((dir, _), activity)| {
+                dir == directory && activity.phase == ActivityPhase::Busy
+            })
+            .count()
+    }
+
+    async fn has_live_sessions(&self, directory: &str) -> bool {
+        self.sessions
+            .lock()
+            .await
+            .iter()
+            .any(|((dir, _), activity)| dir == directory && activity.phase != ActivityPhase::Idle)
+    }
+}
+
+#[derive(Deserialize)]
+struct ProjectInfo {
+    worktree: String,
+}
+
+#[derive(Deserialize)]
+struct SessionInfo {
+    id: String,
+}
+
+#[derive(Clone)]
+struct ActivityTracker {
+    app: AppHandle,
+    client: Client,
+    opencode: Arc<OpenCodeManager>,
+    hub: EventHub,
+    store: SessionActivityStore,
+    /// Upstream streams held open while a directory has busy or cooling-down sessions,
+    /// so switching projects does not stop tracking work still running elsewhere
+    watches: Arc<Mutex<HashMap<String, WatchGuard>>>,
+    /// Reconcile still retrying for a directory, replaced when it reconnects again
+    reconciles: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
 }
 
 pub fn spawn_session_activity_tracker(
@@ -50,426 +184,452 @@ pub fn spawn_session_activity_tracker(
 ) -> tauri::async_runtime::JoinHandle<()> {
     tauri::async_runtime::spawn(async move {
         let client = Client::builder()
-            .timeout(Duration::from_secs(24 * 60 * 60))
-            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .timeout(STATUS_REQUEST_TIMEOUT)
             .build()
             .expect("failed to build reqwest client");
 
         let mut shutdown_rx = runtime.subscribe_shutdown();
-        let phases = Arc::new(Mutex::new(HashMap::<String, ActivityPhase>::new()));
-        let cooldowns = Arc::new(Mutex::new(HashMap::<String, tauri::async_runtime::JoinHandle<()>>::new()));
+        let mut events = runtime.event_hub().subscribe();
+        let tracker = ActivityTracker {
+            app,
+            client,
+            opencode: runtime.opencode_manager(),
+            hub: runtime.event_hub().clone(),
+            store: runtime.session_activity().clone(),
+            watches: Arc::new(Mutex::new(HashMap::new())),
+            reconciles: Arc::new(Mutex::new(HashMap::new())),
+        };
+        let mut discovery = tokio::time::interval(DISCOVERY_INTERVAL);
 
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
-                    info!("[desktop:activity] Shutdown received, stopping SSE listener");
+                    info!("[desktop:activity] Shutdown received, stopping activity tracker");
                     break;
                 }
-                _ = async {
-                    // Reset stale phases to idle before connecting so UI doesn't stay stuck on "working" after wake.
-                    reset_and_emit_all_phases(&app, phases.clone(), cooldowns.clone()).await;
-
-                    if let Err(err) = run_once(&app, &runtime, &client, phases.clone(), cooldowns.clone()).await {
-                        warn!("[desktop:activity] SSE loop error: {err:?}");
+                _ = discovery.tick() => {
+                    tracker.discover_projects().await;
+                }
+                received = events.recv() => match received {
+                    Ok(event) => match event.as_ref() {
+                        // Ask OpenCode which sessions are actually busy so the UI neither stays stuck on
+                        // "working" after wake nor drops live work; sending Last-Event-ID is no guarantee
+                        // that anything missed gets replayed.
+                        HubEvent::Connected { directory } => {
+                            debug!("[desktop:activity] Event stream connected for {directory}");
+                            tracker.schedule_reconcile(directory).await;
+                        }
+                        HubEvent::Event(event) => {
+                            tracker.handle_event(event).await;
+                        }
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("[desktop:activity] Event hub lagged; dropped {skipped} events");
                     }
-                    tokio::time::sleep(Duration::from_secs(2)).await;
-                } => {}
+                    Err(RecvError::Closed) => break,
+                }
             }
         }
-    })
-}
 
-async fn run_once(
-    app: &AppHandle,
-    runtime: &DesktopRuntime,
-    client: &Client,
-    phases: Arc<Mutex<HashMap<String, ActivityPhase>>>,
-    cooldowns: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
-) -> Result<()> {
-    let opencode = runtime.opencode_manager();
-
-    let port = match opencode.current_port() {
-        Some(port) => port,
-        None => {
-            warn!("[desktop:activity] OpenCode port unavailable; will retry");
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            return Ok(());
+        for (_, handle) in tracker.reconciles.lock().await.drain() {
+            handle.abort();
         }
-    };
-
-    let prefix = opencode.api_prefix();
-    let base = format!("http://127.0.0.1:{port}{prefix}");
-    let (response, scope) = connect_activity_sse(runtime, client, &base).await?;
-
-    use tokio::io::AsyncBufReadExt;
-
-    let stream = response
-        .bytes_stream()
-        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err));
-    let mut reader = StreamReader::new(stream);
-    let mut buf = Vec::new();
-    let mut data_lines: Vec<String> = Vec::new();
+        tracker.watches.lock().await.clear();
+    })
+}
 
-    loop {
-        buf.clear();
-        let bytes_read = match tokio::time::timeout(Duration::from_secs(2), reader.read_until(b'\n', &mut buf)).await
-        {
-            Ok(Ok(n)) => n,
-            Ok(Err(err)) => {
-                warn!("[desktop:activity] Read error in SSE stream: {err:?}");
-                return Err(err.into());
+impl ActivityTracker {
+    async fn handle_event(&self, event: &OpenCodeEvent) {
+        let directory = event.directory.as_str();
+        match event.event_type.as_str() {
+            "session.status" => {
+                let session_id = event
+                    .properties
+                    .get("sessionID")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string());
+                let status = event
+                    .properties
+                    .get("status")
+                    .and_then(|s| s.get("type"))
+                    .and_then(Value::as_str);
+
+                if let (Some(id), Some(status_type)) = (session_id, status) {
+                    self.set_phase(directory, &id, phase_for_status(status_type))
+                        .await;
+                }
             }
-            Err(_) => {
-                // No data received recently; if we are connected to a directory-scoped stream and the working directory
-                // has changed, reconnect so activity tracking follows the new directory.
-                if let SseScope::Directory(connected_dir) = &scope {
-                    let current_dir = opencode.get_working_directory();
-                    if current_dir != *connected_dir {
-                        debug!(
-                            "[desktop:activity] Working directory changed; reconnecting activity SSE (from {:?} to {:?})",
-                            connected_dir, current_dir
-                        );
-                        return Ok(());
-                    }
+            "session.deleted" => {
+                let session_id = event
+                    .properties
+                    .get("info")
+                    .and_then(|info| info.get("id"))
+                    .and_then(Value::as_str);
+                if let Some(id) = session_id {
+                    self.forget_session(directory, id).await;
                 }
-                continue;
             }
-        };
-        if bytes_read == 0 {
-            break;
-        }
-
-        let line = match std::str::from_utf8(&buf) {
-            Ok(s) => s.trim_end_matches(&['\r', '\n'][..]).to_string(),
-            Err(err) => {
-                warn!("[desktop:activity] Non-UTF8 SSE chunk: {err}");
-                continue;
+            "session.idle" => {
+                let session_id = event
+                    .properties
+                    .get("sessionID")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string());
+                if let Some(id) = session_id {
+                    self.set_phase(directory, &id, ActivityPhase::Idle).await;
+                }
             }
-        };
+            "message.updated" => {
+                if let Some(info) = event.properties.get("info") {
+                    let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
+                    if role != "assistant" {
+                        return;
+                    }
 
-        if line.is_empty() {
-            if data_lines.is_empty() {
-                continue;
+                    let finish = info.get("finish").and_then(Value::as_str);
+                    if finish != Some("stop") {
+                        return;
+                    }
+
+                    let session_id = info
+                        .get("sessionID")
+                        .and_then(Value::as_str)
+                        .map(|s| s.to_string());
+
+                    if let Some(id) = session_id {
+                        self.enter_cooldown_if_busy(directory, &id).await;
+                    }
+                }
             }
-            let raw = data_lines.join("\n");
-            data_lines.clear();
-
-            match parse_event_envelope(&raw) {
-                Ok((event, _directory)) => handle_event(app, event, phases.clone(), cooldowns.clone()).await,
-                Err(err) => warn!("[desktop:activity] Failed to parse SSE data: {err}; raw={raw}"),
-            };
-            continue;
-        }
+            "message.part.updated" => {
+                let Some(info) = event.properties.get("info") else {
+                    return;
+                };
 
-        if let Some(rest) = line.strip_prefix("data:") {
-            data_lines.push(rest.trim_start().to_string());
-        }
-    }
+                let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
+                if role != "assistant" {
+                    return;
+                }
 
-    Ok(())
-}
+                let session_id = info
+                    .get("sessionID")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string());
 
-fn parse_event_envelope(raw: &str) -> Result<(EventEnvelope, Option<String>)> {
-    if let Ok(event) = serde_json::from_str::<EventEnvelope>(raw) {
-        return Ok((event, None));
-    }
+                let Some(id) = session_id else {
+                    return;
+                };
 
-    let multiplexed = serde_json::from_str::<MultiplexedEventEnvelope>(raw)?;
-    Ok((multiplexed.payload, multiplexed.directory))
-}
+                // Mark session busy when we see assistant parts streaming (covers cases where session.status is missing).
+                if is_streaming_assistant_part(&event.properties) {
+                    self.set_phase(directory, &id, ActivityPhase::Busy).await;
+                }
 
-async fn connect_activity_sse(
-    runtime: &DesktopRuntime,
-    client: &Client,
-    base: &str,
-) -> Result<(reqwest::Response, SseScope)> {
-    let opencode = runtime.opencode_manager();
-
-    let global_url = format!("{base}/global/event");
-    match try_connect_sse(client, &global_url, "[desktop:activity]").await {
-        Ok(response) => {
-            debug!("[desktop:activity] Using SSE endpoint: {global_url}");
-            return Ok((response, SseScope::Global));
-        }
-        Err(err) => {
-            debug!(
-                "[desktop:activity] SSE endpoint unavailable: {global_url} ({err:?}); falling back"
-            );
+                // Derive cooldown from info.finish === 'stop' when present.
+                if has_finish_stop(info) {
+                    self.enter_cooldown_if_busy(directory, &id).await;
+                }
+            }
+            _ => {}
         }
     }
 
-    let event_url = format!("{base}/event");
-    match try_connect_sse(client, &event_url, "[desktop:activity]").await {
-        Ok(response) => {
-            debug!("[desktop:activity] Using SSE endpoint: {event_url}");
-            return Ok((response, SseScope::Global));
-        }
-        Err(err) => {
-            debug!(
-                "[desktop:activity] SSE endpoint unavailable: {event_url} ({err:?}); falling back"
-            );
+    async fn enter_cooldown_if_busy(&self, directory: &str, session_id: &str) {
+        let key = (directory.to_string(), session_id.to_string());
+        if self.store.phase(&key).await != Some(ActivityPhase::Busy) {
+            return;
         }
-    }
 
-    let working_dir = opencode.get_working_directory();
-    let directory = working_dir.to_string_lossy().to_string();
-    let mut parsed = reqwest::Url::parse(&event_url)?;
-    parsed.query_pairs_mut().append_pair("directory", &directory);
-    let directory_url = parsed.to_string();
+        self.set_phase(directory, session_id, ActivityPhase::Cooldown)
+            .await;
 
-    let response = try_connect_sse(client, &directory_url, "[desktop:activity]").await?;
-    debug!("[desktop:activity] Using directory-scoped SSE endpoint: {directory_url}");
-    Ok((response, SseScope::Directory(working_dir)))
-}
+        let tracker = self.clone();
+        let cooldown_key = key.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            if tracker.store.phase(&cooldown_key).await == Some(ActivityPhase::Cooldown) {
+                let (directory, session_id) = &cooldown_key;
+                tracker
+                    .set_phase(directory, session_id, ActivityPhase::Idle)
+                    .await;
+            }
+        });
 
-async fn try_connect_sse(client: &Client, url: &str, log_prefix: &str) -> Result<reqwest::Response> {
-    debug!("{log_prefix} Connecting SSE: {url}");
+        let mut cd = self.store.cooldowns.lock().await;
+        if let Some(prev) = cd.remove(&key) {
+            prev.abort();
+        }
+        cd.insert(key, handle);
+    }
 
-    let response = client
-        .get(url)
-        .header("accept", "text/event-stream")
-        .header("accept-encoding", "identity")
-        .send()
-        .await?;
+    async fn set_phase(&self, directory: &str, session_id: &str, phase: ActivityPhase) {
+        let key = (directory.to_string(), session_id.to_string());
+        let changed_at = {
+            let mut map = self.store.sessions.lock().await;
+            if map.get(&key).map(|a| a.phase) == Some(phase) {
+                return;
+            }
+            let activity = map.entry(key.clone()).or_insert_with(SessionActivity::new);
+            activity.transition(phase, Utc::now());
 
-    debug!(
-        "{log_prefix} SSE response status={} headers={:?}",
-        response.status(),
-        response.headers()
-    );
+            // Cancel cooldown timer when leaving cooldown
+            if phase != ActivityPhase::Cooldown {
+                if let Some(handle) = self.store.cooldowns.lock().await.remove(&key) {
+                    handle.abort();
+                }
+            }
+            activity.changed_at
+        };
 
-    if !response.status().is_success() {
-        anyhow::bail!("SSE connect failed with status {}", response.status());
+        self.emit_phase(directory, session_id, phase, changed_at);
+        self.update_watch(directory).await;
     }
 
-    Ok(response)
-}
+    fn emit_phase(
+        &self,
+        directory: &str,
+        session_id: &str,
+        phase: ActivityPhase,
+        changed_at: DateTime<Utc>,
+    ) {
+        // Emit to webview so UI stays in sync
+        let payload = serde_json::json!({
+            "sessionId": session_id,
+            "directory": directory,
+            "phase": phase.as_str(),
+            "changedAt": changed_at,
+        });
 
-async fn handle_event(
-    app: &AppHandle,
-    event: EventEnvelope,
-    phases: Arc<Mutex<HashMap<String, ActivityPhase>>>,
-    cooldowns: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
-) {
-    match event.event_type.as_str() {
-        "session.status" => {
-            let session_id = event
-                .properties
-                .get("sessionID")
-                .and_then(Value::as_str)
-                .map(|s| s.to_string());
-            let status = event
-                .properties
-                .get("status")
-                .and_then(|s| s.get("type"))
-                .and_then(Value::as_str);
-
-            if let (Some(id), Some(status_type)) = (session_id, status) {
-                let phase = if status_type == "busy" || status_type == "retry" {
-                    ActivityPhase::Busy
-                } else {
-                    ActivityPhase::Idle
-                };
-                set_phase(app, &id, phase, phases.clone(), cooldowns.clone()).await;
+        let _ = self.app.emit("openchamber:session-activity", payload);
+        tray::refresh(&self.app);
+    }
+
+    /// Hold the directory's upstream open exactly while it has sessions that are not idle
+    async fn update_watch(&self, directory: &str) {
+        let live = self.store.has_live_sessions(directory).await;
+        let mut watches = self.watches.lock().await;
+        if live && !watches.contains_key(directory) {
+            debug!("[desktop:activity] Watching {directory} while sessions are active");
+            watches.insert(directory.to_string(), self.hub.watch(directory));
+        } else if !live {
+            if watches.remove(directory).is_some() {
+                debug!("[desktop:activity] No active sessions left in {directory}");
             }
         }
-        "session.idle" => {
-            let session_id = event
-                .properties
-                .get("sessionID")
-                .and_then(Value::as_str)
-                .map(|s| s.to_string());
-            if let Some(id) = session_id {
-                set_phase(app, &id, ActivityPhase::Idle, phases.clone(), cooldowns.clone()).await;
+    }
+
+    /// Reconcile `directory` in the background, retrying with backoff while OpenCode cannot answer
+    async fn schedule_reconcile(&self, directory: &str) {
+        let tracker = self.clone();
+        let dir = directory.to_string();
+        let handle = tauri::async_runtime::spawn(async move {
+            let mut backoff = ReconnectBackoff::default();
+            for attempt in 1..=RECONCILE_ATTEMPTS {
+                match tracker.reconcile_directory(&dir).await {
+                    Ok(()) => return,
+                    Err(err) if attempt == RECONCILE_ATTEMPTS => {
+                        warn!("[desktop:activity] Giving up reconciling {dir} until it reconnects: {err}");
+                    }
+                    Err(err) => {
+                        debug!("[desktop:activity] Reconcile of {dir} failed (attempt {attempt}): {err}");
+                        tokio::time::sleep(backoff.next_delay()).await;
+                    }
+                }
             }
+        });
+
+        if let Some(previous) = self
+            .reconciles
+            .lock()
+            .await
+            .insert(directory.to_string(), handle)
+        {
+            previous.abort();
         }
-        "message.updated" => {
-            if let Some(info) = event.properties.get("info") {
-                let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
-                if role != "assistant" {
-                    return;
-                }
+    }
 
-                let finish = info.get("finish").and_then(Value::as_str);
-                if finish != Some("stop") {
-                    return;
-                }
+    /// Bring every session tracked for `directory` in line with OpenCode's own status map and
+    /// drop sessions it no longer lists; on error the current phases are left alone
+    async fn reconcile_directory(&self, directory: &str) -> Result<()> {
+        let statuses = self.fetch_session_statuses(directory).await?;
+        let existing = self.fetch_session_ids(directory).await?;
+        self.apply_statuses(directory, statuses).await;
+
+        let deleted: Vec<String> = {
+            let sessions = self.store.sessions.lock().await;
+            sessions
+                .keys()
+                .filter(|(dir, id)| dir == directory && !existing.contains(id))
+                .map(|(_, id)| id.clone())
+                .collect()
+        };
+        for session_id in deleted {
+            self.forget_session(directory, &session_id).await;
+        }
+        Ok(())
+    }
 
-                let session_id = info
-                    .get("sessionID")
-                    .and_then(Value::as_str)
-                    .map(|s| s.to_string());
+    /// Stop tracking a session OpenCode deleted, reporting it idle first if it was not
+    async fn forget_session(&self, directory: &str, session_id: &str) {
+        let key = (directory.to_string(), session_id.to_string());
+        if self.store.phase(&key).await.is_none() {
+            return;
+        }
+        self.set_phase(directory, session_id, ActivityPhase::Idle)
+            .await;
+        self.store.sessions.lock().await.remove(&key);
+        if let Some(handle) = self.store.cooldowns.lock().await.remove(&key) {
+            handle.abort();
+        }
+    }
 
-                if let Some(id) = session_id {
-                    enter_cooldown_if_busy(app, &id, phases.clone(), cooldowns.clone()).await;
-                }
+    async fn apply_statuses(&self, directory: &str, statuses: HashMap<String, ActivityPhase>) {
+        let known: Vec<String> = {
+            let sessions = self.store.sessions.lock().await;
+            sessions
+                .keys()
+                .filter(|(dir, _)| dir == directory)
+                .map(|(_, id)| id.clone())
+                .collect()
+        };
+
+        for session_id in known.iter() {
+            let phase = statuses
+                .get(session_id)
+                .copied()
+                .unwrap_or(ActivityPhase::Idle);
+            let key = (directory.to_string(), session_id.clone());
+            // A session still cooling down is idle as far as OpenCode is concerned; leave the timer to finish it
+            if phase == ActivityPhase::Idle
+                && self.store.phase(&key).await == Some(ActivityPhase::Cooldown)
+            {
+                continue;
             }
+            self.set_phase(directory, session_id, phase).await;
         }
-        "message.part.updated" => {
-            let Some(info) = event.properties.get("info") else {
-                return;
-            };
 
-            let role = info.get("role").and_then(Value::as_str).unwrap_or_default();
-            if role != "assistant" {
-                return;
+        for (session_id, phase) in statuses {
+            if !known.contains(&session_id) && phase == ActivityPhase::Busy {
+                self.set_phase(directory, &session_id, phase).await;
             }
+        }
+    }
 
-            let session_id = info
-                .get("sessionID")
-                .and_then(Value::as_str)
-                .map(|s| s.to_string());
-
-            let Some(id) = session_id else {
+    /// Pick up sessions running in projects nobody is currently streaming
+    async fn discover_projects(&self) {
+        let projects = match self.fetch_projects().await {
+            Ok(projects) => projects,
+            Err(err) => {
+                debug!("[desktop:activity] Project discovery skipped: {err}");
                 return;
-            };
-
-            // Mark session busy when we see assistant parts streaming (covers cases where session.status is missing).
-            if is_streaming_assistant_part(&event.properties) {
-                set_phase(app, &id, ActivityPhase::Busy, phases.clone(), cooldowns.clone()).await;
             }
+        };
 
-            // Derive cooldown from info.finish === 'stop' when present.
-            if has_finish_stop(info) {
-                enter_cooldown_if_busy(app, &id, phases.clone(), cooldowns.clone()).await;
+        for directory in projects {
+            // Watched directories are kept current by their own stream
+            if self.watches.lock().await.contains_key(&directory) {
+                continue;
+            }
+            match self.fetch_session_statuses(&directory).await {
+                Ok(statuses) => {
+                    if statuses.values().any(|phase| *phase == ActivityPhase::Busy) {
+                        self.apply_statuses(&directory, statuses).await;
+                    }
+                }
+                Err(err) => {
+                    debug!(
+                        "[desktop:activity] Failed to fetch session status for {directory}: {err}"
+                    )
+                }
             }
         }
-        _ => {}
     }
-}
-
-fn is_streaming_assistant_part(properties: &Value) -> bool {
-    let Some(part) = properties.get("part") else {
-        return false;
-    };
-    let part_type = part.get("type").and_then(Value::as_str).unwrap_or_default();
-    matches!(
-        part_type,
-        "step-start" | "text" | "tool" | "reasoning" | "file" | "patch"
-    )
-}
-
-fn has_finish_stop(info: &Value) -> bool {
-    info.get("finish").and_then(Value::as_str) == Some("stop")
-}
 
-async fn enter_cooldown_if_busy(
-    app: &AppHandle,
-    session_id: &str,
-    phases: Arc<Mutex<HashMap<String, ActivityPhase>>>,
-    cooldowns: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
-) {
-    let current = { phases.lock().await.get(session_id).cloned() };
-    if !matches!(current, Some(ActivityPhase::Busy)) {
-        return;
+    fn base_url(&self) -> Result<String> {
+        let port = self
+            .opencode
+            .current_port()
+            .ok_or_else(|| anyhow::anyhow!("OpenCode port unavailable"))?;
+        Ok(format!(
+            "http://127.0.0.1:{port}{}",
+            self.opencode.api_prefix()
+        ))
     }
 
-    set_phase(
-        app,
-        session_id,
-        ActivityPhase::Cooldown,
-        phases.clone(),
-        cooldowns.clone(),
-    )
-    .await;
-
-    let app_clone = app.clone();
-    let phases_clone = phases.clone();
-    let cooldowns_clone = cooldowns.clone();
-    let id_clone = session_id.to_string();
-    let handle = tauri::async_runtime::spawn(async move {
-        tokio::time::sleep(Duration::from_secs(2)).await;
-        let current = { phases_clone.lock().await.get(&id_clone).cloned() };
-        if matches!(current, Some(ActivityPhase::Cooldown)) {
-            set_phase(
-                &app_clone,
-                &id_clone,
-                ActivityPhase::Idle,
-                phases_clone,
-                cooldowns_clone,
-            )
-            .await;
+    /// `GET /project` lists every project OpenCode knows; the global pseudo-project uses `/`
+    async fn fetch_projects(&self) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(format!("{}/project", self.base_url()?))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("project list request failed with {}", response.status());
         }
-    });
-
-    let mut cd = cooldowns.lock().await;
-    if let Some(prev) = cd.remove(session_id) {
-        prev.abort();
+        let projects: Vec<ProjectInfo> = response.json().await?;
+        Ok(projects
+            .into_iter()
+            .map(|project| project.worktree)
+            .filter(|worktree| !worktree.is_empty() && worktree != "/")
+            .collect())
     }
-    cd.insert(session_id.to_string(), handle);
-}
 
-async fn set_phase(
-    app: &AppHandle,
-    session_id: &str,
-    phase: ActivityPhase,
-    phases: Arc<Mutex<HashMap<String, ActivityPhase>>>,
-    cooldowns: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
-) {
-    {
-        let mut map = phases.lock().await;
-        let current = map.get(session_id);
-        if current == Some(&phase) {
-            return;
-        }
-        map.insert(session_id.to_string(), phase.clone());
+    /// `GET /session` lists every session of the directory's project, idle ones included
+    async fn fetch_session_ids(&self, directory: &str) -> Result<HashSet<String>> {
+        let mut url = reqwest::Url::parse(&format!("{}/session", self.base_url()?))?;
+        url.query_pairs_mut().append_pair("directory", directory);
 
-        // Cancel cooldown timer when leaving cooldown
-        if !matches!(phase, ActivityPhase::Cooldown) {
-            if let Some(handle) = cooldowns.lock().await.remove(session_id) {
-                handle.abort();
-            }
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("session list request failed with {}", response.status());
         }
+        let sessions: Vec<SessionInfo> = response.json().await?;
+        Ok(sessions.into_iter().map(|session| session.id).collect())
     }
 
-    // Emit to webview so UI stays in sync
-    let payload = serde_json::json!({
-        "sessionId": session_id,
-        "phase": match phase {
-            ActivityPhase::Idle => "idle",
-            ActivityPhase::Busy => "busy",
-            ActivityPhase::Cooldown => "cooldown",
+    /// `GET /session/status` returns `{ [sessionID]: { type: "idle" | "busy" | "retry" } }`
+    async fn fetch_session_statuses(
+        &self,
+        directory: &str,
+    ) -> Result<HashMap<String, ActivityPhase>> {
+        let mut url = reqwest::Url::parse(&format!("{}/session/status", self.base_url()?))?;
+        url.query_pairs_mut().append_pair("directory", directory);
+
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("session status request failed with {}", response.status());
         }
-    });
 
-    let _ = app.emit("openchamber:session-activity", payload);
+        let body: HashMap<String, Value> = response.json().await?;
+        Ok(body
+            .into_iter()
+            .filter_map(|(id, status)| {
+                let status_type = status.get("type").and_then(Value::as_str)?;
+                Some((id, phase_for_status(status_type)))
+            })
+            .collect())
+    }
 }
 
-async fn reset_and_emit_all_phases(
-    app: &AppHandle,
-    phases: Arc<Mutex<HashMap<String, ActivityPhase>>>,
-    cooldowns: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
-) {
-    // Cancel any cooldown timers and set all phases to idle to avoid stale "busy" after wake.
-    {
-        let mut cd = cooldowns.lock().await;
-        for handle in cd.values() {
-            handle.abort();
-        }
-        cd.clear();
+fn phase_for_status(status_type: &str) -> ActivityPhase {
+    if status_type == "busy" || status_type == "retry" {
+        ActivityPhase::Busy
+    } else {
+        ActivityPhase::Idle
     }
+}
 
-    let snapshot = {
-        let mut guard = phases.lock().await;
-        for value in guard.values_mut() {
-            *value = ActivityPhase::Idle;
-        }
-        guard.clone()
+fn is_streaming_assistant_part(properties: &Value) -> bool {
+    let Some(part) = properties.get("part") else {
+        return false;
     };
+    let part_type = part.get("type").and_then(Value::as_str).unwrap_or_default();
+    matches!(
+        part_type,
+        "step-start" | "text" | "tool" | "reasoning" | "file" | "patch"
+    )
+}
 
-    if snapshot.is_empty() {
-        return;
-    }
-
-    for (session_id, phase) in snapshot {
-        let payload = serde_json::json!({
-            "sessionId": session_id,
-            "phase": match phase {
-                ActivityPhase::Idle => "idle",
-                ActivityPhase::Busy => "busy",
-                ActivityPhase::Cooldown => "cooldown",
-            }
-        });
-        let _ = app.emit("openchamber:session-activity", payload);
-    }
+fn has_finish_stop(info: &Value) -> bool {
+    info.get("finish").and_then(Value::as_str) == Some("stop")
 }
@@ -0,0 +1,346 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+
+const INTEGRATION_DIR: &str = "shell-integration";
+/// Longest OSC payload we buffer; anything longer is not one of ours
+const MAX_OSC_LEN: usize = 4096;
+
+const BASH_SCRIPT: &str = r#"# OpenChamber shell integration for bash, sourced once from PROMPT_COMMAND
+if [[ -z "$__openchamber_installed" ]]; then
+    __openchamber_installed=1
+    __openchamber_bootstrap='. "$OPENCHAMBER_BASH_INTEGRATION"'
+    PROMPT_COMMAND=${PROMPT_COMMAND//"$__openchamber_bootstrap"/:}
+    # Save the status before anything else in PROMPT_COMMAND runs, report after it all ran
+    PROMPT_COMMAND="__openchamber_status=\$?; ${PROMPT_COMMAND:-:}; __openchamber_prompt"
+
+    __openchamber_prompt() {
+        if [[ -n "$__openchamber_running" ]]; then
+            printf '\e]133;D;%s\a' "$__openchamber_status"
+        fi
+        __openchamber_running=
+        local dir=${PWD//\%/%25}
+        printf '\e]7;file://%s%s\a' "$HOSTNAME" "${dir// /%20}"
+        printf '\e]133;A\a'
+        __openchamber_at_prompt=1
+    }
+
+    __openchamber_preexec() {
+        [[ -n "$__openchamber_at_prompt" ]] || return 0
+        __openchamber_at_prompt=
+        # An empty command line goes straight back to PROMPT_COMMAND
+        [[ "$BASH_COMMAND" == __openchamber_status=* ]] && return 0
+        __openchamber_running=1
+        printf '\e]133;C\a'
+    }
+
+    # Leave an existing DEBUG trap alone; cwd reporting still works without it
+    if [[ -z "$(trap -p DEBUG)" ]]; then
+        trap '__openchamber_preexec' DEBUG
+    fi
+
+    __openchamber_prompt
+fi
+"#;
+
+const ZSH_ENV: &str = r#"# OpenChamber shell integration: load the user's .zshenv from their own ZDOTDIR
+if [[ -f "${OPENCHAMBER_USER_ZDOTDIR:-$HOME}/.zshenv" ]]; then
+    __openchamber_zdotdir=$ZDOTDIR
+    ZDOTDIR=${OPENCHAMBER_USER_ZDOTDIR:-$HOME}
+    . "$ZDOTDIR/.zshenv"
+    ZDOTDIR=$__openchamber_zdotdir
+fi
+"#;
+
+const ZSH_PROFILE: &str = r#"if [[ -f "${OPENCHAMBER_USER_ZDOTDIR:-$HOME}/.zprofile" ]]; then
+    __openchamber_zdotdir=$ZDOTDIR
+    ZDOTDIR=${OPENCHAMBER_USER_ZDOTDIR:-$HOME}
+    . "$ZDOTDIR/.zprofile"
+    ZDOTDIR=$__openchamber_zdotdir
+fi
+"#;
+
+const ZSH_RC: &str = r#"if [[ -f "${OPENCHAMBER_USER_ZDOTDIR:-$HOME}/.zshrc" ]]; then
+    ZDOTDIR=${OPENCHAMBER_USER_ZDOTDIR:-$HOME}
+    . "$ZDOTDIR/.zshrc"
+fi
+
+__openchamber_precmd() {
+    local code=$?
+    if [[ -n "$__openchamber_running" ]]; then
+        printf '\e]133;D;%s\a' "$code"
+    fi
+    __openchamber_running=
+    local dir=${PWD//\%/%25}
+    printf '\e]7;file://%s%s\a' "$HOST" "${dir// /%20}"
+    printf '\e]133;A\a'
+}
+
+__openchamber_preexec() {
+    __openchamber_running=1
+    printf '\e]133;C\a'
+}
+
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __openchamber_precmd
+add-zsh-hook preexec __openchamber_preexec
+
+# Hand ZDOTDIR back so .zlogin and nested shells see the user's setup
+if [[ -n "$OPENCHAMBER_USER_ZDOTDIR" ]]; then
+    ZDOTDIR=$OPENCHAMBER_USER_ZDOTDIR
+else
+    unset ZDOTDIR
+fi
+unset OPENCHAMBER_USER_ZDOTDIR
+"#;
+
+const FISH_SCRIPT: &str = r#"# OpenChamber shell integration for fish, loaded from vendor_conf.d
+if status is-interactive; and not set -q __openchamber_installed
+    set -g __openchamber_installed 1
+
+    function __openchamber_preexec --on-event fish_preexec
+        printf '\e]133;C\a'
+    end
+
+    function __openchamber_postexec --on-event fish_postexec
+        printf '\e]133;D;%s\a' $status
+    end
+
+    function __openchamber_prompt --on-event fish_prompt
+        set -l dir (string replace -a '%' '%25' -- $PWD | string replace -a ' ' '%20')
+        printf '\e]7;file://%s%s\a' $hostname $dir
+        printf '\e]133;A\a'
+    end
+end
+"#;
+
+/// Something the shell reported about itself through OSC 7 or OSC 133
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellMark {
+    Cwd(PathBuf),
+    Prompt,
+    CommandStart,
+    CommandFinish(Option<i32>),
+}
+
+/// Picks OSC 7 and OSC 133 sequences out of a PTY stream, across chunk boundaries
+#[derive(Default)]
+pub struct OscScanner {
+    payload: Vec<u8>,
+    in_osc: bool,
+    after_esc: bool,
+}
+
+impl OscScanner {
+    pub fn scan(&mut self, bytes: &[u8]) -> Vec<ShellMark> {
+        let mut marks = Vec::new();
+        for &byte in bytes {
+            if self.in_osc {
+                match byte {
+                    // BEL or the ESC of an ESC \ terminator ends the sequence
+                    0x07 | 0x1b => {
+                        self.in_osc = false;
+                        self.after_esc = byte == 0x1b;
+                        marks.extend(parse_osc(&self.payload));
+                        self.payload.clear();
+                    }
+                    _ if self.payload.len() >= MAX_OSC_LEN => {
+                        self.in_osc = false;
+                        self.payload.clear();
+                    }
+                    _ => self.payload.push(byte),
+                }
+            } else if self.after_esc && byte == b']' {
+                self.in_osc = true;
+                self.after_esc = false;
+            } else {
+                self.after_esc = byte == 0x1b;
+            }
+        }
+        marks
+    }
+}
+
+fn parse_osc(payload: &[u8]) -> Option<ShellMark> {
+    let text = std::str::from_utf8(payload).ok()?;
+    let (code, rest) = text.split_once(';')?;
+    match code {
+        "7" => {
+            let location = rest.strip_prefix("file://")?;
+            // Skip the host part; the path starts at the next slash
+            let path = &location[location.find('/')?..];
+            let decoded = urlencoding::decode(path)
+                .map(|path| path.into_owned())
+                .unwrap_or_else(|_| path.to_string());
+            Some(ShellMark::Cwd(PathBuf::from(decoded)))
+        }
+        "133" => {
+            let mut parts = rest.split(';');
+            match parts.next()? {
+                "A" => Some(ShellMark::Prompt),
+                "C" => Some(ShellMark::CommandStart),
+                "D" => Some(ShellMark::CommandFinish(
+                    parts.next().and_then(|code| code.parse().ok()),
+                )),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Variables that make the shell load our hooks; `env` is what the terminal already sets.
+/// Shells we have no integration for get nothing.
+pub fn environment(shell_path: &str, env: &[(String, String)]) -> Result<Vec<(String, String)>> {
+    let name = Path::new(shell_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(shell_path);
+    let lookup = |key: &str| {
+        env.iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value.clone())
+            .or_else(|| env::var(key).ok())
+            .filter(|value| !value.is_empty())
+    };
+    let dir = integration_dir()?;
+
+    let vars = match name {
+        "bash" => {
+            let script = dir.join("openchamber.bash");
+            write_if_changed(&script, BASH_SCRIPT)?;
+            vec![
+                (
+                    "OPENCHAMBER_BASH_INTEGRATION".to_string(),
+                    script.to_string_lossy().to_string(),
+                ),
+                (
+                    "PROMPT_COMMAND".to_string(),
+                    r#". "$OPENCHAMBER_BASH_INTEGRATION""#.to_string(),
+                ),
+            ]
+        }
+        "zsh" => {
+            let zdotdir = dir.join("zsh");
+            fs::create_dir_all(&zdotdir)?;
+            write_if_changed(&zdotdir.join(".zshenv"), ZSH_ENV)?;
+            write_if_changed(&zdotdir.join(".zprofile"), ZSH_PROFILE)?;
+            write_if_changed(&zdotdir.join(".zshrc"), ZSH_RC)?;
+            let mut vars = vec![("ZDOTDIR".to_string(), zdotdir.to_string_lossy().to_string())];
+            if let Some(user_zdotdir) = lookup("ZDOTDIR") {
+                vars.push(("OPENCHAMBER_USER_ZDOTDIR".to_string(), user_zdotdir));
+            }
+            vars
+        }
+        "fish" => {
+            let data_dir = dir.join("fish-data");
+            let conf_dir = data_dir.join("fish").join("vendor_conf.d");
+            fs::create_dir_all(&conf_dir)?;
+            write_if_changed(&conf_dir.join("openchamber.fish"), FISH_SCRIPT)?;
+            // Fish falls back to these when XDG_DATA_DIRS is unset; keep them reachable
+            let existing = lookup("XDG_DATA_DIRS")
+                .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+            vec![(
+                "XDG_DATA_DIRS".to_string(),
+                format!("{}:{existing}", data_dir.to_string_lossy()),
+            )]
+        }
+        _ => Vec::new(),
+    };
+    Ok(vars)
+}
+
+fn integration_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    let dir = home
+        .join(".config")
+        .join("openchamber")
+        .join(INTEGRATION_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn write_if_changed(path: &Path, contents: &str) -> Result<()> {
+    if fs::read_to_string(path).ok().as_deref() != Some(contents) {
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_joins_sequences_split_across_chunks() {
+        let mut scanner = OscScanner::default();
+        assert!(scanner.scan(b"output\x1b").is_empty());
+        assert!(scanner.scan(b"]133;").is_empty());
+        assert_eq!(scanner.scan(b"A\x07$ "), vec![ShellMark::Prompt]);
+    }
+
+    #[test]
+    fn scan_accepts_bel_and_st_terminators() {
+        let mut scanner = OscScanner::default();
+        assert_eq!(
+            scanner.scan(b"\x1b]133;C\x07ls\x1b]133;A\x1b\\"),
+            vec![ShellMark::CommandStart, ShellMark::Prompt]
+        );
+        // The backslash of the ST is not mistaken for anything else
+        assert_eq!(
+            scanner.scan(b"\x1b]133;C\x1b"),
+            vec![ShellMark::CommandStart]
+        );
+        assert_eq!(scanner.scan(b"\\\x1b]133;A\x07"), vec![ShellMark::Prompt]);
+    }
+
+    #[test]
+    fn scan_ignores_other_escape_sequences() {
+        let mut scanner = OscScanner::default();
+        assert!(scanner
+            .scan(b"\x1b[1;31mred\x1b[0m\x1b]0;title\x07")
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_osc_decodes_cwd_paths() {
+        assert_eq!(
+            parse_osc(b"7;file://host/Users/me/My%20Project/100%25"),
+            Some(ShellMark::Cwd(PathBuf::from("/Users/me/My Project/100%")))
+        );
+        assert_eq!(
+            parse_osc(b"7;file:///tmp"),
+            Some(ShellMark::Cwd(PathBuf::from("/tmp")))
+        );
+        assert_eq!(parse_osc(b"7;http://host/tmp"), None);
+    }
+
+    #[test]
+    fn parse_osc_reads_command_finish_exit_codes() {
+        assert_eq!(
+            parse_osc(b"133;D;0"),
+            Some(ShellMark::CommandFinish(Some(0)))
+        );
+        assert_eq!(
+            parse_osc(b"133;D;127"),
+            Some(ShellMark::CommandFinish(Some(127)))
+        );
+        assert_eq!(parse_osc(b"133;D"), Some(ShellMark::CommandFinish(None)));
+        assert_eq!(parse_osc(b"133;D;"), Some(ShellMark::CommandFinish(None)));
+        assert_eq!(parse_osc(b"133;B"), None);
+    }
+
+    #[test]
+    fn scan_drops_payloads_over_the_limit() {
+        let mut scanner = OscScanner::default();
+        let mut long = b"\x1b]7;file:///".to_vec();
+        long.extend(vec![b'a'; MAX_OSC_LEN]);
+        long.push(0x07);
+        assert!(scanner.scan(&long).is_empty());
+        // Scanning carries on normally afterwards
+        assert_eq!(scanner.scan(b"\x1b]133;A\x07"), vec![ShellMark::Prompt]);
+    }
+}
@@ -0,0 +1,296 @@
+use std::time::Duration;
+
+/// Reconnection delay used until the server sends a `retry:` hint
+const DEFAULT_RETRY: Duration = Duration::from_secs(2);
+const MIN_RETRY: Duration = Duration::from_millis(250);
+const MAX_RETRY: Duration = Duration::from_secs(60);
+
+/// One dispatched server-sent event
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseMessage {
+    /// `event:` field, `"message"` when absent
+    pub event: String,
+    pub data: String,
+    /// Last event id in effect when this event was dispatched
+    pub id: Option<String>,
+}
+
+/// Incremental `text/event-stream` parser following the WHATWG event stream interpretation rules
+#[derive(Debug, Default)]
+pub struct SseParser {
+    line: Vec<u8>,
+    after_cr: bool,
+    started: bool,
+    data: String,
+    event: String,
+    last_event_id: String,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    /// Seed the id after a reconnect so events without an `id:` keep the resumed id
+    pub fn with_last_event_id(last_event_id: Option<String>) -> Self {
+        Self {
+            last_event_id: last_event_id.unwrap_or_default(),
+            ..Self::default()
+        }
+    }
+
+    pub fn last_event_id(&self) -> Option<&str> {
+        (!self.last_event_id.is_empty()).then_some(self.last_event_id.as_str())
+    }
+
+    /// Most recent `retry:` value sent by the server
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Feed raw bytes; returns every event completed by this chunk
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        let mut messages = Vec::new();
+        let mut bytes = chunk;
+
+        if !self.started && !bytes.is_empty() {
+            self.started = true;
+            // A leading UTF-8 BOM is not part of the stream
+            bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        }
+
+        for &byte in bytes {
+            match byte {
+                b'\n' if self.after_cr => {
+                    // Second half of a CRLF pair; the line was already processed on CR
+                    self.after_cr = false;
+                }
+                b'\r' | b'\n' => {
+                    self.after_cr = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(message) = self.process_line(&line) {
+                        messages.push(message);
+                    }
+                }
+                _ => {
+                    self.after_cr = false;
+                    self.line.push(byte);
+                }
+            }
+        }
+
+        messages
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<SseMessage> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line[0] == b':' {
+            return None;
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.last_event_id = value.to_string(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(ms) = value.parse::<u64>() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseMessage> {
+        let event = std::mem::take(&mut self.event);
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        if data.ends_with('\n') {
+            data.pop();
+        }
+        Some(SseMessage {
+            event: if event.is_empty() {
+                "message".to_string()
+            } else {
+                event
+            },
+            data,
+            id: self.last_event_id().map(str::to_string),
+        })
+    }
+}
+
+/// Exponential reconnect delay with jitter, based on the server's `retry:` hint when given
+#[derive(Debug)]
+pub struct ReconnectBackoff {
+    base: Duration,
+    attempt: u32,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self {
+            base: DEFAULT_RETRY,
+            attempt: 0,
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    pub fn set_retry_hint(&mut self, retry: Duration) {
+        self.base = retry.clamp(MIN_RETRY, MAX_RETRY);
+    }
+
+    /// Call once a connection has delivered events so the next failure starts from the base delay
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Delay before the next attempt: base * 2^attempt capped at a minute, jittered to 50-100%
+    pub fn next_delay(&mut self) -> Duration {
+        let exponent = self.attempt.min(16);
+        self.attempt = self.attempt.saturating_add(1);
+        let delay = self.base.saturating_mul(1u32 << exponent).min(MAX_RETRY);
+        let millis = delay.as_millis() as u64;
+        Duration::from_millis(millis / 2 + fastrand::u64(0..=millis / 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(event: &str, data: &str, id: Option<&str>) -> SseMessage {
+        SseMessage {
+            event: event.to_string(),
+            data: data.to_string(),
+            id: id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn dispatches_on_blank_line_and_joins_data_lines() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"data: first\ndata:second\n").is_empty());
+        assert_eq!(
+            parser.feed(b"\n"),
+            vec![message("message", "first\nsecond", None)]
+        );
+    }
+
+    #[test]
+    fn handles_line_endings_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let mut messages = parser.feed(b"data: a\r");
+        messages.extend(parser.feed(b"\n\r"));
+        messages.extend(parser.feed(b"\ndata: b\r\r"));
+        messages.extend(parser.feed(b"data: c\n"));
+        messages.extend(parser.feed(b"\n"));
+        assert_eq!(
+            messages,
+            vec![
+                message("message", "a", None),
+                message("message", "b", None),
+                message("message", "c", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_bom_comments_and_unknown_fields() {
+        let mut parser = SseParser::default();
+        let messages = parser.feed(b"\xEF\xBB\xBF: keep-alive\nfoo: bar\ndata\n\n");
+        assert_eq!(messages, vec![message("message", "", None)]);
+        // Only the very first bytes of a stream may carry the BOM
+        assert!(parser.feed(b"\xEF\xBB\xBFdata: x\n\n").is_empty());
+    }
+
+    #[test]
+    fn event_name_resets_but_id_persists() {
+        let mut parser = SseParser::default();
+        let messages = parser.feed(b"event: custom\nid: 7\ndata: one\n\ndata: two\n\n");
+        assert_eq!(
+            messages,
+            vec![
+                message("custom", "one", Some("7")),
+                message("message", "two", Some("7")),
+            ]
+        );
+        // An empty id clears it, an id containing NUL is ignored
+        parser.feed(b"id: 8\0\n\n");
+        assert_eq!(parser.last_event_id(), Some("7"));
+        parser.feed(b"id\n\n");
+        assert_eq!(parser.last_event_id(), None);
+    }
+
+    #[test]
+    fn blocks_without_data_are_not_dispatched() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"event: ping\nid: 3\n\n").is_empty());
+        assert_eq!(parser.last_event_id(), Some("3"));
+        assert_eq!(
+            parser.feed(b"data: x\n\n"),
+            vec![message("message", "x", Some("3"))]
+        );
+    }
+
+    #[test]
+    fn seeded_id_survives_a_reconnect() {
+        let mut parser = SseParser::with_last_event_id(Some("42".to_string()));
+        assert_eq!(
+            parser.feed(b"data: x\n\n"),
+            vec![message("message", "x", Some("42"))]
+        );
+        assert_eq!(SseParser::with_last_event_id(None).last_event_id(), None);
+    }
+
+    #[test]
+    fn retry_requires_ascii_digits() {
+        let mut parser = SseParser::default();
+        parser.feed(b"retry: 1500\n");
+        assert_eq!(parser.retry(), Some(Duration::from_millis(1500)));
+        parser.feed(b"retry: 10s\nretry: -1\nretry:\n");
+        assert_eq!(parser.retry(), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn backoff_grows_with_jitter_and_resets() {
+        let mut backoff = ReconnectBackoff::default();
+        for attempt in 0..4 {
+            let full = DEFAULT_RETRY * (1 << attempt);
+            let delay = backoff.next_delay();
+            assert!(delay >= full / 2 && delay <= full, "{delay:?} for {full:?}");
+        }
+        backoff.reset();
+        assert!(backoff.next_delay() <= DEFAULT_RETRY);
+    }
+
+    #[test]
+    fn backoff_is_capped_and_clamps_hints() {
+        let mut backoff = ReconnectBackoff::default();
+        for _ in 0..40 {
+            assert!(backoff.next_delay() <= MAX_RETRY);
+        }
+
+        let mut backoff = ReconnectBackoff::default();
+        backoff.set_retry_hint(Duration::from_millis(1));
+        let delay = backoff.next_delay();
+        assert!(delay >= MIN_RETRY / 2 && delay <= MIN_RETRY);
+
+        backoff.set_retry_hint(Duration::from_secs(600));
+        backoff.reset();
+        assert!(backoff.next_delay() >= MAX_RETRY / 2);
+    }
+}
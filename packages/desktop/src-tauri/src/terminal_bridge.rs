@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::commands::terminal::TerminalState;
+use crate::server_auth;
+
+/// Terminals the user shared with agents, readable and writable by tools running under OpenCode
+pub const TERMINALS_ROUTE: &str = "/api/openchamber/terminals";
+/// Set on the OpenCode process so its tools and MCP servers can find the bridge
+pub const URL_ENV: &str = "OPENCHAMBER_TERMINALS_URL";
+pub const TOKEN_ENV: &str = "OPENCHAMBER_TERMINALS_TOKEN";
+
+const DEFAULT_OUTPUT_LINES: usize = 200;
+const MAX_OUTPUT_LINES: usize = 5000;
+
+/// Variables that point OpenCode's tools at the bridge on the loopback server
+pub fn environment(server_port: u16, token: &str) -> Vec<(String, String)> {
+    vec![
+        (
+            URL_ENV.to_string(),
+            format!("http://127.0.0.1:{server_port}{TERMINALS_ROUTE}"),
+        ),
+        (TOKEN_ENV.to_string(), token.to_string()),
+    ]
+}
+
+/// Bridge routes behind their own token, so agents get no access to the rest of the API
+pub fn router(terminals: TerminalState, token: Arc<str>) -> Router {
+    Router::new()
+        .route(TERMINALS_ROUTE, get(list_handler))
+        .route(
+            &format!("{TERMINALS_ROUTE}/{{session_id}}/output"),
+            get(output_handler),
+        )
+        .route(
+            &format!("{TERMINALS_ROUTE}/{{session_id}}/input"),
+            post(input_handler),
+        )
+        .with_state(terminals)
+        .layer(middleware::from_fn_with_state(
+            token,
+            server_auth::require_token,
+        ))
+}
+
+async fn list_handler(State(terminals): State<TerminalState>) -> Response {
+    Json(json!({ "terminals": terminals.shared_sessions() })).into_response()
+}
+
+#[derive(Deserialize)]
+struct OutputQuery {
+    /// Offset from an earlier read; only output after it is returned
+    since: Option<u64>,
+    lines: Option<usize>,
+}
+
+async fn output_handler(
+    State(terminals): State<TerminalState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<OutputQuery>,
+) -> Response {
+    let lines = query
+        .lines
+        .unwrap_or(DEFAULT_OUTPUT_LINES)
+        .min(MAX_OUTPUT_LINES);
+    match terminals.read_shared_output(&session_id, query.since, lines) {
+        Some(output) => Json(output).into_response(),
+        None => not_shared(),
+    }
+}
+
+#[derive(Deserialize)]
+struct InputRequest {
+    data: String,
+}
+
+async fn input_handler(
+    State(terminals): State<TerminalState>,
+    Path(session_id): Path<String>,
+    Json(request): Json<InputRequest>,
+) -> Response {
+    match terminals.write_shared_input(&session_id, &request.data) {
+        Some(Ok(())) => StatusCode::NO_CONTENT.into_response(),
+        Some(Err(err)) => (StatusCode::BAD_GATEWAY, Json(json!({ "error": err }))).into_response(),
+        None => not_shared(),
+    }
+}
+
+/// Unshared and unknown sessions look the same, so agents cannot probe for ids
+fn not_shared() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "Terminal not found or not shared" })),
+    )
+        .into_response()
+}
@@ -0,0 +1,618 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+        process::CommandExt,
+    },
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use portable_pty::{ChildKiller, MasterPty, NativePtySystem, PtySize, PtySystem};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::terminal_pty::{Scrollback, SpawnSpec};
+
+/// First argument that turns the app binary into the terminal session daemon
+pub const DAEMON_ARG: &str = "--terminal-daemon";
+const SOCKET_FILE: &str = "terminal-daemon.sock";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// The daemon exits once it has hosted no sessions for this long
+const IDLE_EXIT_AFTER: Duration = Duration::from_secs(60);
+/// A client that stops reading is dropped rather than stalling the shell
+const SUBSCRIBER_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long the exit thread waits for buffered output after the shell ends
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// One JSON line from client to daemon
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Request {
+    Create {
+        spec: SpawnSpec,
+    },
+    List,
+    /// Replies with the scrollback, then streams `DaemonEvent` lines on the same connection until it closes
+    #[serde(rename_all = "camelCase")]
+    Attach {
+        session_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Input {
+        session_id: String,
+        data: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Resize {
+        session_id: String,
+        cols: u16,
+        rows: u16,
+    },
+    #[serde(rename_all = "camelCase")]
+    Kill {
+        session_id: String,
+    },
+}
+
+/// One JSON line from daemon to client answering a `Request`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<Vec<DaemonSession>>,
+    /// Base64 of the raw scrollback bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollback: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+}
+
+impl Reply {
+    fn success() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    fn failure(err: anyhow::Error) -> Self {
+        Self {
+            ok: false,
+            error: Some(format!("{err:#}")),
+            ..Default::default()
+        }
+    }
+}
+
+/// A shell hosted by the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonSession {
+    pub session_id: String,
+    pub cwd: PathBuf,
+    pub shell: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub created_at: DateTime<Utc>,
+    /// Connections currently streaming this session's output
+    pub attached_clients: usize,
+    /// Shell process id; absent from daemons started by older versions
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+/// Streamed to attached clients after the attach reply
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum DaemonEvent {
+    /// `data` is base64 so output bytes arrive untouched
+    Data { data: String, offset: u64 },
+    #[serde(rename_all = "camelCase")]
+    Exit {
+        exit_code: i32,
+        signal: Option<String>,
+    },
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    Ok(home.join(".config").join("openchamber").join(SOCKET_FILE))
+}
+
+/// Talks to the daemon, starting it from the app binary when needed
+#[derive(Clone)]
+pub struct DaemonClient {
+    socket: PathBuf,
+}
+
+impl DaemonClient {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            socket: socket_path()?,
+        })
+    }
+
+    pub fn is_running(&self) -> bool {
+        UnixStream::connect(&self.socket).is_ok()
+    }
+
+    pub fn ensure_running(&self) -> Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+
+        let exe = std::env::current_exe().context("Failed to locate app binary")?;
+        let mut child = Command::new(exe)
+            .arg(DAEMON_ARG)
+            .arg(&self.socket)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // Own process group, so quitting the app does not take the shells with it
+            .process_group(0)
+            .spawn()
+            .context("Failed to start terminal daemon")?;
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while Instant::now() < deadline {
+            if self.is_running() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        Err(anyhow!("Terminal daemon did not start"))
+    }
+
+    /// Returns the new session's id and its shell's process id
+    pub fn create(&self, spec: SpawnSpec) -> Result<(String, Option<u32>)> {
+        let reply = self.request(&Request::Create { spec })?;
+        let session_id = reply
+            .session_id
+            .ok_or_else(|| anyhow!("Terminal daemon returned no session id"))?;
+        Ok((session_id, reply.pid))
+    }
+
+    pub fn list(&self) -> Result<Vec<DaemonSession>> {
+        Ok(self.request(&Request::List)?.sessions.unwrap_or_default())
+    }
+
+    pub fn input(&self, session_id: &str, data: &str) -> Result<()> {
+        self.request(&Request::Input {
+            session_id: session_id.to_string(),
+            data: data.to_string(),
+        })
+        .map(|_| ())
+    }
+
+    pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<()> {
+        self.request(&Request::Resize {
+            session_id: session_id.to_string(),
+            cols,
+            rows,
+        })
+        .map(|_| ())
+    }
+
+    pub fn kill(&self, session_id: &str) -> Result<()> {
+        self.request(&Request::Kill {
+            session_id: session_id.to_string(),
+        })
+        .map(|_| ())
+    }
+
+    /// Subscribe to a session; returns its scrollback, the offset it ends at and the event stream
+    pub fn attach(&self, session_id: &str) -> Result<(Vec<u8>, u64, DaemonEvents)> {
+        let (reply, reader) = self.exchange(&Request::Attach {
+            session_id: session_id.to_string(),
+        })?;
+        reader.get_ref().set_read_timeout(None)?;
+        let scrollback = STANDARD
+            .decode(reply.scrollback.unwrap_or_default())
+            .context("Invalid scrollback from terminal daemon")?;
+        Ok((
+            scrollback,
+            reply.offset.unwrap_or_default(),
+            DaemonEvents { reader },
+        ))
+    }
+
+    fn request(&self, request: &Request) -> Result<Reply> {
+        self.exchange(request).map(|(reply, _)| reply)
+    }
+
+    fn exchange(&self, request: &Request) -> Result<(Reply, BufReader<UnixStream>)> {
+        let mut stream =
+            UnixStream::connect(&self.socket).context("Terminal daemon is not running")?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        write_line(&mut stream, request)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("Terminal daemon closed the connection"));
+        }
+        let reply: Reply = serde_json::from_str(&line)?;
+        if !reply.ok {
+            return Err(anyhow!(reply
+                .error
+                .unwrap_or_else(|| "Terminal daemon request failed".to_string())));
+        }
+        Ok((reply, reader))
+    }
+}
+
+/// Output of an attached session; dropping it detaches
+pub struct DaemonEvents {
+    reader: BufReader<UnixStream>,
+}
+
+impl DaemonEvents {
+    /// Blocks for the next event; `None` once the daemon goes away
+    pub fn next_event(&mut self) -> Option<DaemonEvent> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {
+                    if let Ok(event) = serde_json::from_str(&line) {
+                        return Some(event);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct HostedOutput {
+    scrollback: Scrollback,
+    subscribers: Vec<(u64, UnixStream)>,
+}
+
+impl HostedOutput {
+    fn broadcast(&mut self, event: &DaemonEvent) {
+        let Ok(mut line) = serde_json::to_vec(event) else {
+            return;
+        };
+        line.push(b'\n');
+        self.subscribers
+            .retain_mut(|(_, stream)| stream.write_all(&line).is_ok());
+    }
+}
+
+struct HostedSession {
+    master: Box<dyn MasterPty + Send>,
+    /// Locked on its own so a blocked PTY write does not hold up other sessions
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    output: Arc<Mutex<HostedOutput>>,
+    pid: Option<u32>,
+    cwd: PathBuf,
+    shell: String,
+    cols: u16,
+    rows: u16,
+    created_at: DateTime<Utc>,
+}
+
+type HostedSessions = Arc<Mutex<HashMap<String, HostedSession>>>;
+
+/// Run as the daemon when started with `DAEMON_ARG`; returns the process exit code
+pub fn run_from_args() -> Option<i32> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some(DAEMON_ARG) {
+        return None;
+    }
+    let socket = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => match socket_path() {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("[terminal-daemon] {err:#}");
+                return Some(1);
+            }
+        },
+    };
+    Some(match serve(&socket) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("[terminal-daemon] {err:#}");
+            1
+        }
+    })
+}
+
+fn serve(socket: &Path) -> Result<()> {
+    if UnixStream::connect(socket).is_ok() {
+        return Err(anyhow!(
+            "A daemon is already listening on {}",
+            socket.display()
+        ));
+    }
+    let _ = std::fs::remove_file(socket);
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to bind {}", socket.display()))?;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+
+    let sessions = HostedSessions::default();
+    spawn_idle_monitor(sessions.clone(), socket.to_path_buf());
+
+    let next_client = AtomicU64::new(1);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let sessions = sessions.clone();
+        let client_id = next_client.fetch_add(1, Ordering::Relaxed);
+        thread::spawn(move || handle_client(stream, sessions, client_id));
+    }
+    Ok(())
+}
+
+fn spawn_idle_monitor(sessions: HostedSessions, socket: PathBuf) {
+    thread::spawn(move || {
+        let mut idle_since = Instant::now();
+        loop {
+            thread::sleep(Duration::from_secs(5));
+            if !sessions.lock().unwrap().is_empty() {
+                idle_since = Instant::now();
+            } else if idle_since.elapsed() >= IDLE_EXIT_AFTER {
+                let _ = std::fs::remove_file(&socket);
+                std::process::exit(0);
+            }
+        }
+    });
+}
+
+fn handle_client(stream: UnixStream, sessions: HostedSessions, client_id: u64) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut attached = Vec::new();
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Attach { session_id }) => {
+                match subscribe(&sessions, &session_id, &writer, client_id) {
+                    Ok(()) => {
+                        attached.push(session_id);
+                        continue;
+                    }
+                    Err(err) => Reply::failure(err),
+                }
+            }
+            Ok(request) => handle_request(&sessions, request).unwrap_or_else(Reply::failure),
+            Err(err) => Reply::failure(anyhow!("Invalid request: {err}")),
+        };
+        if write_line(&mut writer, &reply).is_err() {
+            break;
+        }
+    }
+
+    let sessions = sessions.lock().unwrap();
+    for session_id in attached {
+        if let Some(session) = sessions.get(&session_id) {
+            session
+                .output
+                .lock()
+                .unwrap()
+                .subscribers
+                .retain(|(id, _)| *id != client_id);
+        }
+    }
+}
+
+fn handle_request(sessions: &HostedSessions, request: Request) -> Result<Reply> {
+    match request {
+        Request::Create { spec } => {
+            let (session_id, pid) = spawn_hosted(sessions, spec)?;
+            Ok(Reply {
+                session_id: Some(session_id),
+                pid,
+                ..Reply::success()
+            })
+        }
+        Request::List => {
+            let sessions = sessions.lock().unwrap();
+            let list = sessions
+                .iter()
+                .map(|(session_id, session)| DaemonSession {
+                    session_id: session_id.clone(),
+                    cwd: session.cwd.clone(),
+                    shell: session.shell.clone(),
+                    cols: session.cols,
+                    rows: session.rows,
+                    created_at: session.created_at,
+                    attached_clients: session.output.lock().unwrap().subscribers.len(),
+                    pid: session.pid,
+                })
+                .collect();
+            Ok(Reply {
+                sessions: Some(list),
+                ..Reply::success()
+            })
+        }
+        Request::Input { session_id, data } => {
+            let writer = sessions
+                .lock()
+                .unwrap()
+                .get(&session_id)
+                .map(|session| session.writer.clone())
+                .ok_or_else(|| anyhow!("Terminal session not found"))?;
+            let mut writer = writer.lock().unwrap();
+            writer.write_all(data.as_bytes())?;
+            writer.flush()?;
+            Ok(Reply::success())
+        }
+        Request::Resize {
+            session_id,
+            cols,
+            rows,
+        } => {
+            let mut sessions = sessions.lock().unwrap();
+            let session = sessions
+                .get_mut(&session_id)
+                .ok_or_else(|| anyhow!("Terminal session not found"))?;
+            session.master.resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })?;
+            session.cols = cols;
+            session.rows = rows;
+            Ok(Reply::success())
+        }
+        Request::Kill { session_id } => {
+            let session = sessions.lock().unwrap().remove(&session_id);
+            if let Some(mut session) = session {
+                session.killer.kill()?;
+            }
+            Ok(Reply::success())
+        }
+        Request::Attach { .. } => Err(anyhow!("Attach is handled per connection")),
+    }
+}
+
+/// Send the attach reply and register the connection under one lock so no output falls in between
+fn subscribe(
+    sessions: &HostedSessions,
+    session_id: &str,
+    stream: &UnixStream,
+    client_id: u64,
+) -> Result<()> {
+    let output = sessions
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .map(|session| session.output.clone())
+        .ok_or_else(|| anyhow!("Terminal session not found"))?;
+    let mut output = output.lock().unwrap();
+
+    let mut subscriber = stream.try_clone()?;
+    subscriber.set_write_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT))?;
+    let reply = Reply {
+        scrollback: Some(STANDARD.encode(output.scrollback.bytes())),
+        offset: Some(output.scrollback.offset()),
+        ..Reply::success()
+    };
+    write_line(&mut subscriber, &reply)?;
+    output.subscribers.push((client_id, subscriber));
+    Ok(())
+}
+
+fn spawn_hosted(sessions: &HostedSessions, spec: SpawnSpec) -> Result<(String, Option<u32>)> {
+    let pair = NativePtySystem::default().openpty(spec.size())?;
+    let mut child = pair
+        .slave
+        .spawn_command(spec.command())
+        .context("Failed to spawn shell")?;
+    drop(pair.slave);
+
+    let mut reader = pair.master.try_clone_reader()?;
+    let writer = pair.master.take_writer()?;
+    let killer = child.clone_killer();
+    let pid = child.process_id();
+    let output = Arc::new(Mutex::new(HostedOutput {
+        scrollback: Scrollback::default(),
+        subscribers: Vec::new(),
+    }));
+
+    let session_id = Uuid::new_v4().to_string();
+    sessions.lock().unwrap().insert(
+        session_id.clone(),
+        HostedSession {
+            master: pair.master,
+            writer: Arc::new(Mutex::new(writer)),
+            killer,
+            output: output.clone(),
+            pid,
+            cwd: spec.cwd,
+            shell: spec.shell,
+            cols: spec.cols,
+            rows: spec.rows,
+            created_at: Utc::now(),
+        },
+    );
+
+    let reader_output = output.clone();
+    let (reader_done_tx, reader_done) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0u8; 16384];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let mut output = reader_output.lock().unwrap();
+                    output.scrollback.record(&buffer[..n]);
+                    let event = DaemonEvent::Data {
+                        data: STANDARD.encode(&buffer[..n]),
+                        offset: output.scrollback.offset(),
+                    };
+                    output.broadcast(&event);
+                }
+            }
+        }
+        let _ = reader_done_tx.send(());
+    });
+
+    let sessions = sessions.clone();
+    let exit_session_id = session_id.clone();
+    thread::spawn(move || {
+        let (exit_code, signal) = match child.wait() {
+            Ok(status) => (
+                status.exit_code() as i32,
+                status.signal().map(|sig| sig.to_string()),
+            ),
+            Err(_) => (1, Some("Terminal crashed".to_string())),
+        };
+        // Let the last output reach clients first; background jobs may hold the PTY open
+        let _ = reader_done.recv_timeout(OUTPUT_DRAIN_TIMEOUT);
+        output
+            .lock()
+            .unwrap()
+            .broadcast(&DaemonEvent::Exit { exit_code, signal });
+        sessions.lock().unwrap().remove(&exit_session_id);
+    });
+
+    Ok((session_id, pid))
+}
+
+fn write_line<T: Serialize>(stream: &mut UnixStream, value: &T) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    Ok(())
+}
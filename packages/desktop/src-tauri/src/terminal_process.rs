@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+
+/// A process running under a terminal's shell, listed in tree order
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub command: String,
+    /// Single-letter scheduler state such as `R`, `S`, `T` or `Z`
+    pub state: String,
+    /// Member of the process group that currently owns the terminal
+    pub foreground: bool,
+    /// 0 for the shell, 1 for its children and so on
+    pub depth: usize,
+}
+
+impl ProcessInfo {
+    /// A job the user would lose by closing the terminal
+    pub fn is_job(&self) -> bool {
+        self.depth > 0 && self.state != "Z"
+    }
+}
+
+/// Signals a user may send to a terminal's processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalSignal {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl TerminalSignal {
+    /// Accepts `SIGINT`, `int` and the like
+    pub fn parse(name: &str) -> Result<Self> {
+        let upper = name.trim().to_ascii_uppercase();
+        match upper.trim_start_matches("SIG") {
+            "INT" => Ok(Self::Interrupt),
+            "TERM" => Ok(Self::Terminate),
+            "KILL" => Ok(Self::Kill),
+            _ => bail!("Unsupported signal: {name}"),
+        }
+    }
+}
+
+/// The shell and everything it started, depth first
+pub fn process_tree(shell_pid: u32) -> Result<Vec<ProcessInfo>> {
+    build_tree(&snapshot()?, shell_pid)
+}
+
+fn build_tree(entries: &[Entry], shell_pid: u32) -> Result<Vec<ProcessInfo>> {
+    let shell = entries
+        .iter()
+        .find(|entry| entry.pid == shell_pid)
+        .ok_or_else(|| anyhow!("Shell process {shell_pid} is not running"))?;
+    // Every process on the PTY sees the same foreground group
+    let foreground_pgid = shell.tpgid;
+
+    let mut children: HashMap<u32, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        children.entry(entry.ppid).or_default().push(entry);
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|entry| entry.pid);
+    }
+
+    let mut tree = Vec::new();
+    let mut stack = vec![(shell, 0)];
+    while let Some((entry, depth)) = stack.pop() {
+        tree.push(ProcessInfo {
+            pid: entry.pid,
+            ppid: entry.ppid,
+            name: entry.name.clone(),
+            command: entry.command.clone(),
+            state: entry.state.clone(),
+            foreground: foreground_pgid > 0 && entry.pgid == foreground_pgid,
+            depth,
+        });
+        if let Some(list) = children.get(&entry.pid) {
+            stack.extend(list.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+    Ok(tree)
+}
+
+/// Signal a process, provided it is the shell or one of its descendants
+pub fn signal_process(shell_pid: u32, pid: u32, signal: TerminalSignal) -> Result<()> {
+    if !process_tree(shell_pid)?
+        .iter()
+        .any(|process| process.pid == pid)
+    {
+        bail!("Process {pid} does not belong to this terminal");
+    }
+
+    #[cfg(unix)]
+    {
+        use nix::{
+            sys::signal::{kill, Signal},
+            unistd::Pid,
+        };
+        let signal = match signal {
+            TerminalSignal::Interrupt => Signal::SIGINT,
+            TerminalSignal::Terminate => Signal::SIGTERM,
+            TerminalSignal::Kill => Signal::SIGKILL,
+        };
+        kill(Pid::from_raw(pid as i32), signal)?;
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        bail!("Sending signals is not supported on this platform")
+    }
+}
+
+struct Entry {
+    pid: u32,
+    ppid: u32,
+    pgid: i32,
+    /// Foreground process group of the process's controlling terminal
+    tpgid: i32,
+    state: String,
+    name: String,
+    command: String,
+}
+
+#[cfg(target_os = "linux")]
+fn snapshot() -> Result<Vec<Entry>> {
+    use std::fs;
+
+    let mut entries = Vec::new();
+    for dir in fs::read_dir("/proc")?.flatten() {
+        let Some(pid) = dir.file_name().to_str().and_then(|name| name.parse().ok()) else {
+            continue;
+        };
+        // Processes can exit between listing and reading
+        let Ok(stat) = fs::read_to_string(dir.path().join("stat")) else {
+            continue;
+        };
+        let Some(entry) = parse_stat(pid, &stat) else {
+            continue;
+        };
+        let command = fs::read(dir.path().join("cmdline"))
+            .map(|raw| {
+                raw.split(|byte| *byte == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(String::from_utf8_lossy)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+        entries.push(Entry {
+            command: if command.is_empty() {
+                entry.name.clone()
+            } else {
+                command
+            },
+            ..entry
+        });
+    }
+    Ok(entries)
+}
+
+/// Parse `/proc/<pid>/stat`; the name is in parentheses and may itself contain them
+#[cfg(target_os = "linux")]
+fn parse_stat(pid: u32, stat: &str) -> Option<Entry> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    Some(Entry {
+        pid,
+        state: fields.first()?.to_string(),
+        ppid: fields.get(1)?.parse().ok()?,
+        pgid: fields.get(2)?.parse().ok()?,
+        tpgid: fields.get(5)?.parse().ok()?,
+        name,
+        command: String::new(),
+    })
+}
+
+/// Without `/proc`, ask `ps`; `command` comes last since it contains spaces
+#[cfg(not(target_os = "linux"))]
+fn snapshot() -> Result<Vec<Entry>> {
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "pid=,ppid=,pgid=,tpgid=,state=,command="])
+        .output()?;
+    if !output.status.success() {
+        bail!("ps exited with {}", output.status);
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let entries = text
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let pgid = fields.next()?.parse().ok()?;
+            let tpgid = fields.next()?.parse().ok()?;
+            let state: String = fields.next()?.chars().take(1).collect();
+            let command = fields.collect::<Vec<_>>().join(" ");
+            let name = command
+                .split_whitespace()
+                .next()
+                .and_then(|program| program.rsplit('/').next())
+                .unwrap_or_default()
+                .to_string();
+            Some(Entry {
+                pid,
+                ppid,
+                pgid,
+                tpgid,
+                state,
+                name,
+                command,
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, ppid: u32, pgid: i32, state: &str) -> Entry {
+        Entry {
+            pid,
+            ppid,
+            pgid,
+            tpgid: 30,
+            state: state.to_string(),
+            name: format!("p{pid}"),
+            command: format!("p{pid}"),
+        }
+    }
+
+    #[test]
+    fn signal_names_parse_with_or_without_prefix() {
+        assert_eq!(
+            TerminalSignal::parse("SIGINT").unwrap(),
+            TerminalSignal::Interrupt
+        );
+        assert_eq!(
+            TerminalSignal::parse(" term ").unwrap(),
+            TerminalSignal::Terminate
+        );
+        assert_eq!(
+            TerminalSignal::parse("sigkill").unwrap(),
+            TerminalSignal::Kill
+        );
+        assert!(TerminalSignal::parse("HUP").is_err());
+        assert!(TerminalSignal::parse("").is_err());
+    }
+
+    #[test]
+    fn tree_lists_descendants_depth_first_in_pid_order() {
+        let entries = vec![
+            entry(1, 0, 1, "S"),
+            entry(40, 10, 30, "R"),
+            entry(30, 10, 30, "S"),
+            entry(10, 1, 10, "S"),
+            entry(20, 1, 20, "Z"),
+            entry(50, 30, 30, "S"),
+            entry(99, 2, 99, "S"),
+        ];
+        let tree = build_tree(&entries, 1).unwrap();
+        let order: Vec<(u32, usize)> = tree
+            .iter()
+            .map(|process| (process.pid, process.depth))
+            .collect();
+        assert_eq!(
+            order,
+            vec![(1, 0), (10, 1), (30, 2), (50, 3), (40, 2), (20, 1)]
+        );
+
+        let foreground: Vec<u32> = tree
+            .iter()
+            .filter(|process| process.foreground)
+            .map(|process| process.pid)
+            .collect();
+        assert_eq!(foreground, vec![30, 50, 40]);
+        // Zombies and the shell itself are not jobs
+        assert!(!tree[0].is_job());
+        assert!(!tree[5].is_job());
+        assert!(tree[1].is_job());
+    }
+
+    #[test]
+    fn tree_requires_a_running_shell() {
+        assert!(build_tree(&[entry(1, 0, 1, "S")], 2).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stat_names_may_contain_parentheses_and_spaces() {
+        let stat = "4242 (my (odd) name) S 100 4242 4242 34816 4300 4194304 0 0";
+        let entry = parse_stat(4242, stat).unwrap();
+        assert_eq!(entry.name, "my (odd) name");
+        assert_eq!(entry.state, "S");
+        assert_eq!(entry.ppid, 100);
+        assert_eq!(entry.pgid, 4242);
+        assert_eq!(entry.tpgid, 4300);
+        assert!(parse_stat(1, "1 (truncated").is_none());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::path_utils::expand_tilde_path;
+
+const SHELLS_FILE: &str = "/etc/shells";
+/// Shells offered as profiles without any configuration when they are installed
+const BUILTIN_SHELLS: &[&str] = &["zsh", "bash", "fish", "nu", "sh"];
+/// Shells that read `-c <command>` the way `sh` does
+const POSIX_SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "mksh", "ash"];
+
+/// How a terminal starts its shell, stored in settings under `terminalProfiles`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellProfile {
+    #[serde(default)]
+    pub name: String,
+    /// Path or bare name looked up in `/etc/shells` and `PATH`; `$SHELL` when unset
+    #[serde(default)]
+    pub shell: Option<String>,
+    /// Passed after the login flag
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Start a login shell; defaults to on for shells that take `-l`
+    #[serde(default)]
+    pub login: Option<bool>,
+    /// Applied over the terminal defaults such as `TERM` and `LANG`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Typed into the shell once it starts, e.g. `nix develop`
+    #[serde(default)]
+    pub initial_command: Option<String>,
+    /// Load hooks that report the cwd and command boundaries; on unless set to false
+    #[serde(default)]
+    pub shell_integration: Option<bool>,
+}
+
+impl ShellProfile {
+    pub fn initial_command(&self) -> Option<&str> {
+        self.initial_command
+            .as_deref()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+    }
+}
+
+/// One profile per known shell installed on this machine
+pub fn builtin_profiles() -> Vec<ShellProfile> {
+    BUILTIN_SHELLS
+        .iter()
+        .filter_map(|name| {
+            let path = find_shell(name)?;
+            Some(ShellProfile {
+                name: name.to_string(),
+                shell: Some(path.to_string_lossy().to_string()),
+                ..ShellProfile::default()
+            })
+        })
+        .collect()
+}
+
+/// Turn a profile's shell into an absolute path the OS allows as a login shell
+pub fn resolve_shell_path(shell: &str) -> Result<String, String> {
+    let trimmed = shell.trim();
+    if trimmed.is_empty() {
+        return Err("Shell path is empty".to_string());
+    }
+
+    let path = if trimmed.contains('/') || trimmed.starts_with('~') {
+        expand_tilde_path(trimmed)
+    } else {
+        find_shell(trimmed).ok_or_else(|| format!("Shell not found: {trimmed}"))?
+    };
+    if !path.is_file() {
+        return Err(format!("Shell not found: {}", path.display()));
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(listed) = listed_shells() {
+        if !is_listed(&path, &listed) {
+            return Err(format!(
+                "Shell is not listed in {SHELLS_FILE}: {}",
+                path.display()
+            ));
+        }
+    }
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Whether a resolved shell path runs `-c <command>` with POSIX semantics
+pub fn is_posix_shell(shell: &str) -> bool {
+    Path::new(shell)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| POSIX_SHELLS.contains(&name))
+}
+
+/// `sh` for command lines a profile's own shell or program cannot run
+pub fn posix_shell() -> Result<String, String> {
+    find_shell("sh")
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| "No POSIX shell (sh) found to run the command".to_string())
+}
+
+/// Entries of `/etc/shells`, or `None` when the system has no such file
+fn listed_shells() -> Option<Vec<PathBuf>> {
+    let contents = std::fs::read_to_string(SHELLS_FILE).ok()?;
+    Some(
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(PathBuf::from)
+            .collect(),
+    )
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_listed(path: &Path, listed: &[PathBuf]) -> bool {
+    // /bin is often a symlink to /usr/bin, so compare resolved paths too
+    let resolved = std::fs::canonicalize(path).ok();
+    listed.iter().any(|entry| {
+        entry == path
+            || resolved.as_ref().is_some_and(|resolved| {
+                std::fs::canonicalize(entry).ok().as_ref() == Some(resolved)
+            })
+    })
+}
+
+/// Look a bare shell name up in `/etc/shells` first, since GUI apps often get a minimal `PATH`
+fn find_shell(name: &str) -> Option<PathBuf> {
+    let listed = listed_shells().unwrap_or_default();
+    let from_listed = listed
+        .into_iter()
+        .find(|entry| entry.file_name().is_some_and(|file| file == name) && entry.is_file());
+    from_listed.or_else(|| {
+        let path = env::var_os("PATH")?;
+        env::split_paths(&path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
@@ -0,0 +1,272 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use portable_pty::{CommandBuilder, PtySize};
+use serde::{Deserialize, Serialize};
+
+/// Output kept per session for replay when a client attaches
+const SCROLLBACK_LIMIT: usize = 256 * 1024;
+
+/// Everything needed to start a shell in a PTY, in the app or in the session daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnSpec {
+    pub shell: String,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    pub env: Vec<(String, String)>,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl SpawnSpec {
+    pub fn size(&self) -> PtySize {
+        PtySize {
+            rows: self.rows,
+            cols: self.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+
+    pub fn command(&self) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(&self.shell);
+        cmd.args(&self.args);
+        cmd.cwd(&self.cwd);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        cmd
+    }
+}
+
+/// Bounded history of a PTY's output plus the total number of bytes it produced
+#[derive(Default)]
+pub struct Scrollback {
+    bytes: VecDeque<u8>,
+    /// Lets clients drop live events that a replay already covered
+    offset: u64,
+}
+
+impl Scrollback {
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn record(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes);
+        let excess = self.bytes.len().saturating_sub(SCROLLBACK_LIMIT);
+        self.bytes.drain(..excess);
+        self.offset += bytes.len() as u64;
+    }
+
+    /// Replace the history with one taken from elsewhere, ending at `offset`
+    pub fn restore(&mut self, bytes: &[u8], offset: u64) {
+        self.bytes.clear();
+        self.record(bytes);
+        self.offset = offset;
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let (front, back) = self.bytes.as_slices();
+        [front, back].concat()
+    }
+
+    /// History as text plus the offset it ends at, which excludes a trailing partial character
+    pub fn replay(&self) -> (String, u64) {
+        let bytes = self.bytes();
+        // Trimming may have cut a character in half
+        let start = bytes
+            .iter()
+            .position(|byte| byte & 0b1100_0000 != 0b1000_0000)
+            .unwrap_or(bytes.len());
+        let end = bytes.len() - incomplete_suffix_len(&bytes[start..]);
+        let text = String::from_utf8_lossy(&bytes[start..end]).into_owned();
+        (text, self.offset - (bytes.len() - end) as u64)
+    }
+}
+
+/// Decodes output chunk by chunk, holding back a character split across reads
+#[derive(Default)]
+pub struct Utf8Stream {
+    pending: Vec<u8>,
+}
+
+impl Utf8Stream {
+    /// Bytes of an incomplete character waiting for the next chunk
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(bytes);
+        let keep = incomplete_suffix_len(&input);
+        self.pending = input.split_off(input.len() - keep);
+        String::from_utf8_lossy(&input).into_owned()
+    }
+
+    /// Continue a stream whose output so far ended with `tail`
+    pub fn resume_after(&mut self, tail: &[u8]) {
+        let keep = incomplete_suffix_len(tail);
+        self.pending = tail[tail.len() - keep..].to_vec();
+    }
+}
+
+/// Length of a multi-byte character cut off at the end of `bytes`, or 0 when it ends cleanly
+fn incomplete_suffix_len(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let width = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if width > back { back } else { 0 };
+    }
+    0
+}
+
+/// Terminal output as readable text: escape sequences dropped, carriage-return overwrites applied
+pub fn plain_text(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let mut lines = vec![String::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let line = lines.last_mut().unwrap();
+        match ch {
+            '\x1b' => skip_escape(&mut chars),
+            '\n' => lines.push(String::new()),
+            // A lone CR rewinds the line, as progress bars rely on
+            '\r' if chars.peek() != Some(&'\n') => line.clear(),
+            '\x08' => {
+                line.pop();
+            }
+            '\t' => line.push(ch),
+            ch if ch.is_control() => {}
+            ch => line.push(ch),
+        }
+    }
+    lines.join("\n")
+}
+
+fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    match chars.next() {
+        // CSI runs until a final byte in @..~
+        Some('[') => {
+            for ch in chars.by_ref() {
+                if ('@'..='~').contains(&ch) {
+                    break;
+                }
+            }
+        }
+        // OSC, DCS and friends run until BEL or ST
+        Some(']' | 'P' | '_' | '^' | 'X') => {
+            while let Some(ch) = chars.next() {
+                if ch == '\x07' {
+                    break;
+                }
+                if ch == '\x1b' && chars.peek() == Some(&'\\') {
+                    chars.next();
+                    break;
+                }
+            }
+        }
+        // Charset selection takes one more character
+        Some('(' | ')' | '*' | '+' | '#' | '%') => {
+            chars.next();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_stream_holds_back_split_characters() {
+        for text in ["é", "€", "😀"] {
+            let bytes = text.as_bytes();
+            for split in 1..bytes.len() {
+                let mut stream = Utf8Stream::default();
+                let mut first = b"a".to_vec();
+                first.extend_from_slice(&bytes[..split]);
+                assert_eq!(stream.decode(&first), "a");
+                assert_eq!(stream.pending(), split);
+
+                let mut second = bytes[split..].to_vec();
+                second.push(b'b');
+                assert_eq!(stream.decode(&second), format!("{text}b"));
+                assert_eq!(stream.pending(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn utf8_stream_resumes_after_a_partial_tail() {
+        let bytes = "x😀".as_bytes();
+        let mut stream = Utf8Stream::default();
+        stream.resume_after(&bytes[..3]);
+        assert_eq!(stream.pending(), 2);
+        assert_eq!(stream.decode(&bytes[3..]), "😀");
+    }
+
+    #[test]
+    fn incomplete_suffix_len_counts_only_unfinished_characters() {
+        let euro = "€".as_bytes();
+        let emoji = "😀".as_bytes();
+        assert_eq!(incomplete_suffix_len(b""), 0);
+        assert_eq!(incomplete_suffix_len(b"abc"), 0);
+        assert_eq!(incomplete_suffix_len("aé".as_bytes()), 0);
+        assert_eq!(incomplete_suffix_len(&"aé".as_bytes()[..2]), 1);
+        assert_eq!(incomplete_suffix_len(euro), 0);
+        assert_eq!(incomplete_suffix_len(&euro[..1]), 1);
+        assert_eq!(incomplete_suffix_len(&euro[..2]), 2);
+        assert_eq!(incomplete_suffix_len(emoji), 0);
+        assert_eq!(incomplete_suffix_len(&emoji[..3]), 3);
+    }
+
+    #[test]
+    fn replay_skips_a_character_cut_by_trimming() {
+        let mut scrollback = Scrollback::default();
+        scrollback.record(&"€".as_bytes()[1..]);
+        scrollback.record(b"ok");
+        assert_eq!(scrollback.replay(), ("ok".to_string(), 4));
+
+        // The emoji's first two bytes fall off the front of a full buffer
+        let mut scrollback = Scrollback::default();
+        scrollback.record("😀".as_bytes());
+        scrollback.record(&vec![b'a'; SCROLLBACK_LIMIT - 2]);
+        let (text, offset) = scrollback.replay();
+        assert_eq!(offset, SCROLLBACK_LIMIT as u64 + 2);
+        assert_eq!(text.len(), SCROLLBACK_LIMIT - 2);
+        assert!(text.bytes().all(|byte| byte == b'a'));
+    }
+
+    #[test]
+    fn replay_offset_excludes_a_trailing_partial_character() {
+        let mut scrollback = Scrollback::default();
+        scrollback.record(b"hi");
+        scrollback.record(&"😀".as_bytes()[..2]);
+        assert_eq!(scrollback.offset(), 4);
+        assert_eq!(scrollback.replay(), ("hi".to_string(), 2));
+    }
+
+    #[test]
+    fn plain_text_applies_carriage_returns_and_backspaces() {
+        assert_eq!(plain_text(b"10%\r50%\r100%\r\ndone"), "100%\ndone");
+        assert_eq!(plain_text(b"abc\x08\x08d\n"), "ad\n");
+        assert_eq!(plain_text(b"a\tb\x07"), "a\tb");
+    }
+
+    #[test]
+    fn plain_text_strips_csi_and_osc_sequences() {
+        assert_eq!(plain_text(b"\x1b[1;31mred\x1b[0m"), "red");
+        assert_eq!(plain_text(b"\x1b]0;title\x07prompt"), "prompt");
+        assert_eq!(plain_text(b"\x1b]7;file:///tmp\x1b\\$ ls\x1b(B"), "$ ls");
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::terminal_pty::{plain_text, Utf8Stream};
+
+const RECORDINGS_DIR: &str = "recordings";
+const CAST_EXTENSION: &str = "cast";
+
+/// Summary of an asciicast v2 file under the recordings directory
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub id: String,
+    pub path: String,
+    pub title: Option<String>,
+    pub width: u16,
+    pub height: u16,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: f64,
+    pub size_bytes: u64,
+}
+
+/// First line of an asciicast v2 file
+#[derive(Serialize, Deserialize)]
+struct CastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default)]
+    env: serde_json::Map<String, Value>,
+}
+
+/// Writes one terminal session's output and resizes as an asciicast v2 recording
+pub struct Recorder {
+    id: String,
+    path: PathBuf,
+    file: BufWriter<File>,
+    started: Instant,
+    started_at: DateTime<Utc>,
+    title: Option<String>,
+    width: u16,
+    height: u16,
+    /// Events hold text, so characters split across chunks are held back
+    utf8: Utf8Stream,
+}
+
+impl Recorder {
+    pub fn start(
+        session_id: &str,
+        cols: u16,
+        rows: u16,
+        shell: &str,
+        title: Option<String>,
+    ) -> Result<Self> {
+        let dir = recordings_dir()?;
+        fs::create_dir_all(&dir)?;
+
+        let started_at = Utc::now();
+        let short_session: String = session_id.chars().take(8).collect();
+        let id = format!("{}-{short_session}", started_at.format("%Y%m%d-%H%M%S%3f"));
+        let path = dir.join(format!("{id}.{CAST_EXTENSION}"));
+        let mut file = BufWriter::new(
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+
+        let mut env = serde_json::Map::new();
+        env.insert("SHELL".to_string(), json!(shell));
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: started_at.timestamp(),
+            title: title.clone(),
+            env,
+        };
+        writeln!(file, "{}", serde_json::to_string(&header)?)?;
+        file.flush()?;
+
+        Ok(Self {
+            id,
+            path,
+            file,
+            started: Instant::now(),
+            started_at,
+            title,
+            width: cols,
+            height: rows,
+            utf8: Utf8Stream::default(),
+        })
+    }
+
+    pub fn output(&mut self, bytes: &[u8]) -> Result<()> {
+        let data = self.utf8.decode(bytes);
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.write_event("o", &data)
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.write_event("r", &format!("{cols}x{rows}"))
+    }
+
+    /// Flush the file and describe the finished recording
+    pub fn finish(mut self) -> Result<RecordingInfo> {
+        self.file.flush()?;
+        Ok(self.info())
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn info(&self) -> RecordingInfo {
+        RecordingInfo {
+            id: self.id.clone(),
+            path: self.path.to_string_lossy().to_string(),
+            title: self.title.clone(),
+            width: self.width,
+            height: self.height,
+            started_at: self.started_at,
+            duration_secs: self.started.elapsed().as_secs_f64(),
+            size_bytes: fs::metadata(&self.path).map(|meta| meta.len()).unwrap_or(0),
+        }
+    }
+
+    fn write_event(&mut self, code: &str, data: &str) -> Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let event = json!([(elapsed * 1e6).round() / 1e6, code, data]);
+        writeln!(self.file, "{event}")?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+pub fn recordings_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("No home directory"))?;
+    Ok(home
+        .join(".config")
+        .join("openchamber")
+        .join(RECORDINGS_DIR))
+}
+
+/// Recordings on disk, newest first; unreadable files are skipped
+pub fn list() -> Result<Vec<RecordingInfo>> {
+    let dir = recordings_dir()?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut recordings: Vec<RecordingInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == CAST_EXTENSION))
+        .filter_map(|path| describe(&path).ok())
+        .collect();
+    recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(recordings)
+}
+
+/// A recording's output with escape sequences stripped, for pasting into a bug report
+pub fn export_plain_text(id: &str) -> Result<String> {
+    let path = recording_path(id)?;
+    let reader = BufReader::new(File::open(&path)?);
+    let mut output = Vec::new();
+    for line in reader.lines().skip(1) {
+        let Ok(Value::Array(event)) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+        if let [_, Value::String(code), Value::String(data)] = event.as_slice() {
+            if code == "o" {
+                output.extend_from_slice(data.as_bytes());
+            }
+        }
+    }
+    Ok(plain_text(&output))
+}
+
+fn recording_path(id: &str) -> Result<PathBuf> {
+    // Ids are file stems we generated; anything else could escape the directory
+    if id.is_empty() || !id.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-') {
+        bail!("Invalid recording id: {id}");
+    }
+    let path = recordings_dir()?.join(format!("{id}.{CAST_EXTENSION}"));
+    if !path.is_file() {
+        bail!("Recording not found: {id}");
+    }
+    Ok(path)
+}
+
+fn describe(path: &Path) -> Result<RecordingInfo> {
+    let mut header_line = String::new();
+    BufReader::new(File::open(path)?).read_line(&mut header_line)?;
+    let header: CastHeader = serde_json::from_str(&header_line)?;
+    let metadata = fs::metadata(path)?;
+    let started_at = DateTime::from_timestamp(header.timestamp, 0).unwrap_or_default();
+    // The last write is when the final event landed
+    let duration_secs = metadata
+        .modified()
+        .ok()
+        .map(DateTime::<Utc>::from)
+        .map(|modified| (modified - started_at).num_milliseconds().max(0) as f64 / 1000.0)
+        .unwrap_or(0.0);
+
+    Ok(RecordingInfo {
+        id: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        title: header.title,
+        width: header.width,
+        height: header.height,
+        started_at,
+        duration_secs,
+        size_bytes: metadata.len(),
+    })
+}
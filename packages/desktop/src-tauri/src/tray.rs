@@ -0,0 +1,367 @@
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use once_cell::sync::Lazy;
+use tauri::{
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    tray::TrayIconBuilder,
+    AppHandle, Emitter, Manager,
+};
+use tokio::sync::Mutex;
+
+use crate::opencode_manager::PermissionReply;
+use crate::permission_requests::PendingPermission;
+use crate::session_activity::{ActivityPhase, SessionActivitySnapshot};
+use crate::{DesktopRuntime, SettingsStore};
+
+const TRAY_ID: &str = "openchamber";
+const TRAY_TOOLTIP: &str = "OpenChamber";
+const MENU_ITEM_SHOW_ID: &str = "tray_show";
+const MENU_ITEM_RESTART_ID: &str = "tray_restart_opencode";
+const MENU_ITEM_STATUS_ID: &str = "tray_status";
+const MENU_ITEM_NO_PERMISSIONS_ID: &str = "tray_no_permissions";
+/// Menu ids for permission answers look like `permission:<reply>:<permission id>`
+const PERMISSION_ID_PREFIX: &str = "permission:";
+/// Menu ids for active sessions look like `session:<session id>`
+const SESSION_ID_PREFIX: &str = "session:";
+/// Menu ids for recent projects look like `project:<absolute path>`
+const PROJECT_ID_PREFIX: &str = "project:";
+const SWITCH_DIRECTORY_EVENT: &str = "openchamber:switch-directory";
+const MAX_MENU_LABEL_CHARS: usize = 60;
+const MAX_RECENT_PROJECTS: usize = 8;
+
+/// Everything the tray menu shows, gathered before the menu is rebuilt
+#[derive(Default)]
+struct TrayContents {
+    pending: Vec<PendingPermission>,
+    /// Sessions that are not idle, most recently changed first
+    sessions: Vec<SessionActivitySnapshot>,
+    projects: Vec<PathBuf>,
+    current_directory: Option<PathBuf>,
+}
+
+impl TrayContents {
+    fn busy_count(&self) -> usize {
+        self.sessions
+            .iter()
+            .filter(|session| session.phase == ActivityPhase::Busy)
+            .count()
+    }
+
+    /// What the rendered menu depends on, so unchanged contents skip the rebuild
+    fn key(&self) -> MenuKey {
+        MenuKey {
+            pending: self
+                .pending
+                .iter()
+                .map(|permission| permission.id.clone())
+                .collect(),
+            sessions: self
+                .sessions
+                .iter()
+                .map(|session| (session.session_id.clone(), session.phase))
+                .collect(),
+            projects: self.projects.clone(),
+            current_directory: self.current_directory.clone(),
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct MenuKey {
+    pending: Vec<String>,
+    sessions: Vec<(String, ActivityPhase)>,
+    projects: Vec<PathBuf>,
+    current_directory: Option<PathBuf>,
+}
+
+/// Projects as of the last settings revision read, and the contents the menu was last built from
+#[derive(Default)]
+struct TrayCache {
+    settings_revision: Option<u64>,
+    projects: Vec<PathBuf>,
+    current_directory: Option<PathBuf>,
+    rendered: Option<MenuKey>,
+}
+
+static TRAY_CACHE: Lazy<Mutex<TrayCache>> = Lazy::new(|| Mutex::new(TrayCache::default()));
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app, &TrayContents::default())?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(TRAY_TOOLTIP)
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    refresh(app);
+    Ok(())
+}
+
+/// Rebuild the tray menu from pending permissions, session activity and recent projects
+///
+/// Settings are only re-read after they were saved, and the menu is left alone when
+/// nothing it shows has changed.
+pub fn refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(runtime) = app.try_state::<DesktopRuntime>() else {
+            return;
+        };
+        let Some(tray) = app.tray_by_id(TRAY_ID) else {
+            return;
+        };
+
+        // Held throughout so concurrent refreshes cannot apply an older menu last
+        let mut cache = TRAY_CACHE.lock().await;
+        let revision = runtime.settings().revision();
+        if cache.settings_revision != Some(revision) {
+            match runtime.settings().load().await {
+                Ok(settings) => {
+                    cache.projects = SettingsStore::recent_projects_in(&settings)
+                        .into_iter()
+                        .filter(|path| path.is_dir())
+                        .take(MAX_RECENT_PROJECTS)
+                        .collect();
+                    cache.current_directory = SettingsStore::last_directory_in(&settings);
+                    cache.settings_revision = Some(revision);
+                }
+                Err(err) => warn!("[desktop:tray] Failed to load settings: {err}"),
+            }
+        }
+
+        let contents = TrayContents {
+            pending: runtime.permission_requests().list(),
+            sessions: runtime
+                .session_activity()
+                .snapshot(None)
+                .await
+                .into_iter()
+                .filter(|session| session.phase != ActivityPhase::Idle)
+                .collect(),
+            projects: cache.projects.clone(),
+            current_directory: cache.current_directory.clone(),
+        };
+        let key = contents.key();
+        if cache.rendered.as_ref() == Some(&key) {
+            return;
+        }
+
+        // Windows has no tray title and Linux no tooltip, so the status also heads the menu
+        let busy = contents.busy_count();
+        let (tooltip, title) = if busy > 0 {
+            (
+                format!("{TRAY_TOOLTIP} — {}", status_text(busy)),
+                Some(busy.to_string()),
+            )
+        } else {
+            (TRAY_TOOLTIP.to_string(), None)
+        };
+        let result = build_menu(&app, &contents)
+            .and_then(|menu| tray.set_menu(Some(menu)))
+            .and_then(|_| tray.set_tooltip(Some(tooltip)))
+            .and_then(|_| tray.set_title(title));
+        match result {
+            Ok(()) => cache.rendered = Some(key),
+            Err(err) => warn!("[desktop:tray] Failed to update tray menu: {err}"),
+        }
+    });
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn build_menu(app: &AppHandle, contents: &TrayContents) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+
+    menu.append(&MenuItem::with_id(
+        app,
+        MENU_ITEM_STATUS_ID,
+        status_text(contents.busy_count()),
+        false,
+        None::<&str>,
+    )?)?;
+    for session in &contents.sessions {
+        menu.append(&MenuItem::with_id(
+            app,
+            format!("{SESSION_ID_PREFIX}{}", session.session_id),
+            truncate_label(&session_label(session)),
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    if contents.pending.is_empty() {
+        menu.append(&MenuItem::with_id(
+            app,
+            MENU_ITEM_NO_PERMISSIONS_ID,
+            "No pending permission requests",
+            false,
+            None::<&str>,
+        )?)?;
+    } else {
+        for permission in &contents.pending {
+            let submenu = Submenu::with_items(
+                app,
+                truncate_label(&permission.label()),
+                true,
+                &[
+                    &permission_item(app, permission, PermissionReply::Once, "Allow Once")?,
+                    &permission_item(app, permission, PermissionReply::Always, "Always Allow")?,
+                    &permission_item(app, permission, PermissionReply::Reject, "Deny")?,
+                ],
+            )?;
+            menu.append(&submenu)?;
+        }
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    let projects = Submenu::new(app, "Recent Projects", !contents.projects.is_empty())?;
+    for project in &contents.projects {
+        let current = contents.current_directory.as_deref() == Some(project.as_path());
+        projects.append(&CheckMenuItem::with_id(
+            app,
+            format!("{PROJECT_ID_PREFIX}{}", project.display()),
+            truncate_label(&project.display().to_string()),
+            !current,
+            current,
+            None::<&str>,
+        )?)?;
+    }
+    menu.append(&projects)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        MENU_ITEM_RESTART_ID,
+        "Restart OpenCode",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        MENU_ITEM_SHOW_ID,
+        "Show OpenChamber",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
+
+fn permission_item(
+    app: &AppHandle,
+    permission: &PendingPermission,
+    reply: PermissionReply,
+    text: &str,
+) -> tauri::Result<MenuItem<tauri::Wry>> {
+    let reply_id = match reply {
+        PermissionReply::Once => "once",
+        PermissionReply::Always => "always",
+        PermissionReply::Reject => "reject",
+    };
+    MenuItem::with_id(
+        app,
+        format!("{PERMISSION_ID_PREFIX}{reply_id}:{}", permission.id),
+        text,
+        true,
+        None::<&str>,
+    )
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if id == MENU_ITEM_SHOW_ID || id.starts_with(SESSION_ID_PREFIX) {
+        show_main_window(app);
+        return;
+    }
+    if id == MENU_ITEM_RESTART_ID {
+        restart_opencode(app);
+        return;
+    }
+    if let Some(path) = id.strip_prefix(PROJECT_ID_PREFIX) {
+        // The webview owns the directory switch so its stores follow along
+        let _ = app.emit(SWITCH_DIRECTORY_EVENT, path);
+        show_main_window(app);
+        return;
+    }
+
+    let Some((reply, permission_id)) = id
+        .strip_prefix(PERMISSION_ID_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return;
+    };
+    let reply = match reply {
+        "once" => PermissionReply::Once,
+        "always" => PermissionReply::Always,
+        "reject" => PermissionReply::Reject,
+        _ => return,
+    };
+
+    let app = app.clone();
+    let permission_id = permission_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(runtime) = app.try_state::<DesktopRuntime>() else {
+            return;
+        };
+        let opencode = runtime.opencode_manager();
+        if let Err(err) = runtime
+            .permission_requests()
+            .reply(&opencode, &permission_id, reply)
+            .await
+        {
+            warn!("[desktop:tray] Failed to answer permission {permission_id}: {err}");
+        }
+        refresh(&app);
+    });
+}
+
+fn restart_opencode(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(runtime) = app.try_state::<DesktopRuntime>() else {
+            return;
+        };
+        if let Err(err) = runtime.restart_opencode().await {
+            warn!("[desktop:tray] Failed to restart OpenCode: {err}");
+        }
+    });
+}
+
+fn status_text(busy: usize) -> String {
+    match busy {
+        0 => "All sessions idle".to_string(),
+        1 => "1 session working".to_string(),
+        n => format!("{n} sessions working"),
+    }
+}
+
+fn session_label(session: &SessionActivitySnapshot) -> String {
+    let project = Path::new(&session.directory)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| session.directory.clone());
+    let phase = match session.phase {
+        ActivityPhase::Idle => "Idle",
+        ActivityPhase::Busy => "Working",
+        ActivityPhase::Cooldown => "Finishing",
+    };
+    format!("{phase} · {project} · {}", session.session_id)
+}
+
+fn truncate_label(label: &str) -> String {
+    let mut chars = label.chars();
+    let truncated: String = chars.by_ref().take(MAX_MENU_LABEL_CHARS).collect();
+    if chars.next().is_some() {
+        format!("{truncated}…")
+    } else {
+        truncated
+    }
+}
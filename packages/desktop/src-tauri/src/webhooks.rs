@@ -0,0 +1,449 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use parking_lot::Mutex;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Client,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::notification_rules::{NotificationCandidate, RuleTrigger};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 3;
+#[cfg(not(test))]
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+#[cfg(test)]
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+const MAX_LOG_ENTRIES: usize = 100;
+/// Keep log entries readable when a receiver answers with a full HTML page
+const MAX_LOGGED_RESPONSE_CHARS: usize = 500;
+
+/// `webhooks[]` entry in settings.json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookConfig {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Events that fire this webhook; empty fires on all of them
+    #[serde(default)]
+    pub events: Vec<RuleTrigger>,
+    /// JSON document whose string values may contain `{{placeholders}}`; empty sends the default payload
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl WebhookConfig {
+    fn wants(&self, trigger: RuleTrigger) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&trigger))
+    }
+}
+
+/// Outcome of one webhook delivery, kept in a bounded in-memory log
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: RuleTrigger,
+    pub url: String,
+    pub ok: bool,
+    pub status: Option<u16>,
+    pub attempts: u32,
+    pub error: Option<String>,
+    pub response: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+struct DispatcherInner {
+    client: Client,
+    log: Mutex<VecDeque<WebhookDelivery>>,
+}
+
+/// Sends agent lifecycle events to the webhooks configured in settings
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    inner: Arc<DispatcherInner>,
+}
+
+impl WebhookDispatcher {
+    pub fn new() -> Result<Self> {
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self {
+            inner: Arc::new(DispatcherInner {
+                client,
+                log: Mutex::new(VecDeque::new()),
+            }),
+        })
+    }
+
+    /// Newest first
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.inner.log.lock().iter().rev().cloned().collect()
+    }
+
+    /// Fire every webhook subscribed to the candidate's event in the background
+    pub fn dispatch(&self, webhooks: Vec<WebhookConfig>, candidate: &NotificationCandidate) {
+        for webhook in webhooks
+            .into_iter()
+            .filter(|webhook| webhook.wants(candidate.trigger))
+        {
+            let dispatcher = self.clone();
+            let candidate = candidate.clone();
+            tauri::async_runtime::spawn(async move {
+                dispatcher.deliver(&webhook, &candidate).await;
+            });
+        }
+    }
+
+    /// Send one event to one webhook, retrying network failures, 429 and 5xx responses
+    pub async fn deliver(
+        &self,
+        webhook: &WebhookConfig,
+        candidate: &NotificationCandidate,
+    ) -> WebhookDelivery {
+        let started = std::time::Instant::now();
+        let mut delivery = WebhookDelivery {
+            id: Uuid::new_v4().to_string(),
+            webhook_id: webhook.id.clone(),
+            event: candidate.trigger,
+            url: webhook.url.clone(),
+            ok: false,
+            status: None,
+            attempts: 0,
+            error: None,
+            response: None,
+            delivered_at: Utc::now(),
+            duration_ms: 0,
+        };
+
+        match build_request(webhook, candidate) {
+            Ok((headers, body)) => {
+                while delivery.attempts < MAX_ATTEMPTS {
+                    if delivery.attempts > 0 {
+                        tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(delivery.attempts - 1))
+                            .await;
+                    }
+                    delivery.attempts += 1;
+
+                    let result = self
+                        .inner
+                        .client
+                        .post(&webhook.url)
+                        .headers(headers.clone())
+                        .body(body.clone())
+                        .send()
+                        .await;
+                    match result {
+                        Ok(response) => {
+                            let status = response.status();
+                            delivery.status = Some(status.as_u16());
+                            let text = response.text().await.unwrap_or_default();
+                            delivery.response = (!text.is_empty())
+                                .then(|| text.chars().take(MAX_LOGGED_RESPONSE_CHARS).collect());
+                            if status.is_success() {
+                                delivery.ok = true;
+                                delivery.error = None;
+                                break;
+                            }
+                            delivery.error = Some(format!("Receiver answered {status}"));
+                            if !(status.is_server_error() || status.as_u16() == 429) {
+                                break;
+                            }
+                        }
+                        Err(err) => {
+                            delivery.status = None;
+                            delivery.error = Some(err.to_string());
+                        }
+                    }
+                }
+            }
+            Err(err) => delivery.error = Some(err.to_string()),
+        }
+
+        delivery.delivered_at = Utc::now();
+        delivery.duration_ms = started.elapsed().as_millis() as u64;
+        if delivery.ok {
+            info!(
+                "[desktop:webhooks] Delivered {:?} to {} (attempts={})",
+                delivery.event, webhook.id, delivery.attempts
+            );
+        } else {
+            warn!(
+                "[desktop:webhooks] Failed to deliver {:?} to {}: {}",
+                delivery.event,
+                webhook.id,
+                delivery.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+
+        {
+            let mut log = self.inner.log.lock();
+            log.push_back(delivery.clone());
+            while log.len() > MAX_LOG_ENTRIES {
+                log.pop_front();
+            }
+        }
+        delivery
+    }
+}
+
+/// Event used by `test_webhook`, so a receiver can be checked without waiting for an agent
+pub fn sample_candidate(trigger: RuleTrigger, directory: String) -> NotificationCandidate {
+    NotificationCandidate {
+        trigger,
+        directory,
+        session_id: Some("test-session".to_string()),
+        agent: Some("build".to_string()),
+        model: None,
+        busy_minutes: None,
+        title: "OpenChamber test event".to_string(),
+        body: "Webhook delivery test".to_string(),
+    }
+}
+
+fn build_request(
+    webhook: &WebhookConfig,
+    candidate: &NotificationCandidate,
+) -> Result<(HeaderMap, String)> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    for (name, value) in &webhook.headers {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| anyhow!("Invalid header name: {name}"))?;
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|_| anyhow!("Invalid value for header {name}"))?;
+        headers.insert(name, value);
+    }
+
+    let variables = template_variables(candidate);
+    let body = match webhook
+        .template
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        Some(template) => {
+            let mut document: Value = serde_json::from_str(template)
+                .map_err(|err| anyhow!("Template is not valid JSON: {err}"))?;
+            fill_template(&mut document, &variables);
+            document
+        }
+        None => json!(variables),
+    };
+    Ok((headers, body.to_string()))
+}
+
+fn template_variables(candidate: &NotificationCandidate) -> HashMap<&'static str, String> {
+    let event = serde_json::to_value(candidate.trigger)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    HashMap::from([
+        ("event", event),
+        ("title", candidate.title.clone()),
+        ("body", candidate.body.clone()),
+        ("directory", candidate.directory.clone()),
+        (
+            "sessionId",
+            candidate.session_id.clone().unwrap_or_default(),
+        ),
+        ("agent", candidate.agent.clone().unwrap_or_default()),
+        ("model", candidate.model.clone().unwrap_or_default()),
+        ("timestamp", Utc::now().to_rfc3339()),
+    ])
+}
+
+/// Substitute `{{name}}` inside every string of the template; serde takes care of escaping
+fn fill_template(value: &mut Value, variables: &HashMap<&'static str, String>) {
+    match value {
+        Value::String(text) => {
+            for (name, replacement) in variables {
+                let placeholder = format!("{{{{{name}}}}}");
+                if text.contains(&placeholder) {
+                    *text = text.replace(&placeholder, replacement);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| fill_template(item, variables)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|item| fill_template(item, variables)),
+        _ => {}
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::post, Router};
+
+    /// Local receiver answering with `statuses` in order (200 once they run out)
+    #[derive(Clone, Default)]
+    struct Receiver {
+        statuses: Arc<Mutex<VecDeque<u16>>>,
+        received: Arc<Mutex<Vec<(axum::http::HeaderMap, String)>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: axum::http::HeaderMap,
+        body: String,
+    ) -> (StatusCode, String) {
+        receiver.received.lock().push((headers, body));
+        let status = receiver.statuses.lock().pop_front().unwrap_or(200);
+        let status = StatusCode::from_u16(status).unwrap();
+        (status, format!("answered {}", status.as_u16()))
+    }
+
+    async fn spawn_receiver(statuses: &[u16]) -> (Receiver, String) {
+        let receiver = Receiver::default();
+        receiver.statuses.lock().extend(statuses);
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (receiver, url)
+    }
+
+    fn webhook(url: &str) -> WebhookConfig {
+        WebhookConfig {
+            id: "hook".to_string(),
+            name: "Test".to_string(),
+            url: url.to_string(),
+            enabled: true,
+            headers: HashMap::new(),
+            events: Vec::new(),
+            template: None,
+        }
+    }
+
+    fn candidate() -> NotificationCandidate {
+        sample_candidate(RuleTrigger::AssistantReady, "/tmp/project".to_string())
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_and_server_errors() {
+        let (receiver, url) = spawn_receiver(&[503, 429]).await;
+        let dispatcher = WebhookDispatcher::new().unwrap();
+
+        let delivery = dispatcher.deliver(&webhook(&url), &candidate()).await;
+        assert!(delivery.ok);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status, Some(200));
+        assert_eq!(delivery.error, None);
+        assert_eq!(delivery.response.as_deref(), Some("answered 200"));
+        assert_eq!(receiver.received.lock().len(), 3);
+
+        let log = dispatcher.deliveries();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].id, delivery.id);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_and_skips_retry_on_client_errors() {
+        let (receiver, url) = spawn_receiver(&[500, 500, 500]).await;
+        let dispatcher = WebhookDispatcher::new().unwrap();
+
+        let failed = dispatcher.deliver(&webhook(&url), &candidate()).await;
+        assert!(!failed.ok);
+        assert_eq!(failed.attempts, MAX_ATTEMPTS);
+        assert_eq!(failed.status, Some(500));
+        assert!(failed.error.as_deref().unwrap().contains("500"));
+        assert_eq!(receiver.received.lock().len(), MAX_ATTEMPTS as usize);
+
+        receiver.statuses.lock().extend([404, 500]);
+        let rejected = dispatcher.deliver(&webhook(&url), &candidate()).await;
+        assert!(!rejected.ok);
+        assert_eq!(rejected.attempts, 1);
+        assert_eq!(rejected.status, Some(404));
+
+        // Newest first
+        let log = dispatcher.deliveries();
+        assert_eq!(
+            log.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(),
+            vec![rejected.id.as_str(), failed.id.as_str()]
+        );
+    }
+
+    #[tokio::test]
+    async fn network_failures_are_retried_and_logged() {
+        // Nothing listens on this port once the listener is dropped
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+
+        let dispatcher = WebhookDispatcher::new().unwrap();
+        let delivery = dispatcher.deliver(&webhook(&url), &candidate()).await;
+        assert!(!delivery.ok);
+        assert_eq!(delivery.attempts, MAX_ATTEMPTS);
+        assert_eq!(delivery.status, None);
+        assert!(delivery.error.is_some());
+        assert_eq!(dispatcher.deliveries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sends_headers_and_filled_template() {
+        let (receiver, url) = spawn_receiver(&[]).await;
+        let mut hook = webhook(&url);
+        hook.headers
+            .insert("X-Token".to_string(), " secret ".to_string());
+        hook.template = Some(r#"{"text": "{{title}} in {{directory}}", "n": 1}"#.to_string());
+
+        let delivery = WebhookDispatcher::new()
+            .unwrap()
+            .deliver(&hook, &candidate())
+            .await;
+        assert!(delivery.ok);
+
+        let received = receiver.received.lock();
+        let (headers, body) = &received[0];
+        assert_eq!(headers["x-token"], "secret");
+        assert_eq!(headers["content-type"], "application/json");
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            body,
+            json!({ "text": "OpenChamber test event in /tmp/project", "n": 1 })
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_templates_fail_without_sending() {
+        let (receiver, url) = spawn_receiver(&[]).await;
+        let mut hook = webhook(&url);
+        hook.template = Some("{not json".to_string());
+
+        let delivery = WebhookDispatcher::new()
+            .unwrap()
+            .deliver(&hook, &candidate())
+            .await;
+        assert!(!delivery.ok);
+        assert_eq!(delivery.attempts, 0);
+        assert!(delivery.error.unwrap().contains("Template"));
+        assert!(receiver.received.lock().is_empty());
+    }
+}
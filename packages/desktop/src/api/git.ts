@@ -163,6 +163,7 @@ export const createDesktopGitAPI = (): GitAPI => ({
 			directory,
 			pathStr: payload.path,
 			force: payload.force,
+			closeTerminals: payload.closeTerminals,
 		});
 		return { success: true };
 	},
@@ -1,11 +1,21 @@
 import type {
 	CreateTerminalOptions,
 	ResizeTerminalPayload,
+	RunTerminalCommandOptions,
 	TerminalAPI,
+	TerminalCommandResult,
 	TerminalHandlers,
+	TerminalProcessList,
+	TerminalProfileInfo,
+	TerminalRecordingInfo,
 	TerminalSession,
+	TerminalSessionInfo,
+	TerminalShellState,
+	TerminalSignal,
 	TerminalStreamEvent,
+	TerminalStreamOptions,
 } from "@openchamber/ui/lib/api/types";
+import { Channel } from "@tauri-apps/api/core";
 import { safeInvoke, safeListen } from "../lib/tauriCallbackManager";
 
 async function safeTerminalInvoke<T>(
@@ -28,6 +38,53 @@ async function safeTerminalInvoke<T>(
 	}
 }
 
+async function fetchCommandResult(
+	sessionId: string,
+): Promise<TerminalCommandResult | null> {
+	const result = await safeTerminalInvoke<{
+		command: string;
+		exit_code: number;
+		signal: string | null;
+		duration_ms: number;
+		output_tail: string;
+	} | null>("get_terminal_command_result", {
+		sessionId,
+		session_id: sessionId,
+	});
+
+	return result
+		? {
+				command: result.command,
+				exitCode: result.exit_code,
+				signal: result.signal,
+				durationMs: result.duration_ms,
+				outputTail: result.output_tail,
+			}
+		: null;
+}
+
+type RawRecordingInfo = {
+	id: string;
+	path: string;
+	title: string | null;
+	width: number;
+	height: number;
+	started_at: string;
+	duration_secs: number;
+	size_bytes: number;
+};
+
+const mapRecording = (recording: RawRecordingInfo): TerminalRecordingInfo => ({
+	id: recording.id,
+	path: recording.path,
+	title: recording.title,
+	width: recording.width,
+	height: recording.height,
+	startedAt: recording.started_at,
+	durationSecs: recording.duration_secs,
+	sizeBytes: recording.size_bytes,
+});
+
 export const createDesktopTerminalAPI = (): TerminalAPI => ({
 	async createSession(
 		options: CreateTerminalOptions,
@@ -42,6 +99,9 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 					cols,
 					rows,
 					cwd: options.cwd,
+					detached: options.detached,
+					profile: options.profile,
+					shell_profile: options.shellProfile,
 				},
 			},
 		);
@@ -53,10 +113,17 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 		};
 	},
 
-	connect(sessionId: string, handlers: TerminalHandlers) {
+	connect(
+		sessionId: string,
+		handlers: TerminalHandlers,
+		options?: TerminalStreamOptions,
+	) {
 		let unlistenFn: (() => void) | undefined;
 		let cancelled = false;
 		let isConnected = false;
+		// Events arriving before the attach reply are held so the replay stays in order
+		let pending: TerminalStreamEvent[] | null = [];
+		let replayedOffset = -1;
 
 		const stopListening = () => {
 			if (unlistenFn) {
@@ -66,21 +133,57 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 			}
 		};
 
+		const deliver = (payload: TerminalStreamEvent) => {
+			if (cancelled) {
+				return;
+			}
+			if (
+				payload?.type === "data" &&
+				typeof payload.offset === "number" &&
+				payload.offset <= replayedOffset
+			) {
+				return;
+			}
+
+			handlers.onEvent(payload);
+
+			if (payload?.type === "exit") {
+				stopListening();
+			}
+		};
+
+		const receive = (payload: TerminalStreamEvent) => {
+			if (pending) {
+				pending.push(payload);
+			} else {
+				deliver(payload);
+			}
+		};
+
+		// In binary mode output arrives as raw bytes; the decoder keeps characters split across messages whole
+		let channel: Channel<ArrayBuffer | TerminalStreamEvent> | undefined;
+		if (options?.binary) {
+			const decoder = new TextDecoder();
+			channel = new Channel<ArrayBuffer | TerminalStreamEvent>();
+			channel.onmessage = (message) => {
+				if (message instanceof ArrayBuffer) {
+					const data = decoder.decode(new Uint8Array(message), {
+						stream: true,
+					});
+					if (data) {
+						receive({ type: "data", data });
+					}
+				} else {
+					receive(message);
+				}
+			};
+		}
+
 		const startListening = async () => {
 			try {
 				const unlisten = await safeListen<TerminalStreamEvent>(
 					`terminal://${sessionId}`,
-					(event) => {
-						if (cancelled) {
-							return;
-						}
-
-						handlers.onEvent(event.payload);
-
-						if (event.payload?.type === "exit") {
-							stopListening();
-						}
-					},
+					(event) => receive(event.payload),
 				);
 
 				if (cancelled) {
@@ -91,6 +194,49 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 				unlistenFn = unlisten;
 				isConnected = true;
 				handlers.onEvent({ type: "connected" });
+
+				// Route the session's output to this window and pick up what it printed so far
+				try {
+					const attached = await safeTerminalInvoke<{
+						scrollback: string;
+						offset: number;
+					}>(
+						channel ? "attach_terminal_channel" : "attach_terminal_session",
+						{
+							sessionId,
+							session_id: sessionId,
+							...(channel ? { channel } : {}),
+						},
+					);
+					if (options?.replayScrollback) {
+						if (attached.scrollback && !cancelled) {
+							handlers.onEvent({
+								type: "data",
+								data: attached.scrollback,
+								offset: attached.offset,
+							});
+						}
+						replayedOffset = attached.offset;
+					}
+				} catch (err) {
+					// A short command run may have exited before we started listening
+					const result = await fetchCommandResult(sessionId).catch(() => null);
+					if (result) {
+						pending?.push({
+							type: "exit",
+							exitCode: result.exitCode,
+							command: result.command,
+							durationMs: result.durationMs,
+							outputTail: result.outputTail,
+						});
+					} else {
+						console.warn("Failed to attach terminal session:", err);
+					}
+				}
+
+				const queued = pending ?? [];
+				pending = null;
+				queued.forEach(deliver);
 			} catch (err) {
 				console.error("Failed to listen to terminal events:", err);
 				if (!cancelled) {
@@ -129,10 +275,11 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 		});
 	},
 
-	async close(sessionId: string): Promise<void> {
+	async close(sessionId: string, options?: { force?: boolean }): Promise<void> {
 		await safeTerminalInvoke("close_terminal", {
 			sessionId,
 			session_id: sessionId,
+			force: options?.force ?? false,
 		});
 	},
 
@@ -162,6 +309,173 @@ export const createDesktopTerminalAPI = (): TerminalAPI => ({
 		};
 	},
 
+	async listSessions(project?: string): Promise<TerminalSessionInfo[]> {
+		const sessions = await safeTerminalInvoke<
+			Array<{
+				session_id: string;
+				cwd: string;
+				project: string;
+				worktree: boolean;
+				cols: number;
+				rows: number;
+				created_at: string;
+				detached: boolean;
+				pid: number | null;
+				agent_access: boolean;
+				command: string | null;
+				profile: string | null;
+				recording: string | null;
+			}>
+		>("list_terminal_sessions", { project: project ?? null });
+
+		return sessions.map((session) => ({
+			sessionId: session.session_id,
+			cwd: session.cwd,
+			project: session.project,
+			worktree: session.worktree,
+			cols: session.cols,
+			rows: session.rows,
+			createdAt: session.created_at,
+			detached: session.detached,
+			pid: session.pid ?? undefined,
+			agentAccess: session.agent_access,
+			command: session.command ?? undefined,
+			profile: session.profile ?? undefined,
+			recording: session.recording ?? undefined,
+		}));
+	},
+
+	async runCommand(
+		options: RunTerminalCommandOptions,
+	): Promise<TerminalSession> {
+		const cols = options.cols ?? 80;
+		const rows = options.rows ?? 24;
+
+		const res = await safeTerminalInvoke<{ session_id: string }>(
+			"run_terminal_command",
+			{
+				payload: {
+					command: options.command,
+					cols,
+					rows,
+					cwd: options.cwd,
+					tail_lines: options.tailLines,
+					profile: options.profile,
+				},
+			},
+		);
+
+		return {
+			sessionId: res.session_id,
+			cols,
+			rows,
+		};
+	},
+
+	getCommandResult: fetchCommandResult,
+
+	async listProfiles(): Promise<TerminalProfileInfo[]> {
+		const profiles = await safeTerminalInvoke<
+			Array<{
+				name: string;
+				shell: string | null;
+				builtin: boolean;
+				is_default: boolean;
+			}>
+		>("list_terminal_profiles");
+
+		return profiles.map((profile) => ({
+			name: profile.name,
+			shell: profile.shell,
+			builtin: profile.builtin,
+			isDefault: profile.is_default,
+		}));
+	},
+
+	async startRecording(
+		sessionId: string,
+		title?: string,
+	): Promise<TerminalRecordingInfo> {
+		const recording = await safeTerminalInvoke<RawRecordingInfo>(
+			"start_terminal_recording",
+			{ sessionId, session_id: sessionId, title: title ?? null },
+		);
+		return mapRecording(recording);
+	},
+
+	async stopRecording(sessionId: string): Promise<TerminalRecordingInfo> {
+		const recording = await safeTerminalInvoke<RawRecordingInfo>(
+			"stop_terminal_recording",
+			{ sessionId, session_id: sessionId },
+		);
+		return mapRecording(recording);
+	},
+
+	async listRecordings(): Promise<TerminalRecordingInfo[]> {
+		const recordings = await safeTerminalInvoke<RawRecordingInfo[]>(
+			"list_terminal_recordings",
+		);
+		return recordings.map(mapRecording);
+	},
+
+	async exportRecordingText(recordingId: string): Promise<string> {
+		return safeTerminalInvoke<string>("export_terminal_recording_text", {
+			recordingId,
+			recording_id: recordingId,
+		});
+	},
+
+	async getCwd(sessionId: string): Promise<TerminalShellState> {
+		const state = await safeTerminalInvoke<{
+			cwd: string;
+			reported: boolean;
+			command_running: boolean;
+			last_exit_code: number | null;
+		}>("get_terminal_cwd", { sessionId, session_id: sessionId });
+
+		return {
+			cwd: state.cwd,
+			reported: state.reported,
+			commandRunning: state.command_running,
+			lastExitCode: state.last_exit_code,
+		};
+	},
+
+	async listProcesses(sessionId: string): Promise<TerminalProcessList> {
+		const list = await safeTerminalInvoke<{
+			shell_pid: number;
+			processes: TerminalProcessList["processes"];
+			running_jobs: number;
+		}>("list_terminal_processes", { sessionId, session_id: sessionId });
+
+		return {
+			shellPid: list.shell_pid,
+			processes: list.processes,
+			runningJobs: list.running_jobs,
+		};
+	},
+
+	async signalProcess(
+		sessionId: string,
+		pid: number,
+		signal: TerminalSignal,
+	): Promise<void> {
+		await safeTerminalInvoke("signal_terminal_process", {
+			sessionId,
+			session_id: sessionId,
+			pid,
+			signal,
+		});
+	},
+
+	async setAgentAccess(sessionId: string, enabled: boolean): Promise<void> {
+		await safeTerminalInvoke("set_terminal_agent_access", {
+			sessionId,
+			session_id: sessionId,
+			enabled,
+		});
+	},
+
 	async forceKill(options: {
 		sessionId?: string;
 		cwd?: string;
@@ -2,6 +2,7 @@ import { cleanupAllTauriCallbacks, safeInvoke } from "./tauriCallbackManager";
 
 type ServerInfo = {
 	server_port: number;
+	auth_token: string;
 	opencode_port?: number | null;
 	api_prefix?: string | null;
 	cli_available?: boolean;
@@ -48,8 +49,9 @@ async function setupBridge(): Promise<void> {
 			cliAvailable: info.cli_available ?? false,
 		};
 
-		patchFetch(origin);
-		patchEventSource(origin);
+		patchFetch(origin, info.auth_token);
+		patchEventSource(origin, info.auth_token);
+		patchWebSocket(origin, info.auth_token);
 
 		const cleanupDevtools = registerDevtoolsShortcut();
 
@@ -86,9 +88,28 @@ async function setupBridge(): Promise<void> {
 	}
 }
 
-function patchFetch(origin: string) {
+const isServerUrl = (origin: string, value: string): boolean =>
+	value === origin || value.startsWith(`${origin}/`);
+
+/** Query parameter the server accepts in place of the Authorization header */
+const TOKEN_QUERY_PARAM = "oc_token";
+
+// EventSource and WebSocket cannot send headers, so the token rides in the query
+const withTokenQuery = (target: string, token: string): string => {
+	const url = new URL(target);
+	url.searchParams.set(TOKEN_QUERY_PARAM, token);
+	return url.toString();
+};
+
+function patchFetch(origin: string, token: string) {
 	const originalFetch = window.fetch.bind(window);
 
+	const withAuth = (init: RequestInit | undefined, base?: Headers) => {
+		const headers = new Headers(init?.headers ?? base);
+		headers.set("Authorization", `Bearer ${token}`);
+		return { ...init, headers };
+	};
+
 	const rewrite = (value: string): string => {
 		if (value.startsWith("http://") || value.startsWith("https://")) {
 			return value;
@@ -104,27 +125,36 @@ function patchFetch(origin: string) {
 
 	window.fetch = (input: RequestInfo | URL, init?: RequestInit) => {
 		if (typeof input === "string") {
-			return originalFetch(rewrite(input), init);
+			const rewritten = rewrite(input);
+			return originalFetch(
+				rewritten,
+				isServerUrl(origin, rewritten) ? withAuth(init) : init,
+			);
 		}
 
 		if (input instanceof Request) {
 			const rewritten = rewrite(input.url);
-			if (rewritten === input.url) {
+			if (!isServerUrl(origin, rewritten)) {
 				return originalFetch(input, init);
 			}
-			const cloned = new Request(rewritten, input);
-			return originalFetch(cloned, init);
+			const cloned =
+				rewritten === input.url ? input : new Request(rewritten, input);
+			return originalFetch(cloned, withAuth(init, cloned.headers));
 		}
 
 		if (input instanceof URL) {
-			return originalFetch(rewrite(input.toString()), init);
+			const rewritten = rewrite(input.toString());
+			return originalFetch(
+				rewritten,
+				isServerUrl(origin, rewritten) ? withAuth(init) : init,
+			);
 		}
 
 		return originalFetch(input, init);
 	};
 }
 
-function patchEventSource(origin: string) {
+function patchEventSource(origin: string, token: string) {
 	if (typeof window.EventSource === "undefined") {
 		return;
 	}
@@ -134,10 +164,13 @@ function patchEventSource(origin: string) {
 	class DesktopEventSource extends OriginalEventSource {
 		constructor(url: string | URL, eventSourceInit?: EventSourceInit) {
 			const normalized = typeof url === "string" ? url : url.toString();
-			super(
-				normalized.startsWith("/") ? `${origin}${normalized}` : normalized,
-				eventSourceInit,
-			);
+			const absolute = normalized.startsWith("/")
+				? `${origin}${normalized}`
+				: normalized;
+			const target = isServerUrl(origin, absolute)
+				? withTokenQuery(absolute, token)
+				: absolute;
+			super(target, eventSourceInit);
 		}
 	}
 
@@ -153,6 +186,39 @@ function patchEventSource(origin: string) {
 	window.EventSource = DesktopEventSource as unknown as typeof EventSource;
 }
 
+function patchWebSocket(origin: string, token: string) {
+	if (typeof window.WebSocket === "undefined") {
+		return;
+	}
+
+	const OriginalWebSocket = window.WebSocket;
+	const wsOrigin = origin.replace(/^http/, "ws");
+
+	class DesktopWebSocket extends OriginalWebSocket {
+		constructor(url: string | URL, protocols?: string | string[]) {
+			const normalized = typeof url === "string" ? url : url.toString();
+			const absolute = normalized.startsWith("/")
+				? `${wsOrigin}${normalized}`
+				: normalized;
+			const target = isServerUrl(wsOrigin, absolute)
+				? withTokenQuery(absolute, token)
+				: absolute;
+			super(target, protocols);
+		}
+	}
+
+	Object.defineProperty(DesktopWebSocket, "name", {
+		value: "DesktopWebSocket",
+	});
+	Object.setPrototypeOf(
+		DesktopWebSocket.prototype,
+		OriginalWebSocket.prototype,
+	);
+	Object.setPrototypeOf(DesktopWebSocket, OriginalWebSocket);
+
+	window.WebSocket = DesktopWebSocket as unknown as typeof WebSocket;
+}
+
 function registerDevtoolsShortcut() {
 	const handler = (event: KeyboardEvent) => {
 		const key = event.key?.toLowerCase();
@@ -47,6 +47,7 @@ declare global {
 
 const CHECK_FOR_UPDATES_EVENT = 'openchamber:check-for-updates';
 const MENU_ACTION_EVENT = 'openchamber:menu-action';
+const SWITCH_DIRECTORY_EVENT = 'openchamber:switch-directory';
 
 const cleanupFunctions: Array<() => void | Promise<void>> = [];
 
@@ -68,6 +69,11 @@ try {
   });
   cleanupFunctions.push(() => menuActionUnlisten());
 
+  const switchDirectoryUnlisten = await listen<string>(SWITCH_DIRECTORY_EVENT, (event) => {
+    window.dispatchEvent(new CustomEvent(SWITCH_DIRECTORY_EVENT, { detail: event.payload }));
+  });
+  cleanupFunctions.push(() => switchDirectoryUnlisten());
+
   requestInitialNotificationPermission().catch(err => {
     console.error('[main] Failed to request notification permission:', err);
   });
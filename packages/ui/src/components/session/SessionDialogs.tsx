@@ -544,7 +544,7 @@ export const SessionDialogs: React.FC = () => {
 
             const session = await createSession(undefined, metadata.path);
             if (!session) {
-                await removeWorktree({ projectDirectory, path: metadata.path, force: true }).catch(() => undefined);
+                await removeWorktree({ projectDirectory, path: metadata.path, force: true, closeTerminals: true }).catch(() => undefined);
                 const message = 'Failed to create session for worktree';
                 setWorktreeError(message);
                 toast.error(message);
@@ -561,7 +561,7 @@ export const SessionDialogs: React.FC = () => {
             toast.success('Worktree created');
         } catch (error) {
             if (cleanupMetadata) {
-                await removeWorktree({ projectDirectory, path: cleanupMetadata.path, force: true }).catch(() => undefined);
+                await removeWorktree({ projectDirectory, path: cleanupMetadata.path, force: true, closeTerminals: true }).catch(() => undefined);
             }
             const message = error instanceof Error ? error.message : 'Failed to create worktree';
             setWorktreeError(message);
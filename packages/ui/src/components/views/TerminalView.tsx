@@ -1,5 +1,5 @@
 import React from 'react';
-import { RiAlertLine, RiArrowDownLine, RiArrowGoBackLine, RiArrowLeftLine, RiArrowRightLine, RiArrowUpLine, RiCheckboxCircleLine, RiCircleLine, RiCloseLine, RiCommandLine, RiDeleteBinLine, RiRestartLine } from '@remixicon/react';
+import { RiAlertLine, RiArrowDownLine, RiArrowGoBackLine, RiArrowLeftLine, RiArrowRightLine, RiArrowUpLine, RiCheckboxCircleLine, RiCircleLine, RiCloseLine, RiCommandLine, RiDeleteBinLine, RiRestartLine, RiRobot2Line } from '@remixicon/react';
 
 import { useSessionStore } from '@/stores/useSessionStore';
 import { useDirectoryStore } from '@/stores/useDirectoryStore';
@@ -129,6 +129,7 @@ export const TerminalView: React.FC = () => {
     const [isFatalError, setIsFatalError] = React.useState(false);
     const [activeModifier, setActiveModifier] = React.useState<Modifier | null>(null);
     const [isRestarting, setIsRestarting] = React.useState(false);
+    const [agentAccess, setAgentAccess] = React.useState(false);
 
     const streamCleanupRef = React.useRef<(() => void) | null>(null);
     const activeTerminalIdRef = React.useRef<string | null>(null);
@@ -147,6 +148,21 @@ export const TerminalView: React.FC = () => {
         directoryRef.current = effectiveDirectory;
     }, [effectiveDirectory]);
 
+    React.useEffect(() => {
+        setAgentAccess(false);
+        if (!terminalSessionId || !terminal.setAgentAccess || !terminal.listSessions) return;
+        let cancelled = false;
+        terminal.listSessions()
+            .then((sessions) => {
+                const match = sessions.find((session) => session.sessionId === terminalSessionId);
+                if (!cancelled) setAgentAccess(Boolean(match?.agentAccess));
+            })
+            .catch(() => { /* ignored */ });
+        return () => {
+            cancelled = true;
+        };
+    }, [terminal, terminalSessionId]);
+
     React.useEffect(() => {
         if (!isMobile && activeModifier !== null) {
             setActiveModifier(null);
@@ -181,6 +197,12 @@ export const TerminalView: React.FC = () => {
 
             disconnectStream();
 
+            // A fresh buffer means this view has not seen the session's output yet
+            const currentDirectory = directoryRef.current;
+            const existingBuffer = currentDirectory
+                ? useTerminalStore.getState().getTerminalSession(currentDirectory)?.bufferLength ?? 0
+                : 0;
+
             const subscription = terminal.connect(
                 terminalId,
                 {
@@ -246,7 +268,7 @@ export const TerminalView: React.FC = () => {
                         }
                     },
                 },
-                STREAM_OPTIONS
+                { ...STREAM_OPTIONS, replayScrollback: existingBuffer === 0 }
             );
 
             streamCleanupRef.current = () => {
@@ -278,6 +300,31 @@ export const TerminalView: React.FC = () => {
 
             let terminalId = currentState?.terminalSessionId ?? null;
 
+            // After a webview reload or a project switch the shell is still running in the desktop runtime
+            if (!terminalId && terminal.listSessions) {
+                try {
+                    const live = await terminal.listSessions(directory);
+                    const match =
+                        live.find((session) => session.cwd === directory) ??
+                        live.find((session) => session.project === directory);
+                    if (cancelled) return;
+                    if (match) {
+                        setTerminalSession(directory, match);
+                        terminalId = match.sessionId;
+                    }
+                    // Restore the project's other terminals under their own directories, so
+                    // switching to a subdirectory or worktree reattaches instead of starting a shell
+                    const store = useTerminalStore.getState();
+                    const claimed = new Set<string>([directory]);
+                    for (const session of live) {
+                        if (session === match || claimed.has(session.cwd)) continue;
+                        claimed.add(session.cwd);
+                        if (store.getTerminalSession(session.cwd)?.terminalSessionId) continue;
+                        setTerminalSession(session.cwd, session);
+                    }
+                } catch { /* ignored */ }
+            }
+
             if (!terminalId) {
                 setConnectionError(null);
                 setIsFatalError(false);
@@ -288,7 +335,7 @@ export const TerminalView: React.FC = () => {
                     });
                     if (cancelled) {
                         try {
-                            await terminal.close(session.sessionId);
+                            await terminal.close(session.sessionId, { force: true });
                         } catch { /* ignored */ }
                         return;
                     }
@@ -333,9 +380,27 @@ export const TerminalView: React.FC = () => {
         terminal,
     ]);
 
+    // Restarting ends whatever the shell is running, so ask first when it has jobs
+    const confirmDiscardJobs = React.useCallback(async (sessionId: string | null) => {
+        if (!sessionId || !terminal.listProcesses) return true;
+        try {
+            const { processes, runningJobs } = await terminal.listProcesses(sessionId);
+            if (runningJobs === 0) return true;
+            const names = Array.from(new Set(
+                processes.filter((process) => process.depth > 0 && process.state !== 'Z').map((process) => process.name)
+            ));
+            return window.confirm(
+                `This terminal is still running ${names.join(', ')}.\n\nRestarting will stop ${runningJobs === 1 ? 'it' : 'them'}. Continue?`
+            );
+        } catch {
+            return true;
+        }
+    }, [terminal]);
+
     const handleRestart = React.useCallback(async () => {
         if (!effectiveDirectory) return;
         if (isRestarting) return;
+        if (!(await confirmDiscardJobs(terminalIdRef.current))) return;
 
         setIsRestarting(true);
         setConnectionError(null);
@@ -355,7 +420,7 @@ export const TerminalView: React.FC = () => {
             } else {
                 if (currentTerminalId) {
                     try {
-                        await terminal.close(currentTerminalId);
+                        await terminal.close(currentTerminalId, { force: true });
                     } catch { /* ignored */ }
                 }
                 removeTerminalSession(effectiveDirectory);
@@ -368,11 +433,12 @@ export const TerminalView: React.FC = () => {
         } finally {
             setIsRestarting(false);
         }
-    }, [effectiveDirectory, isRestarting, disconnectStream, terminal, setTerminalSession, startStream, removeTerminalSession]);
+    }, [effectiveDirectory, isRestarting, confirmDiscardJobs, disconnectStream, terminal, setTerminalSession, startStream, removeTerminalSession]);
 
     const handleHardRestart = React.useCallback(async () => {
         if (!effectiveDirectory) return;
         if (isRestarting) return;
+        if (!(await confirmDiscardJobs(terminalIdRef.current))) return;
 
         setIsRestarting(true);
         setConnectionError(null);
@@ -408,7 +474,18 @@ export const TerminalView: React.FC = () => {
         } finally {
             setIsRestarting(false);
         }
-    }, [effectiveDirectory, isRestarting, disconnectStream, terminal, removeTerminalSession, clearBuffer, setConnecting, setTerminalSession, startStream]);
+    }, [effectiveDirectory, isRestarting, confirmDiscardJobs, disconnectStream, terminal, removeTerminalSession, clearBuffer, setConnecting, setTerminalSession, startStream]);
+
+    const handleToggleAgentAccess = React.useCallback(async () => {
+        if (!terminalSessionId || !terminal.setAgentAccess) return;
+        const next = !agentAccess;
+        try {
+            await terminal.setAgentAccess(terminalSessionId, next);
+            setAgentAccess(next);
+        } catch (error) {
+            setConnectionError(error instanceof Error ? error.message : 'Failed to change agent access');
+        }
+    }, [agentAccess, terminal, terminalSessionId]);
 
     const handleClear = React.useCallback(() => {
         if (!effectiveDirectory) return;
@@ -671,6 +748,21 @@ export const TerminalView: React.FC = () => {
                     </div>
                     <div className="flex items-center gap-2">
                         {statusIcon}
+                        {terminal.setAgentAccess ? (
+                            <Button
+                                size="sm"
+                                variant={agentAccess ? 'default' : 'outline'}
+                                className="h-7 px-2 py-0"
+                                onClick={handleToggleAgentAccess}
+                                disabled={!terminalSessionId}
+                                title={agentAccess ? 'Stop sharing this terminal with agents' : 'Let agents read and type into this terminal'}
+                                aria-pressed={agentAccess}
+                                type="button"
+                            >
+                                <RiRobot2Line size={16} />
+                                {agentAccess ? 'Shared' : 'Share'}
+                            </Button>
+                        ) : null}
                         <Button
                             size="sm"
                             variant="default"
@@ -9,6 +9,7 @@ import { sessionEvents } from '@/lib/sessionEvents';
 import { isDesktopRuntime } from '@/lib/desktop';
 
 const MENU_ACTION_EVENT = 'openchamber:menu-action';
+const SWITCH_DIRECTORY_EVENT = 'openchamber:switch-directory';
 
 type MenuAction =
   | 'about'
@@ -193,4 +194,17 @@ export const useMenuActions = (
     onToggleMemoryDebug,
     handleChangeWorkspace,
   ]);
+
+  // Recent projects picked from the desktop tray
+  React.useEffect(() => {
+    const handleSwitchDirectory = (event: Event) => {
+      const path = (event as CustomEvent<string>).detail;
+      if (typeof path === 'string' && path.length > 0) {
+        setDirectory(path, { showOverlay: true });
+      }
+    };
+
+    window.addEventListener(SWITCH_DIRECTORY_EVENT, handleSwitchDirectory);
+    return () => window.removeEventListener(SWITCH_DIRECTORY_EVENT, handleSwitchDirectory);
+  }, [setDirectory]);
 };
@@ -36,12 +36,21 @@ export interface TerminalSession {
 }
 
 export interface TerminalStreamEvent {
-  type: 'connected' | 'data' | 'exit' | 'reconnecting';
+  type: 'connected' | 'data' | 'exit' | 'reconnecting' | 'cwd' | 'command-start' | 'command-finish';
   data?: string;
-  exitCode?: number;
+  /** Null on a command-finish event when the shell did not report a status */
+  exitCode?: number | null;
   signal?: number | null;
   attempt?: number;
   maxAttempts?: number;
+  /** Total output bytes produced by the session up to and including this event */
+  offset?: number;
+  /** Set on the exit event of a command run */
+  command?: string;
+  durationMs?: number;
+  outputTail?: string;
+  /** Set on cwd events from shell integration */
+  cwd?: string;
 
   runtime?: 'node' | 'bun';
   ptyBackend?: string;
@@ -51,11 +60,124 @@ export interface CreateTerminalOptions {
   cwd: string;
   cols?: number;
   rows?: number;
+  /** Host the shell in the session daemon so it outlives the app; defaults to the persistentTerminals setting */
+  detached?: boolean;
+  /** Named shell profile; defaults to the defaultTerminalProfile setting */
+  profile?: string;
+  /** One-off shell profile that takes precedence over `profile` */
+  shellProfile?: TerminalShellProfile;
+}
+
+/** Same shape as the entries of the terminalProfiles setting */
+export interface TerminalShellProfile {
+  name: string;
+  shell?: string;
+  args?: string[];
+  login?: boolean;
+  env?: Record<string, string>;
+  initialCommand?: string;
+}
+
+export interface TerminalProfileInfo {
+  name: string;
+  shell: string | null;
+  /** Installed shell offered without configuration */
+  builtin: boolean;
+  isDefault: boolean;
+}
+
+export interface RunTerminalCommandOptions {
+  command: string;
+  cwd: string;
+  cols?: number;
+  rows?: number;
+  /** Lines of output kept in the result; defaults to 100 */
+  tailLines?: number;
+  /** Shell profile to run the command with; its initial command is skipped */
+  profile?: string;
+}
+
+export interface TerminalShellState {
+  cwd: string;
+  /** False when the shell has no integration and `cwd` is where it started */
+  reported: boolean;
+  commandRunning: boolean;
+  lastExitCode: number | null;
+}
+
+export interface TerminalCommandResult {
+  command: string;
+  exitCode: number;
+  signal: string | null;
+  durationMs: number;
+  /** Last lines of output as plain text */
+  outputTail: string;
 }
 
 export interface TerminalStreamOptions {
   retry?: Partial<RetryPolicy>;
   connectionTimeoutMs?: number;
+  /** Deliver output the session produced before this connection as a first data event */
+  replayScrollback?: boolean;
+  /** Receive output as raw bytes over an IPC channel instead of JSON events; suits high-throughput commands */
+  binary?: boolean;
+}
+
+export interface TerminalSessionInfo {
+  sessionId: string;
+  cwd: string;
+  /** Project directory or git worktree the terminal was opened in */
+  project?: string;
+  /** `project` is a linked git worktree */
+  worktree?: boolean;
+  cols: number;
+  rows: number;
+  createdAt: string;
+  detached?: boolean;
+  /** Shell process id */
+  pid?: number;
+  /** Agents may read and type into this terminal through the local bridge */
+  agentAccess?: boolean;
+  /** Command line of a command run; absent for interactive shells */
+  command?: string;
+  profile?: string;
+  /** Id of the recording in progress */
+  recording?: string;
+}
+
+export interface TerminalProcessInfo {
+  pid: number;
+  ppid: number;
+  name: string;
+  command: string;
+  /** Scheduler state letter such as R, S, T or Z */
+  state: string;
+  /** In the process group that currently owns the terminal */
+  foreground: boolean;
+  /** 0 for the shell, 1 for its children and so on */
+  depth: number;
+}
+
+export interface TerminalProcessList {
+  shellPid: number;
+  /** The shell first, then its descendants in tree order */
+  processes: TerminalProcessInfo[];
+  /** Processes that closing the terminal would end, not counting the shell */
+  runningJobs: number;
+}
+
+export type TerminalSignal = 'SIGINT' | 'SIGTERM' | 'SIGKILL';
+
+export interface TerminalRecordingInfo {
+  id: string;
+  /** asciicast v2 file */
+  path: string;
+  title: string | null;
+  width: number;
+  height: number;
+  startedAt: string;
+  durationSecs: number;
+  sizeBytes: number;
 }
 
 export interface ResizeTerminalPayload {
@@ -79,9 +201,28 @@ export interface TerminalAPI {
   connect(sessionId: string, handlers: TerminalHandlers, options?: TerminalStreamOptions): Subscription;
   sendInput(sessionId: string, input: string): Promise<void>;
   resize(payload: ResizeTerminalPayload): Promise<void>;
-  close(sessionId: string): Promise<void>;
+  /** Fails while the shell is running jobs unless `force` is set */
+  close(sessionId: string, options?: { force?: boolean }): Promise<void>;
   restartSession?(currentSessionId: string, options: CreateTerminalOptions): Promise<TerminalSession>;
   forceKill?(options: ForceKillOptions): Promise<void>;
+  /** Pass a project directory or worktree to list only the terminals opened in it */
+  listSessions?(project?: string): Promise<TerminalSessionInfo[]>;
+  runCommand?(options: RunTerminalCommandOptions): Promise<TerminalSession>;
+  /** Resolves to null while the command is still running */
+  getCommandResult?(sessionId: string): Promise<TerminalCommandResult | null>;
+  listProfiles?(): Promise<TerminalProfileInfo[]>;
+  startRecording?(sessionId: string, title?: string): Promise<TerminalRecordingInfo>;
+  stopRecording?(sessionId: string): Promise<TerminalRecordingInfo>;
+  listRecordings?(): Promise<TerminalRecordingInfo[]>;
+  /** Recording output with ANSI escapes stripped */
+  exportRecordingText?(recordingId: string): Promise<string>;
+  /** Current directory and command state as reported by shell integration */
+  getCwd?(sessionId: string): Promise<TerminalShellState>;
+  listProcesses?(sessionId: string): Promise<TerminalProcessList>;
+  /** Only the shell and its descendants can be signalled */
+  signalProcess?(sessionId: string, pid: number, signal: TerminalSignal): Promise<void>;
+  /** Share the terminal with agents; sharing ends when the app restarts */
+  setAgentAccess?(sessionId: string, enabled: boolean): Promise<void>;
 }
 
 export interface GitStatusFile {
@@ -230,7 +371,10 @@ export interface GitAddWorktreePayload {
 
 export interface GitRemoveWorktreePayload {
   path: string;
+  /** Passed to `git worktree remove --force`, discarding uncommitted changes */
   force?: boolean;
+  /** Close the worktree's terminals even while they are running commands */
+  closeTerminals?: boolean;
 }
 
 export interface GitDeleteBranchPayload {
@@ -1,5 +1,6 @@
 import { addGitWorktree, deleteGitBranch, deleteRemoteBranch, getGitStatus, listGitWorktrees, removeGitWorktree, type GitAddWorktreePayload, type GitWorktreeInfo } from '@/lib/gitApi';
 import { opencodeClient } from '@/lib/opencode/client';
+import { useTerminalStore } from '@/stores/useTerminalStore';
 import type { WorktreeMetadata } from '@/types/worktree';
 
 const WORKTREE_ROOT = '.openchamber';
@@ -66,6 +67,7 @@ export interface RemoveWorktreeOptions {
   projectDirectory: string;
   path: string;
   force?: boolean;
+  closeTerminals?: boolean;
 }
 
 export interface ArchiveWorktreeOptions {
@@ -73,6 +75,7 @@ export interface ArchiveWorktreeOptions {
   path: string;
   branch: string;
   force?: boolean;
+  closeTerminals?: boolean;
   deleteRemote?: boolean;
   remote?: string;
 }
@@ -109,20 +112,27 @@ export async function createWorktree(options: CreateWorktreeOptions): Promise<Wo
   };
 }
 
+// The runtime closes terminals opened in a removed worktree; drop their cached state too
+const forgetWorktreeTerminal = (path: string): void => {
+  useTerminalStore.getState().removeTerminalSession(normalize(path));
+};
+
 export async function removeWorktree(options: RemoveWorktreeOptions): Promise<void> {
-  const { projectDirectory, path, force } = options;
+  const { projectDirectory, path, force, closeTerminals } = options;
   const normalizedProject = normalize(projectDirectory);
-  await removeGitWorktree(normalizedProject, { path, force });
+  await removeGitWorktree(normalizedProject, { path, force, closeTerminals });
+  forgetWorktreeTerminal(path);
 }
 
 export async function archiveWorktree(options: ArchiveWorktreeOptions): Promise<void> {
-  const { projectDirectory, path, branch, force, deleteRemote, remote } = options;
+  const { projectDirectory, path, branch, force, closeTerminals, deleteRemote, remote } = options;
   const normalizedProject = normalize(projectDirectory);
   const normalizedBranch = branch.startsWith('refs/heads/')
     ? branch.substring('refs/heads/'.length)
     : branch;
 
-  await removeGitWorktree(normalizedProject, { path, force });
+  await removeGitWorktree(normalizedProject, { path, force, closeTerminals });
+  forgetWorktreeTerminal(path);
   if (normalizedBranch) {
     await deleteGitBranch(normalizedProject, { branch: normalizedBranch, force: true });
     if (deleteRemote) {
@@ -1,6 +1,6 @@
 import { createOpencodeClient, OpencodeClient } from "@opencode-ai/sdk/v2";
 import type { FilesAPI, RuntimeAPIs } from "../api/types";
-import { getDesktopHomeDirectory } from "../desktop";
+import { getDesktopHomeDirectory, isDesktopRuntime } from "../desktop";
 import type {
   Session,
   Message,
@@ -24,6 +24,8 @@ type StreamEvent<TData> = {
 // Can be overridden with VITE_OPENCODE_URL for absolute URLs in special deployments
 const DEFAULT_BASE_URL = import.meta.env.VITE_OPENCODE_URL || "/api";
 const ABSOLUTE_URL_PATTERN = /^[a-zA-Z][a-zA-Z\d+\-.]*:\/\//;
+// The desktop app keeps one upstream event stream per directory and serves it to the webview here
+const DESKTOP_EVENTS_PATH = "/api/openchamber/events";
 
 const ensureAbsoluteBaseUrl = (candidate: string): string => {
   const normalized = typeof candidate === "string" && candidate.trim().length > 0 ? candidate.trim() : "/api";
@@ -842,6 +844,10 @@ class OpencodeService {
       this.sseAbortController.abort();
     }
 
+    if (isDesktopRuntime()) {
+      return this.subscribeToDesktopEvents(onMessage, onError, onOpen, directoryOverride);
+    }
+
     // Create new AbortController for this subscription
     const abortController = new AbortController();
     this.sseAbortController = abortController;
@@ -954,6 +960,57 @@ class OpencodeService {
     };
   }
 
+  // Reads the desktop hub instead of opening another upstream stream; EventSource reconnects on its own
+  private subscribeToDesktopEvents(
+    onMessage: (event: { type: string; properties?: Record<string, unknown> }) => void,
+    onError?: (error: unknown) => void,
+    onOpen?: () => void,
+    directoryOverride?: string | null
+  ): () => void {
+    const abortController = new AbortController();
+    this.sseAbortController = abortController;
+
+    const resolvedDirectory =
+      typeof directoryOverride === 'string' && directoryOverride.trim().length > 0
+        ? directoryOverride.trim()
+        : this.currentDirectory;
+    const url = resolvedDirectory
+      ? `${DESKTOP_EVENTS_PATH}?${new URLSearchParams({ directory: resolvedDirectory }).toString()}`
+      : DESKTOP_EVENTS_PATH;
+    console.log('[OpencodeClient] Connecting to desktop event hub with directory:', resolvedDirectory);
+
+    const source = new EventSource(url);
+    source.onopen = () => {
+      if (onOpen && !abortController.signal.aborted) {
+        onOpen();
+      }
+    };
+    source.onmessage = (message: MessageEvent<string>) => {
+      if (abortController.signal.aborted) return;
+      try {
+        const payload = JSON.parse(message.data);
+        if (payload && typeof payload === 'object') {
+          onMessage(payload as Event);
+        }
+      } catch (error) {
+        console.warn('[OpencodeClient] Ignoring malformed hub event:', error);
+      }
+    };
+    source.onerror = (error) => {
+      if (onError && !abortController.signal.aborted) {
+        onError(error);
+      }
+    };
+    abortController.signal.addEventListener('abort', () => source.close());
+
+    return () => {
+      if (this.sseAbortController === abortController) {
+        this.sseAbortController = null;
+      }
+      abortController.abort();
+    };
+  }
+
   // File Operations
   async readFile(path: string): Promise<string> {
     try {